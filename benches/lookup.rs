@@ -0,0 +1,74 @@
+//! Lookup cost of the license and module tables.
+//!
+//! Run with `cargo bench --bench lookup`. Each lookup function is compared
+//! against a linear scan of its table (the previous implementation), over
+//! every key in the table plus a few misses.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use distro_spec::shared::licenses::{
+    package_for_binary, package_for_library, BINARY_TO_PACKAGE, LIB_TO_PACKAGE,
+};
+use distro_spec::shared::modules::{module_path, MODULE_PATHS};
+
+const ROUNDS: u32 = 2_000;
+
+fn bench(name: &str, keys: &[String], f: impl Fn(&str) -> Option<&'static str>) -> Duration {
+    // Warm up
+    for key in keys {
+        black_box(f(black_box(key)));
+    }
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for key in keys {
+            black_box(f(black_box(key)));
+        }
+    }
+    let per = start.elapsed() / (ROUNDS * keys.len() as u32);
+    println!("{:<40} {:>8.1?} / lookup", name, per);
+    per
+}
+
+fn keys(pairs: &[(&str, &str)], suffix: &str) -> Vec<String> {
+    let mut keys: Vec<String> = pairs
+        .iter()
+        .map(|(k, _)| format!("{}{}", k, suffix))
+        .collect();
+    keys.extend(["nonexistent", "zzz-missing", "a"].map(String::from));
+    keys
+}
+
+fn linear_exact(pairs: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn linear_prefix(
+    pairs: &'static [(&'static str, &'static str)],
+    key: &str,
+) -> Option<&'static str> {
+    pairs
+        .iter()
+        .find(|(k, _)| key.starts_with(k))
+        .map(|(_, v)| *v)
+}
+
+fn main() {
+    let bins = keys(BINARY_TO_PACKAGE, "");
+    bench("package_for_binary (linear scan)", &bins, |k| {
+        linear_exact(BINARY_TO_PACKAGE, k)
+    });
+    bench("package_for_binary", &bins, package_for_binary);
+
+    let libs = keys(LIB_TO_PACKAGE, ".1");
+    bench("package_for_library (linear scan)", &libs, |k| {
+        linear_prefix(LIB_TO_PACKAGE, k)
+    });
+    bench("package_for_library", &libs, package_for_library);
+
+    let modules = keys(MODULE_PATHS, "");
+    bench("module_path (linear scan)", &modules, |k| {
+        linear_exact(MODULE_PATHS, k)
+    });
+    bench("module_path", &modules, module_path);
+}
@@ -11,6 +11,8 @@ pub use crate::shared::boot::{
     ESP_MOUNT_POINT, LOADER_CONF_PATH, ENTRIES_DIR, DEFAULT_TIMEOUT,
     bootctl_install_command,
 };
+use crate::shared::boot_counting::{EFIVARS_DIR, LOADER_BOOT_COUNT_PATH_VAR};
+use crate::shared::const_check::static_check;
 
 // Re-export shared boot module constants for direct access
 pub use crate::shared::boot_modules::{CORE_BOOT_MODULES, USB_BOOT_MODULES};
@@ -80,6 +82,7 @@ pub const BOOT_MODULES: &[&str] = &[
     "kernel/drivers/hid/hid-generic",
     "kernel/drivers/hid/usbhid/usbhid",
 ];
+static_check!(boot_modules BOOT_MODULES);
 
 // =============================================================================
 // AcornOS-Specific Constructors
@@ -109,3 +112,62 @@ pub fn boot_entry_with_label(label: impl Into<String>) -> BootEntry {
 pub fn default_loader_config() -> LoaderConfig {
     LoaderConfig::with_defaults(OS_ID)
 }
+
+// =============================================================================
+// Boot Assessment
+// =============================================================================
+
+/// OpenRC service that blesses the booted entry (AcornOS has no systemd-bless-boot).
+pub const BLESS_BOOT_SERVICE: &str = "bless-boot";
+
+/// Install path of the bless-boot init script.
+pub const BLESS_BOOT_SCRIPT_PATH: &str = "/etc/init.d/bless-boot";
+
+/// Init script equivalent to `systemd-bless-boot.service`.
+///
+/// Runs after every other service in the default runlevel. If none of them
+/// crashed, it strips the `+LEFT-DONE` counter from the file named by
+/// `LoaderBootCountPath`. Otherwise the counter stays, systemd-boot keeps
+/// counting down, and once it hits zero the previous entry boots instead.
+pub fn bless_boot_init_script() -> String {
+    format!(
+        r#"#!/sbin/openrc-run
+# Boot assessment: mark the systemd-boot entry we booted as good.
+
+description="Mark the current boot entry as good"
+
+depend() {{
+	need localmount
+	after *
+}}
+
+start() {{
+	var="/{efivars}/{var}"
+	[ -r "$var" ] || return 0
+
+	if [ -n "$(rc-status --crashed)" ]; then
+		eerror "Services crashed, leaving boot counter in place"
+		return 1
+	fi
+
+	path=$(tail -c +5 "$var" | tr -d '\000' | tr '\\' '/')
+	dir=${{path%/*}}
+	file=${{path##*/}}
+	blessed=$(printf '%s' "$file" | sed -E 's/\+[0-9]+(-[0-9]+)?(\.[^.]*)$/\2/')
+	[ "$file" != "$blessed" ] || return 0
+
+	ebegin "Blessing boot entry $blessed"
+	mv "{esp}$dir/$file" "{esp}$dir/$blessed" && sync
+	eend $?
+}}
+"#,
+        efivars = EFIVARS_DIR,
+        var = LOADER_BOOT_COUNT_PATH_VAR,
+        esp = ESP_MOUNT_POINT,
+    )
+}
+
+/// Command to enable the bless-boot service in the chroot.
+pub fn bless_boot_enable_command() -> String {
+    format!("rc-update add {} default", BLESS_BOOT_SERVICE)
+}
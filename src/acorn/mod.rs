@@ -15,8 +15,8 @@ pub mod services;
 pub mod uki;
 
 pub use boot::{
-    boot_entry_with_label, boot_entry_with_partuuid, boot_entry_with_root, bootctl_install_command,
-    default_boot_entry, default_loader_config, BootEntry, LoaderConfig, BOOT_MODULES,
+    bless_boot_enable_command, bless_boot_init_script, boot_entry_with_label, boot_entry_with_partuuid, boot_entry_with_root, bootctl_install_command,
+    default_boot_entry, default_loader_config, BootEntry, LoaderConfig, BLESS_BOOT_SCRIPT_PATH, BLESS_BOOT_SERVICE, BOOT_MODULES,
     DEFAULT_TIMEOUT, ENTRIES_DIR, ESP_MOUNT_POINT, LOADER_CONF_PATH,
 };
 pub use paths::{
@@ -74,10 +74,14 @@ pub use paths::{
     UKI_INSTALLED_RECOVERY_FILENAME,
     UKI_LIVE_FILENAME,
 };
-pub use services::{optional_services, required_services, ServiceSpec, ENABLED_SERVICES};
+pub use services::{
+    optional_services, profile_services, required_services, ServiceSpec, AVAILABLE_SERVICES,
+    ENABLED_SERVICES,
+};
 pub use packages::{
-    all_live_packages, bootable_packages, core_packages, daily_driver_packages,
+    all_live_packages, bootable_packages, core_packages, daily_driver_packages, profile_packages,
     ALPINE_KEYS, BOOTABLE_PACKAGES, CORE_PACKAGES, DAILY_DRIVER_PACKAGES, LIVE_ISO_PACKAGES,
+    SERVER_PACKAGES,
 };
 pub use uki::{UkiEntry, UKI_ENTRIES, UKI_INSTALLED_ENTRIES};
 
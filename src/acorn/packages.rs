@@ -9,6 +9,9 @@
 //! - **Tier 2 (Daily Driver)**: Networking, diagnostics, certificates
 //! - **Tier 3 (Live ISO)**: Installer tools, partitioning
 //!
+//! Install profiles map onto the tiers: minimal is Tier 1, server is Tier 1
+//! plus `SERVER_PACKAGES`, desktop is Tier 2 (see `profile_packages`).
+//!
 //! # Usage
 //!
 //! ```rust
@@ -19,6 +22,8 @@
 //! println!("Installing {} packages", packages.len());
 //! ```
 
+use crate::shared::profile::Profile;
+
 // =============================================================================
 // Tier 0: Bootable Minimum (~15 packages)
 // =============================================================================
@@ -136,6 +141,30 @@ pub const DAILY_DRIVER_PACKAGES: &[&str] = &[
     "openssh",
 ];
 
+/// Server profile packages, added to Tier 1.
+///
+/// The headless subset of Tier 2: wired networking, certificates, SSH and
+/// time sync, without WiFi, audio firmware or desktop diagnostics.
+pub const SERVER_PACKAGES: &[&str] = &[
+    // Networking (wired only)
+    "dhcpcd",
+    "iproute2",
+    "iputils",
+    // Certificates and timezones
+    "ca-certificates",
+    "tzdata",
+    // Tools
+    "curl",
+    "less",
+    "vim",
+    // Disk health
+    "smartmontools",
+    "nvme-cli",
+    // Remote access and time sync
+    "openssh",
+    "chrony",
+];
+
 // =============================================================================
 // Tier 3: Live ISO Specific
 // =============================================================================
@@ -206,6 +235,19 @@ pub fn daily_driver_packages() -> Vec<&'static str> {
     packages
 }
 
+/// Returns the packages installed for `profile`.
+pub fn profile_packages(profile: Profile) -> Vec<&'static str> {
+    match profile {
+        Profile::Minimal => core_packages(),
+        Profile::Server => {
+            let mut packages = core_packages();
+            packages.extend_from_slice(SERVER_PACKAGES);
+            packages
+        }
+        Profile::Desktop => daily_driver_packages(),
+    }
+}
+
 /// Returns all packages for a live ISO (Tiers 0-3).
 ///
 /// This is the default package set for `acornos build`.
@@ -262,6 +304,15 @@ mod tests {
         assert!(all.contains(&"curl"), "curl required for HTTP downloads");
     }
 
+    #[test]
+    fn test_profile_packages() {
+        assert_eq!(profile_packages(Profile::Desktop), daily_driver_packages());
+        let server = profile_packages(Profile::Server);
+        assert!(server.contains(&"openssh") && server.contains(&"chrony"));
+        assert!(!server.contains(&"sof-firmware") && !server.contains(&"iwd"));
+        assert!(!profile_packages(Profile::Minimal).contains(&"openssh"));
+    }
+
     #[test]
     fn test_alpine_keys_present() {
         assert_eq!(ALPINE_KEYS.len(), 5, "Should have 5 Alpine signing keys");
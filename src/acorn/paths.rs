@@ -5,6 +5,8 @@
 //!
 //! AcornOS: Alpine Linux base, openrc, musl, busybox
 
+use crate::arch::Arch;
+
 // Re-export shared constants that are identical across distros
 pub use crate::shared::{
     // Paths
@@ -19,34 +21,19 @@ pub use crate::shared::{
 // EROFS Constants (AcornOS-specific compression)
 // =============================================================================
 
-/// Filesystem type for the rootfs image.
-pub const ROOTFS_TYPE: &str = "erofs";
-
-/// Name of the EROFS rootfs image file.
-pub const EROFS_NAME: &str = "filesystem.erofs";
-
-/// Path to EROFS rootfs on mounted CDROM at runtime.
-pub const EROFS_CDROM_PATH: &str = "/media/cdrom/live/filesystem.erofs";
-
-/// Compression algorithm for mkfs.erofs.
-///
-/// AcornOS uses zstd for best compression ratio.
-/// The kernel must have CONFIG_EROFS_FS_ZIP_ZSTD=y to decompress.
-pub const EROFS_COMPRESSION: &str = "zstd";
+// Rootfs names, paths and EROFS algorithm/chunk size are shared with LevitateOS.
+pub use crate::shared::{
+    EROFS_CDROM_PATH, EROFS_CHUNK_SIZE, EROFS_COMPRESSION, EROFS_NAME, ROOTFS_CDROM_PATH,
+    ROOTFS_NAME, ROOTFS_TYPE,
+};
 
 /// Compression level for zstd (1-22, higher = better compression, slower).
 /// Level 3 is a good balance for live ISO usage.
+///
+/// The only EROFS setting that differs from LevitateOS; see
+/// `ErofsBuildParams::acorn()`.
 pub const EROFS_COMPRESSION_LEVEL: u8 = 3;
 
-/// Chunk size for mkfs.erofs (1MB).
-pub const EROFS_CHUNK_SIZE: u32 = 1048576;
-
-/// Unified rootfs name (currently EROFS).
-pub const ROOTFS_NAME: &str = EROFS_NAME;
-
-/// Unified rootfs CDROM path (currently EROFS).
-pub const ROOTFS_CDROM_PATH: &str = EROFS_CDROM_PATH;
-
 // NOTE: No squashfs constants exported. AcornOS uses EROFS only.
 // Squashfs is only for reading Alpine's modloop, handled by shared::rootfs.
 
@@ -70,7 +57,7 @@ pub const UKI_DEBUG_FILENAME: &str = "acornos-debug.efi";
 pub const UKI_INSTALLED_FILENAME: &str = "acornos.efi";
 
 /// UKI filename for installed system recovery mode.
-pub const UKI_INSTALLED_RECOVERY_FILENAME: &str = "acornos-recovery.efi";
+pub const UKI_INSTALLED_RECOVERY_FILENAME: &str = "recovery-acornos.efi";
 
 /// loader.conf directory on EFI system partition.
 pub const LOADER_ENTRIES_DIR: &str = "loader";
@@ -80,12 +67,12 @@ pub const LOADER_ENTRIES_DIR: &str = "loader";
 /// This is the PE stub that UKIs are built from. Available from:
 /// - systemd-boot package (Fedora: /usr/lib/systemd/boot/efi/linuxx64.efi.stub)
 /// - systemd-efistub package (Alpine 3.22+: same path)
-pub const SYSTEMD_BOOT_STUB: &str = "/usr/lib/systemd/boot/efi/linuxx64.efi.stub";
+pub const SYSTEMD_BOOT_STUB: &str = Arch::DEFAULT.systemd_boot_stub();
 
 /// systemd-boot binary path.
 ///
 /// This is copied to EFI/BOOT/BOOTX64.EFI to serve as the bootloader.
-pub const SYSTEMD_BOOT_EFI: &str = "/usr/lib/systemd/boot/efi/systemd-bootx64.efi";
+pub const SYSTEMD_BOOT_EFI: &str = Arch::DEFAULT.systemd_boot_efi();
 
 // =============================================================================
 // ISO Constants
@@ -162,8 +149,8 @@ pub const ISO_FILENAME: &str = "acornos.iso";
 /// Update this when upgrading to a new Alpine release series.
 pub const ALPINE_VERSION: &str = "3.23";
 
-/// Target architecture.
-pub const TARGET_ARCH: &str = "x86_64";
+/// Target architecture (`Arch::alpine_arch` for other architectures).
+pub const TARGET_ARCH: &str = Arch::DEFAULT.alpine_arch();
 
 // =============================================================================
 // Live System
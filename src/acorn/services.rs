@@ -2,6 +2,7 @@
 //!
 //! Defines which services should be enabled by default on a fresh AcornOS installation.
 
+use crate::shared::profile::Profile;
 use crate::shared::services::ServiceManager;
 
 /// Services that must be enabled during installation.
@@ -28,6 +29,36 @@ pub const ENABLED_SERVICES: &[ServiceSpec] = &[
     },
 ];
 
+/// OpenRC services shipped by the AcornOS package tiers.
+///
+/// Every service here has an init script in `/etc/init.d/` on the installed
+/// system and can be enabled with `rc-update add`. Used to validate
+/// user-requested services (e.g., from an answer file).
+pub const AVAILABLE_SERVICES: &[&str] = &[
+    // alpine-base / openrc
+    "networking",
+    "hostname",
+    "hwclock",
+    "modules",
+    "sysctl",
+    "bootmisc",
+    "syslog",
+    "crond",
+    "local",
+    // eudev-openrc
+    "udev",
+    "udev-trigger",
+    "udev-settle",
+    // Daily driver networking
+    "dhcpcd",
+    "iwd",
+    "chronyd",
+    // openssh
+    "sshd",
+    // distro-spec boot assessment (see boot::bless_boot_init_script)
+    "bless-boot",
+];
+
 /// Specification for an OpenRC service.
 #[derive(Debug, Clone, Copy)]
 pub struct ServiceSpec {
@@ -80,3 +111,36 @@ pub fn required_services() -> impl Iterator<Item = &'static ServiceSpec> {
 pub fn optional_services() -> impl Iterator<Item = &'static ServiceSpec> {
     ENABLED_SERVICES.iter().filter(|s| !s.required)
 }
+
+/// Get the services enabled for an install profile.
+///
+/// `ENABLED_SERVICES` is the desktop set; smaller profiles drop services
+/// whose component they leave out.
+pub fn profile_services(profile: Profile) -> impl Iterator<Item = &'static ServiceSpec> {
+    ENABLED_SERVICES
+        .iter()
+        .filter(move |s| profile.includes_service(s.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_services_are_available() {
+        for service in ENABLED_SERVICES {
+            assert!(
+                AVAILABLE_SERVICES.contains(&service.name),
+                "Enabled service {} is not in AVAILABLE_SERVICES",
+                service.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_profile_services() {
+        let names = |p| profile_services(p).map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(names(Profile::Server), vec!["networking", "chronyd", "sshd"]);
+        assert_eq!(names(Profile::Minimal), vec!["networking"]);
+    }
+}
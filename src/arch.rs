@@ -0,0 +1,303 @@
+//! Target CPU architecture.
+//!
+//! Architecture-dependent names (EFI binaries, the dynamic linker, GPT
+//! partition types, QEMU machine settings, output filenames) are derived from
+//! `Arch` instead of being spelled out per constant. The flat constants
+//! elsewhere in the spec (`EFI_BOOTLOADER`, `SYSTEMD_BOOT_STUB`, ...) are the
+//! `Arch::DEFAULT` values and stay for existing callers.
+
+use std::fmt;
+
+/// A supported target architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Arch {
+    /// 64-bit x86 (amd64)
+    #[default]
+    X86_64,
+    /// 64-bit ARM (arm64)
+    Aarch64,
+}
+
+impl Arch {
+    /// All supported architectures.
+    pub const ALL: &'static [Arch] = &[Arch::X86_64, Arch::Aarch64];
+
+    /// Architecture the flat constants are written for.
+    pub const DEFAULT: Arch = Arch::X86_64;
+
+    /// Parse `uname -m` style names and common aliases (amd64, arm64).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "x86_64" | "amd64" | "x64" => Some(Arch::X86_64),
+            "aarch64" | "arm64" | "aa64" => Some(Arch::Aarch64),
+            _ => None,
+        }
+    }
+
+    /// Canonical name, as printed by `uname -m`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+        }
+    }
+
+    /// Alpine `apk --arch` / `TARGET_ARCH` value.
+    pub const fn alpine_arch(&self) -> &'static str {
+        // Alpine uses the uname names for both
+        self.name()
+    }
+
+    // =========================================================================
+    // EFI
+    // =========================================================================
+
+    /// UEFI short name used in EFI binary names ("x64", "aa64").
+    pub const fn efi_suffix(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x64",
+            Arch::Aarch64 => "aa64",
+        }
+    }
+
+    /// Removable-media fallback bootloader name in `EFI/BOOT/`.
+    pub const fn efi_bootloader(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "BOOTX64.EFI",
+            Arch::Aarch64 => "BOOTAA64.EFI",
+        }
+    }
+
+    /// GRUB EFI binary name.
+    pub const fn efi_grub(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "grubx64.efi",
+            Arch::Aarch64 => "grubaa64.efi",
+        }
+    }
+
+    /// systemd-boot binary shipped by the systemd package.
+    pub const fn systemd_boot_efi(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "/usr/lib/systemd/boot/efi/systemd-bootx64.efi",
+            Arch::Aarch64 => "/usr/lib/systemd/boot/efi/systemd-bootaa64.efi",
+        }
+    }
+
+    /// systemd EFI stub that ukify wraps the kernel in.
+    pub const fn systemd_boot_stub(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "/usr/lib/systemd/boot/efi/linuxx64.efi.stub",
+            Arch::Aarch64 => "/usr/lib/systemd/boot/efi/linuxaa64.efi.stub",
+        }
+    }
+
+    // =========================================================================
+    // ELF / Dynamic Linker
+    // =========================================================================
+
+    /// glibc dynamic linker (relative to `/`).
+    pub const fn glibc_dynamic_linker(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "usr/lib64/ld-linux-x86-64.so.2",
+            Arch::Aarch64 => "usr/lib64/ld-linux-aarch64.so.1",
+        }
+    }
+
+    /// musl dynamic linker (relative to `/`).
+    pub const fn musl_dynamic_linker(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "lib/ld-musl-x86_64.so.1",
+            Arch::Aarch64 => "lib/ld-musl-aarch64.so.1",
+        }
+    }
+
+    /// ELF `e_machine` value.
+    pub const fn elf_machine(&self) -> u16 {
+        match self {
+            Arch::X86_64 => 62,   // EM_X86_64
+            Arch::Aarch64 => 183, // EM_AARCH64
+        }
+    }
+
+    // =========================================================================
+    // Partitions
+    // =========================================================================
+
+    /// Discoverable Partitions root partition type GUID.
+    ///
+    /// With this type systemd-gpt-auto-generator finds the root partition
+    /// without `root=` on the kernel command line.
+    pub const fn gpt_root_type(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709",
+            Arch::Aarch64 => "B921B045-1DF0-41C3-AF44-4C6F280D3FAE",
+        }
+    }
+
+    // =========================================================================
+    // QEMU
+    // =========================================================================
+
+    /// QEMU system emulator binary.
+    pub const fn qemu_binary(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "qemu-system-x86_64",
+            Arch::Aarch64 => "qemu-system-aarch64",
+        }
+    }
+
+    /// QEMU machine type.
+    pub const fn qemu_machine(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "q35",
+            Arch::Aarch64 => "virt",
+        }
+    }
+
+    /// CPU model for TCG emulation (when KVM is unavailable).
+    pub const fn qemu_tcg_cpu(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "qemu64",
+            Arch::Aarch64 => "cortex-a72",
+        }
+    }
+
+    /// Serial console device name in the guest.
+    pub const fn serial_console(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "ttyS0",
+            Arch::Aarch64 => "ttyAMA0",
+        }
+    }
+
+    // =========================================================================
+    // Output Filenames
+    // =========================================================================
+
+    /// ISO filename for an OS (`<os_id>-<arch>.iso`).
+    pub fn iso_filename(&self, os_id: &str) -> String {
+        format!("{}-{}.iso", os_id, self.name())
+    }
+
+    /// qcow2 VM image filename (`<os_id>-<arch>.qcow2`).
+    pub fn qcow2_filename(&self, os_id: &str) -> String {
+        format!("{}-{}.qcow2", os_id, self.name())
+    }
+
+    /// Raw disk image filename (`<os_id>-<arch>.raw`).
+    pub fn raw_disk_filename(&self, os_id: &str) -> String {
+        format!("{}-{}.raw", os_id, self.name())
+    }
+}
+
+/// Length of `<os_id>-<arch>.<ext>`.
+pub(crate) const fn filename_len(os_id: &str, arch: Arch, ext: &str) -> usize {
+    os_id.len() + 1 + arch.name().len() + 1 + ext.len()
+}
+
+/// `<os_id>-<arch>.<ext>` as bytes; `N` must be `filename_len(..)`.
+pub(crate) const fn filename_bytes<const N: usize>(os_id: &str, arch: Arch, ext: &str) -> [u8; N] {
+    let mut out = [0u8; N];
+    let parts: [&[u8]; 5] = [
+        os_id.as_bytes(),
+        b"-",
+        arch.name().as_bytes(),
+        b".",
+        ext.as_bytes(),
+    ];
+    let mut at = 0;
+    let mut p = 0;
+    while p < parts.len() {
+        let mut i = 0;
+        while i < parts[p].len() {
+            out[at] = parts[p][i];
+            at += 1;
+            i += 1;
+        }
+        p += 1;
+    }
+    out
+}
+
+/// `&str` constant `<os_id>-<arch>.<ext>`, the compile-time form of
+/// `Arch::iso_filename` and friends.
+macro_rules! output_filename {
+    ($os_id:expr, $arch:expr, $ext:expr) => {{
+        use $crate::arch::{filename_bytes, filename_len};
+        const BYTES: [u8; filename_len($os_id, $arch, $ext)] = filename_bytes($os_id, $arch, $ext);
+        match core::str::from_utf8(&BYTES) {
+            Ok(s) => s,
+            Err(_) => panic!("output filename is not UTF-8"),
+        }
+    }};
+}
+pub(crate) use output_filename;
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levitate::{QCOW2_IMAGE_FILENAME, RAW_DISK_FILENAME};
+    use crate::shared::components::{critical_libs, CRITICAL_LIBS};
+    use crate::shared::iso::{EFI_BOOTLOADER, EFI_GRUB};
+    use crate::shared::qemu::QEMU_CPU_MODE;
+    use crate::shared::uki::{SYSTEMD_BOOT_EFI, SYSTEMD_BOOT_STUB};
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_flat_constants_match_default_arch() {
+        let arch = Arch::DEFAULT;
+        assert_eq!(EFI_BOOTLOADER, arch.efi_bootloader());
+        assert_eq!(SYSTEMD_BOOT_EFI, arch.systemd_boot_efi());
+        assert_eq!(SYSTEMD_BOOT_STUB, arch.systemd_boot_stub());
+        assert_eq!(QEMU_CPU_MODE, arch.qemu_tcg_cpu());
+        assert_eq!(QCOW2_IMAGE_FILENAME, arch.qcow2_filename("levitateos"));
+        assert_eq!(RAW_DISK_FILENAME, arch.raw_disk_filename("levitateos"));
+        assert_eq!(EFI_GRUB, arch.efi_grub());
+        assert_eq!(crate::acorn::TARGET_ARCH, arch.alpine_arch());
+        assert!(CRITICAL_LIBS.contains(&arch.glibc_dynamic_linker()));
+        assert_eq!(critical_libs(arch), CRITICAL_LIBS);
+        for arch in Arch::ALL {
+            let libs = critical_libs(*arch);
+            assert_eq!(libs.len(), CRITICAL_LIBS.len());
+            assert!(libs.contains(&arch.glibc_dynamic_linker()));
+        }
+    }
+
+    #[test]
+    fn test_iso_filenames_match_default_arch() {
+        assert_eq!(
+            crate::levitate::ISO_FILENAME,
+            Arch::DEFAULT.iso_filename(Variant::Levitate.os_id())
+        );
+        // AcornOS keeps its arch-less name
+        assert_eq!(crate::acorn::ISO_FILENAME, "acornos.iso");
+        assert_eq!(
+            output_filename!("acornos", Arch::Aarch64, "iso"),
+            Arch::Aarch64.iso_filename("acornos")
+        );
+        for arch in Arch::ALL {
+            assert!(arch.efi_grub().starts_with("grub"));
+            assert!(arch.efi_grub().contains(arch.efi_suffix()));
+        }
+    }
+
+    #[test]
+    fn test_aarch64() {
+        let arch = Arch::from_name("arm64").unwrap();
+        assert_eq!(arch, Arch::Aarch64);
+        assert_eq!(arch.efi_bootloader(), "BOOTAA64.EFI");
+        assert!(arch
+            .systemd_boot_efi()
+            .ends_with(&format!("systemd-boot{}.efi", arch.efi_suffix())));
+        assert_eq!(arch.iso_filename("acornos"), "acornos-aarch64.iso");
+        assert_eq!(arch.qemu_machine(), "virt");
+        assert_eq!(Arch::from_name("riscv64"), None);
+    }
+}
@@ -55,3 +55,27 @@ pub fn boot_entry_with_label(label: impl Into<String>) -> BootEntry {
 pub fn default_loader_config() -> LoaderConfig {
     LoaderConfig::with_defaults(OS_ID)
 }
+
+// =============================================================================
+// Boot Assessment
+// =============================================================================
+
+/// Units that bless a counted boot entry once the system is up.
+///
+/// `systemd-bless-boot-generator` pulls `systemd-bless-boot.service` in when
+/// the system was booted from a counted entry. It runs after
+/// `boot-complete.target`, which `systemd-boot-check-no-failures.service`
+/// only lets through if no unit failed.
+pub const BOOT_ASSESSMENT_UNITS: &[&str] = &[
+    "systemd-bless-boot.service",
+    "systemd-boot-check-no-failures.service",
+    "boot-complete.target",
+];
+
+/// Generator that activates `systemd-bless-boot.service` (in system-generators/).
+pub const BLESS_BOOT_GENERATOR: &str = "systemd-bless-boot-generator";
+
+/// Commands to run in the chroot to gate blessing on a failure-free boot.
+pub fn boot_assessment_enable_commands() -> Vec<String> {
+    vec!["systemctl enable systemd-boot-check-no-failures.service".to_string()]
+}
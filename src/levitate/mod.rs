@@ -13,8 +13,8 @@ pub mod services;
 pub mod uki;
 
 pub use boot::{
-    boot_entry_with_label, boot_entry_with_partuuid, boot_entry_with_root, bootctl_install_command,
-    default_boot_entry, default_loader_config, BootEntry, LoaderConfig, BOOT_MODULES,
+    boot_assessment_enable_commands, boot_entry_with_label, boot_entry_with_partuuid, boot_entry_with_root, bootctl_install_command,
+    default_boot_entry, default_loader_config, BootEntry, LoaderConfig, BLESS_BOOT_GENERATOR, BOOT_ASSESSMENT_UNITS, BOOT_MODULES,
     DEFAULT_TIMEOUT, ENTRIES_DIR, ESP_MOUNT_POINT, LOADER_CONF_PATH,
 };
 pub use paths::{
@@ -36,6 +36,9 @@ pub use paths::{
     INITRAMFS_INSTALLED_ISO_PATH,
     // ISO Output
     ISO_FILENAME,
+    // qcow2 VM Image Output
+    QCOW2_IMAGE_FILENAME,
+    RAW_DISK_FILENAME,
     // ISO constants
     ISO_LABEL,
     KERNEL_FILENAME,
@@ -67,7 +70,10 @@ pub use paths::{
     UKI_INSTALLED_ISO_PATH,
     UKI_INSTALLED_RECOVERY_ISO_PATH,
 };
-pub use services::{optional_services, required_services, ServiceSpec, ENABLED_SERVICES};
+pub use services::{
+    optional_services, profile_services, required_services, ServiceSpec, ENABLED_SERVICES,
+    FALLBACK_NETWORK_SERVICES,
+};
 pub use uki::{UkiEntry, UKI_ENTRIES, UKI_INSTALLED_ENTRIES};
 
 // Re-export shared constants
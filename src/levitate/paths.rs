@@ -5,6 +5,8 @@
 //!
 //! LevitateOS: Rocky Linux base, systemd, glibc, GNU coreutils
 
+use crate::arch::{output_filename, Arch};
+
 // Re-export shared constants that are identical across distros
 pub use crate::shared::{
     // Paths
@@ -85,8 +87,22 @@ pub const ROOT_SHELL: &str = "/bin/bash";
 // ISO Output
 // =============================================================================
 
-/// ISO output filename (x86_64 architecture)
-pub const ISO_FILENAME: &str = "levitateos-x86_64.iso";
+/// ISO output filename (x86_64 architecture; see `Arch::iso_filename`)
+pub const ISO_FILENAME: &str = output_filename!(OS_ID, Arch::DEFAULT, "iso");
+
+// =============================================================================
+// qcow2 VM Image Output
+// =============================================================================
+
+/// Output filename for qcow2 VM images (x86_64 architecture).
+///
+/// Other architectures: `Arch::qcow2_filename`.
+pub const QCOW2_IMAGE_FILENAME: &str = output_filename!(OS_ID, Arch::DEFAULT, "qcow2");
+
+/// Temporary raw disk filename (converted to qcow2 after building).
+///
+/// Other architectures: `Arch::raw_disk_filename`.
+pub const RAW_DISK_FILENAME: &str = output_filename!(OS_ID, Arch::DEFAULT, "raw");
 
 // =============================================================================
 // Initramfs Build
@@ -109,15 +125,13 @@ pub const INITRAMFS_INSTALLED_ISO_PATH: &str = "boot/initramfs-installed.img";
 // Installed UKIs (for installed systems)
 // =============================================================================
 
-/// Directory on ISO containing pre-built UKIs for installed systems.
-/// Users copy these to /boot/EFI/Linux/ during installation.
-pub const UKI_INSTALLED_ISO_DIR: &str = "boot/uki";
+pub use crate::shared::UKI_INSTALLED_ISO_DIR;
 
 /// Installed UKI path on ISO (normal boot).
 pub const UKI_INSTALLED_ISO_PATH: &str = "boot/uki/levitateos.efi";
 
 /// Installed UKI path on ISO (recovery mode).
-pub const UKI_INSTALLED_RECOVERY_ISO_PATH: &str = "boot/uki/levitateos-recovery.efi";
+pub const UKI_INSTALLED_RECOVERY_ISO_PATH: &str = "boot/uki/recovery-levitateos.efi";
 
 // =============================================================================
 // Live System
@@ -2,6 +2,8 @@
 //!
 //! Defines which services should be enabled by default on a fresh installation.
 
+use crate::shared::profile::Profile;
+use crate::shared::registry::NETWORKMANAGER;
 use crate::shared::services::ServiceManager;
 
 /// Services that must be enabled during installation.
@@ -26,6 +28,23 @@ pub const ENABLED_SERVICES: &[ServiceSpec] = &[
     },
 ];
 
+/// Services enabled instead of NetworkManager by profiles that leave it out.
+///
+/// systemd-networkd and systemd-resolved ship with systemd itself, so even a
+/// minimal install comes up with DHCP and DNS.
+pub const FALLBACK_NETWORK_SERVICES: &[ServiceSpec] = &[
+    ServiceSpec {
+        name: "systemd-networkd",
+        description: "Network configuration (no NetworkManager)",
+        required: true,
+    },
+    ServiceSpec {
+        name: "systemd-resolved",
+        description: "DNS resolution (no NetworkManager)",
+        required: true,
+    },
+];
+
 /// Specification for a systemd service.
 #[derive(Debug, Clone, Copy)]
 pub struct ServiceSpec {
@@ -83,3 +102,36 @@ pub fn required_services() -> impl Iterator<Item = &'static ServiceSpec> {
 pub fn optional_services() -> impl Iterator<Item = &'static ServiceSpec> {
     ENABLED_SERVICES.iter().filter(|s| !s.required)
 }
+
+/// Get the services enabled for an install profile.
+///
+/// `ENABLED_SERVICES` is the desktop set; smaller profiles drop services
+/// whose component they leave out. Profiles without NetworkManager get
+/// `FALLBACK_NETWORK_SERVICES` instead.
+pub fn profile_services(profile: Profile) -> impl Iterator<Item = &'static ServiceSpec> {
+    let fallback = if profile.includes(&NETWORKMANAGER) {
+        &[][..]
+    } else {
+        FALLBACK_NETWORK_SERVICES
+    };
+    ENABLED_SERVICES
+        .iter()
+        .filter(move |s| profile.includes_service(s.name))
+        .chain(fallback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_services() {
+        let names = |p| profile_services(p).map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(names(Profile::Desktop).len(), ENABLED_SERVICES.len());
+        assert_eq!(names(Profile::Server), vec!["NetworkManager", "chronyd", "sshd"]);
+        assert_eq!(
+            names(Profile::Minimal),
+            vec!["systemd-networkd", "systemd-resolved"]
+        );
+    }
+}
@@ -42,14 +42,21 @@
 //! Any mismatch between these three is a bug.
 
 pub mod acorn;
+pub mod arch;
 pub mod levitate;
 pub mod shared;
+pub mod variant;
 
 // Re-export shared items at crate root for convenience
 pub use shared::{
     boot::{BootEntry, LoaderConfig, ESP_MOUNT_POINT, LOADER_CONF_PATH, ENTRIES_DIR, DEFAULT_TIMEOUT, bootctl_install_command},
     chroot::{BindMount, CHROOT_BIND_MOUNTS, mounts_in_order, mounts_in_unmount_order},
+    install::{InstallConfig, InstallPlan, InstallStep, StepAction, StepKind},
     partitions::{PartitionLayout, PartitionSpec, EFI_PARTITION_SIZE_MB},
     services::ServiceManager,
     users::{UserSpec, MIN_UID, MIN_GID, SUDOERS_WHEEL_LINE},
 };
+pub use arch::Arch;
+pub use shared::profile::Profile;
+pub use shared::validate::validate_spec;
+pub use variant::Variant;
@@ -0,0 +1,1261 @@
+//! Unattended installation answer files.
+//!
+//! An answer file is a TOML document describing one installation. It is parsed
+//! into an `InstallRequest`, validated against the rest of the spec
+//! (`SystemRequirements`, `UserSpec` rules, known services), and turned into an
+//! `InstallPlan`.
+//!
+//! # Format
+//!
+//! ```toml
+//! variant = "levitate"            # or "acorn"
+//! profile = "server"              # optional: minimal, server or desktop (default)
+//!
+//! [disk]
+//! device = "/dev/nvme0n1"         # whole disk, wiped
+//! preset = "default"              # see PartitionLayout::PRESETS
+//! filesystem = "ext4"             # ext4, xfs or btrfs
+//! encryption = false              # LUKS2 on the root partition
+//! key_file = "/run/luks.key"      # optional, requires encryption = true
+//!
+//! [system]
+//! hostname = "ws01"
+//! locale = "en_US.UTF-8"
+//! timezone = "Europe/Amsterdam"
+//! keymap = "us"
+//!
+//! [services]
+//! enable = ["bluetooth"]          # in addition to the profile's services
+//!
+//! [root]
+//! ssh_keys = ["ssh-ed25519 AAAA... admin@laptop"]
+//!
+//! [[users]]
+//! name = "alice"
+//! full_name = "Alice Example"     # optional
+//! shell = "/bin/bash"             # optional, variant default
+//! groups = ["wheel", "video"]     # optional, DEFAULT_USER_GROUPS
+//! ssh_keys = ["ssh-ed25519 AAAA... alice@laptop"]
+//! ```
+//!
+//! Only the subset of TOML shown above is accepted: tables, arrays of tables,
+//! strings, integers, booleans and (possibly multi-line) arrays. Every error
+//! carries the line it was found on.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::install::{InstallConfig, InstallPlan, RootEncryption};
+use super::partitions::{PartitionLayout, SUPPORTED_ROOT_FILESYSTEMS};
+use super::paths::DEFAULT_USER_GROUPS;
+use super::profile::Profile;
+use super::requirements::BYTES_PER_GB;
+use super::users::UserSpec;
+use crate::variant::Variant;
+
+/// SSH public key types accepted in `ssh_keys`.
+pub const SSH_KEY_TYPES: &[&str] = &[
+    "ssh-ed25519",
+    "ssh-rsa",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "sk-ssh-ed25519@openssh.com",
+    "sk-ecdsa-sha2-nistp256@openssh.com",
+];
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// A single problem found in an answer file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerFileError {
+    /// 1-based line number (0 if the problem is not tied to a line)
+    pub line: usize,
+    /// What is wrong
+    pub message: String,
+}
+
+impl AnswerFileError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for AnswerFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// All problems found in an answer file, in line order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerFileErrors(pub Vec<AnswerFileError>);
+
+impl fmt::Display for AnswerFileErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", err)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AnswerFileErrors {}
+
+// =============================================================================
+// Install Request
+// =============================================================================
+
+/// A validated installation request parsed from an answer file.
+#[derive(Debug, Clone)]
+pub struct InstallRequest {
+    /// Variant to install
+    pub variant: Variant,
+    /// Install profile
+    pub profile: Profile,
+    /// Target disk
+    pub disk: String,
+    /// Partition preset name
+    pub preset: String,
+    /// Root filesystem type
+    pub filesystem: &'static str,
+    /// Root partition encryption
+    pub encryption: Option<RootEncryption>,
+    /// Hostname
+    pub hostname: String,
+    /// Locale
+    pub locale: Option<String>,
+    /// Timezone
+    pub timezone: Option<String>,
+    /// Console keymap
+    pub keymap: Option<String>,
+    /// Regular users
+    pub users: Vec<UserSpec>,
+    /// SSH keys per account, as (username, keys); includes root
+    pub authorized_keys: Vec<(String, Vec<String>)>,
+    /// Services to enable beyond the variant defaults
+    pub extra_services: Vec<String>,
+    /// Line of `[disk] device`, for errors raised after parsing
+    device_line: usize,
+}
+
+impl InstallRequest {
+    /// Parse and validate an answer file.
+    pub fn from_toml(input: &str) -> Result<Self, AnswerFileErrors> {
+        let doc = parse_document(input).map_err(|e| AnswerFileErrors(vec![e]))?;
+        let mut v = Validator::default();
+        let request = v.request(&doc);
+        v.errors.sort_by_key(|e| e.line);
+        match request {
+            Some(request) if v.errors.is_empty() => Ok(request),
+            _ => Err(AnswerFileErrors(v.errors)),
+        }
+    }
+
+    /// Check the target disk size against the variant's `SystemRequirements`.
+    ///
+    /// Installers call this once they know the real size of `disk`.
+    pub fn check_disk_size(&self, size_bytes: u64) -> Result<(), AnswerFileError> {
+        let min_gb = self.variant.requirements().min_disk_gb;
+        let size_gb = size_bytes / BYTES_PER_GB;
+        if size_gb < u64::from(min_gb) {
+            return Err(AnswerFileError::new(
+                self.device_line,
+                format!(
+                    "{} is {} GB; {} requires at least {} GB",
+                    self.disk,
+                    size_gb,
+                    self.variant.os_name(),
+                    min_gb
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Convert into an `InstallConfig`.
+    pub fn to_install_config(&self) -> InstallConfig {
+        let mut layout = PartitionLayout::preset(&self.preset).unwrap_or_default();
+        layout.root.filesystem = self.filesystem;
+
+        let mut config = InstallConfig::new(self.variant, self.disk.clone())
+            .with_profile(self.profile)
+            .with_layout(layout)
+            .with_hostname(self.hostname.clone());
+        config.locale = self.locale.clone();
+        config.timezone = self.timezone.clone();
+        config.keymap = self.keymap.clone();
+        config.encryption = self.encryption.clone();
+        config.users = self.users.clone();
+        config.authorized_keys = self.authorized_keys.clone();
+        config.extra_services = self.extra_services.clone();
+        config
+    }
+
+    /// Build the installation plan for this request.
+    pub fn plan(&self) -> InstallPlan {
+        InstallPlan::new(self.to_install_config())
+    }
+}
+
+// =============================================================================
+// Validation
+// =============================================================================
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<AnswerFileError>,
+}
+
+impl Validator {
+    fn err(&mut self, line: usize, message: impl Into<String>) {
+        self.errors.push(AnswerFileError::new(line, message));
+    }
+
+    /// Reject keys not in `allowed`.
+    fn known_keys(&mut self, table: &Table, allowed: &[&str]) {
+        for entry in &table.entries {
+            if !allowed.contains(&entry.key.as_str()) {
+                let context = if table.name.is_empty() {
+                    "top level".to_string()
+                } else {
+                    format!("[{}]", table.name)
+                };
+                self.err(
+                    entry.line,
+                    format!(
+                        "unknown key '{}' in {} (expected one of: {})",
+                        entry.key,
+                        context,
+                        allowed.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    fn string(&mut self, table: &Table, key: &str) -> Option<(String, usize)> {
+        let entry = table.get(key)?;
+        match entry.value {
+            Value::Str(ref s) => Some((s.clone(), entry.line)),
+            ref other => {
+                self.err(
+                    entry.line,
+                    format!("'{}' must be a string, found {}", key, other.type_name()),
+                );
+                None
+            }
+        }
+    }
+
+    fn boolean(&mut self, table: &Table, key: &str) -> Option<(bool, usize)> {
+        let entry = table.get(key)?;
+        match entry.value {
+            Value::Bool(b) => Some((b, entry.line)),
+            ref other => {
+                self.err(
+                    entry.line,
+                    format!("'{}' must be a boolean, found {}", key, other.type_name()),
+                );
+                None
+            }
+        }
+    }
+
+    /// Array of strings, each with its own line number.
+    fn strings(&mut self, table: &Table, key: &str) -> Option<Vec<(String, usize)>> {
+        let entry = table.get(key)?;
+        let Value::Array(ref items) = entry.value else {
+            self.err(
+                entry.line,
+                format!(
+                    "'{}' must be an array of strings, found {}",
+                    key,
+                    entry.value.type_name()
+                ),
+            );
+            return None;
+        };
+        let mut out = Vec::new();
+        for (item, line) in items {
+            match item {
+                Value::Str(s) => out.push((s.clone(), *line)),
+                other => self.err(
+                    *line,
+                    format!(
+                        "'{}' entries must be strings, found {}",
+                        key,
+                        other.type_name()
+                    ),
+                ),
+            }
+        }
+        Some(out)
+    }
+
+    fn request(&mut self, doc: &Document) -> Option<InstallRequest> {
+        for table in &doc.tables {
+            let known = matches!(
+                (table.name.as_str(), table.is_array),
+                ("", false)
+                    | ("disk", false)
+                    | ("system", false)
+                    | ("services", false)
+                    | ("root", false)
+                    | ("users", true)
+            );
+            if !known {
+                let header = if table.is_array {
+                    format!("[[{}]]", table.name)
+                } else {
+                    format!("[{}]", table.name)
+                };
+                self.err(table.line, format!("unknown table {}", header));
+            }
+        }
+
+        let empty = Table::default();
+        let top = doc.table("").unwrap_or(&empty);
+        self.known_keys(top, &["variant", "profile"]);
+        let variant = match self.string(top, "variant") {
+            Some((name, line)) => match Variant::from_name(&name) {
+                Some(v) => Some(v),
+                None => {
+                    self.err(
+                        line,
+                        format!(
+                            "unknown variant '{}' (expected \"levitate\" or \"acorn\")",
+                            name
+                        ),
+                    );
+                    None
+                }
+            },
+            None => {
+                if top.get("variant").is_none() {
+                    self.err(0, "missing required key 'variant'");
+                }
+                None
+            }
+        };
+
+        let profile = match self.string(top, "profile") {
+            Some((name, line)) => Profile::from_name(&name).unwrap_or_else(|| {
+                self.err(
+                    line,
+                    format!(
+                        "unknown profile '{}' (expected \"minimal\", \"server\" or \"desktop\")",
+                        name
+                    ),
+                );
+                Profile::default()
+            }),
+            None => Profile::default(),
+        };
+
+        let disk = self.disk(doc.table("disk"));
+        let system = self.system(doc.table("system").unwrap_or(&empty));
+        let extra_services =
+            self.services(doc.table("services").unwrap_or(&empty), variant, profile);
+
+        let mut authorized_keys = Vec::new();
+        if let Some(root) = doc.table("root") {
+            self.known_keys(root, &["ssh_keys"]);
+            let keys = self.ssh_keys(root);
+            if !keys.is_empty() {
+                authorized_keys.push(("root".to_string(), keys));
+            }
+        }
+
+        let mut users = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for table in doc
+            .tables
+            .iter()
+            .filter(|t| t.is_array && t.name == "users")
+        {
+            if let Some((user, keys)) = self.user(table, variant) {
+                if let Some(first) = seen.insert(user.username.clone(), table.line) {
+                    self.err(
+                        table.line,
+                        format!(
+                            "user '{}' is already defined on line {}",
+                            user.username, first
+                        ),
+                    );
+                    continue;
+                }
+                if !keys.is_empty() {
+                    authorized_keys.push((user.username.clone(), keys));
+                }
+                users.push(user);
+            }
+        }
+
+        let variant = variant?;
+        let (disk, device_line, preset, filesystem, encryption) = disk?;
+        let (hostname, locale, timezone, keymap) = system;
+        Some(InstallRequest {
+            variant,
+            profile,
+            disk,
+            preset,
+            filesystem,
+            encryption,
+            hostname: hostname.unwrap_or_else(|| variant.default_hostname().to_string()),
+            locale,
+            timezone,
+            keymap,
+            users,
+            authorized_keys,
+            extra_services,
+            device_line,
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn disk(
+        &mut self,
+        table: Option<&Table>,
+    ) -> Option<(String, usize, String, &'static str, Option<RootEncryption>)> {
+        let Some(table) = table else {
+            self.err(0, "missing required table [disk]");
+            return None;
+        };
+        self.known_keys(
+            table,
+            &["device", "preset", "filesystem", "encryption", "key_file"],
+        );
+
+        let device = match self.string(table, "device") {
+            Some((dev, line)) if dev.starts_with("/dev/") && dev.len() > 5 => Some((dev, line)),
+            Some((dev, line)) => {
+                self.err(
+                    line,
+                    format!("disk device '{}' must be a path under /dev/", dev),
+                );
+                None
+            }
+            None => {
+                if table.get("device").is_none() {
+                    self.err(table.line, "[disk] is missing required key 'device'");
+                }
+                None
+            }
+        };
+
+        let preset = match self.string(table, "preset") {
+            Some((name, line)) if PartitionLayout::preset(&name).is_none() => {
+                self.err(
+                    line,
+                    format!(
+                        "unknown partition preset '{}' (expected one of: {})",
+                        name,
+                        PartitionLayout::PRESETS.join(", ")
+                    ),
+                );
+                None
+            }
+            Some((name, _)) => Some(name),
+            None => Some("default".to_string()),
+        };
+
+        let filesystem = match self.string(table, "filesystem") {
+            Some((fs, line)) => match SUPPORTED_ROOT_FILESYSTEMS.iter().find(|s| **s == fs) {
+                Some(fs) => Some(*fs),
+                None => {
+                    self.err(
+                        line,
+                        format!(
+                            "unsupported root filesystem '{}' (expected one of: {})",
+                            fs,
+                            SUPPORTED_ROOT_FILESYSTEMS.join(", ")
+                        ),
+                    );
+                    None
+                }
+            },
+            None => Some(PartitionLayout::default().root.filesystem),
+        };
+
+        let encrypted = self
+            .boolean(table, "encryption")
+            .map(|(b, _)| b)
+            .unwrap_or(false);
+        let key_file = self.string(table, "key_file");
+        let encryption = match (encrypted, key_file) {
+            (true, key) => Some(RootEncryption {
+                key_file: key.map(|(k, _)| k),
+                uuid: None,
+            }),
+            (false, Some((_, line))) => {
+                self.err(line, "'key_file' requires 'encryption = true'");
+                None
+            }
+            (false, None) => None,
+        };
+
+        let (device, line) = device?;
+        Some((device, line, preset?, filesystem?, encryption))
+    }
+
+    fn system(
+        &mut self,
+        table: &Table,
+    ) -> (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    ) {
+        self.known_keys(table, &["hostname", "locale", "timezone", "keymap"]);
+
+        let hostname = self.string(table, "hostname").and_then(|(h, line)| {
+            let valid_label = |l: &str| {
+                !l.is_empty()
+                    && l.len() <= 63
+                    && !l.starts_with('-')
+                    && !l.ends_with('-')
+                    && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            };
+            if h.len() <= 253 && h.split('.').all(valid_label) {
+                Some(h)
+            } else {
+                self.err(line, format!("invalid hostname '{}' (RFC 1123: letters, digits and '-', labels of 1-63 characters)", h));
+                None
+            }
+        });
+
+        let locale = self.string(table, "locale").and_then(|(l, line)| {
+            let (lang, codeset) = l.split_once('.').unwrap_or((l.as_str(), ""));
+            let lang_ok = matches!(lang, "C" | "POSIX")
+                || lang.split_once('_').is_some_and(|(ll, cc)| {
+                    (2..=3).contains(&ll.len())
+                        && ll.chars().all(|c| c.is_ascii_lowercase())
+                        && cc.len() == 2
+                        && cc.chars().all(|c| c.is_ascii_uppercase())
+                });
+            let codeset_ok = codeset.is_empty() || matches!(codeset, "UTF-8" | "utf8");
+            if lang_ok && codeset_ok {
+                Some(l)
+            } else {
+                self.err(
+                    line,
+                    format!(
+                        "invalid locale '{}' (expected e.g. \"en_US.UTF-8\" or \"C.UTF-8\")",
+                        l
+                    ),
+                );
+                None
+            }
+        });
+
+        let timezone = self.string(table, "timezone").and_then(|(tz, line)| {
+            let valid = !tz.is_empty()
+                && !tz.starts_with('/')
+                && !tz.split('/').any(|p| p.is_empty() || p == "." || p == "..")
+                && tz.chars().all(|c| c.is_ascii_alphanumeric() || "/_+-".contains(c));
+            if valid {
+                Some(tz)
+            } else {
+                self.err(line, format!("invalid timezone '{}' (expected a zoneinfo name like \"Europe/Amsterdam\" or \"UTC\")", tz));
+                None
+            }
+        });
+
+        let keymap = self.string(table, "keymap").and_then(|(k, line)| {
+            if !k.is_empty()
+                && k.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+            {
+                Some(k)
+            } else {
+                self.err(line, format!("invalid keymap '{}'", k));
+                None
+            }
+        });
+
+        (hostname, locale, timezone, keymap)
+    }
+
+    fn services(
+        &mut self,
+        table: &Table,
+        variant: Option<Variant>,
+        profile: Profile,
+    ) -> Vec<String> {
+        self.known_keys(table, &["enable"]);
+        let mut out: Vec<String> = Vec::new();
+        for (name, line) in self.strings(table, "enable").unwrap_or_default() {
+            if let Some(v) = variant {
+                if !v.is_known_service(&name) {
+                    self.err(
+                        line,
+                        format!("unknown service '{}' for {}", name, v.os_name()),
+                    );
+                    continue;
+                }
+                let default = v
+                    .profile_services(profile)
+                    .iter()
+                    .any(|s| s.name() == name.trim_end_matches(".service"));
+                if default {
+                    // Already enabled by the profile; nothing to do
+                    continue;
+                }
+            }
+            if out.contains(&name) {
+                self.err(line, format!("service '{}' listed twice", name));
+                continue;
+            }
+            out.push(name);
+        }
+        out
+    }
+
+    fn ssh_keys(&mut self, table: &Table) -> Vec<String> {
+        let mut keys = Vec::new();
+        for (key, line) in self.strings(table, "ssh_keys").unwrap_or_default() {
+            let mut parts = key.split_whitespace();
+            let kind = parts.next().unwrap_or_default();
+            let blob = parts.next().unwrap_or_default();
+            if !SSH_KEY_TYPES.contains(&kind) {
+                self.err(
+                    line,
+                    format!(
+                        "unsupported SSH key type '{}' (expected one of: {})",
+                        kind,
+                        SSH_KEY_TYPES.join(", ")
+                    ),
+                );
+            } else if blob.is_empty()
+                || !blob
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+/=".contains(c))
+            {
+                self.err(
+                    line,
+                    format!(
+                        "SSH key of type {} has a missing or malformed base64 body",
+                        kind
+                    ),
+                );
+            } else {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    fn user(&mut self, table: &Table, variant: Option<Variant>) -> Option<(UserSpec, Vec<String>)> {
+        self.known_keys(table, &["name", "full_name", "shell", "groups", "ssh_keys"]);
+        let name = self.string(table, "name");
+        if name.is_none() && table.get("name").is_none() {
+            self.err(table.line, "[[users]] is missing required key 'name'");
+        }
+        let full_name = self.string(table, "full_name");
+        let shell = self.string(table, "shell");
+        let groups = self.strings(table, "groups");
+        let keys = self.ssh_keys(table);
+
+        let (name, name_line) = name?;
+        let default_shell = variant.map_or("/bin/sh", |v| v.default_shell());
+        let group_names: Vec<&str> = match groups {
+            Some(ref g) => g.iter().map(|(g, _)| g.as_str()).collect(),
+            None => DEFAULT_USER_GROUPS.to_vec(),
+        };
+        let mut user = match shell {
+            Some((ref s, _)) => UserSpec::new(name, s.clone(), &group_names),
+            None => UserSpec::new(name, default_shell, &group_names),
+        };
+        if let Some((full, _)) = full_name.clone() {
+            user = user.with_full_name(full);
+        }
+
+        // Point at the line of the offending field where possible
+        if let Err(reason) = user.validate() {
+            let line = if reason.starts_with("shell") {
+                shell.map_or(name_line, |(_, l)| l)
+            } else if reason.starts_with("full name") {
+                full_name.map_or(name_line, |(_, l)| l)
+            } else if reason.starts_with("group") {
+                groups
+                    .as_ref()
+                    .and_then(|g| g.iter().find(|(g, _)| reason.contains(&format!("'{}'", g))))
+                    .map_or(name_line, |(_, l)| *l)
+            } else {
+                name_line
+            };
+            self.err(line, format!("invalid user: {}", reason));
+            return None;
+        }
+        Some((user, keys))
+    }
+}
+
+// =============================================================================
+// TOML Subset Parser
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    /// Array items with the line each item starts on
+    Array(Vec<(Value, usize)>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "a string",
+            Value::Int(_) => "an integer",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    key: String,
+    value: Value,
+    line: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Table {
+    /// Table name ("" for the top level)
+    name: String,
+    /// Whether this is an `[[array]]` element
+    is_array: bool,
+    /// Line of the header (0 for the top level)
+    line: usize,
+    entries: Vec<Entry>,
+}
+
+impl Table {
+    fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Document {
+    tables: Vec<Table>,
+}
+
+impl Document {
+    fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.iter().find(|t| t.name == name && !t.is_array)
+    }
+}
+
+/// Character cursor over the whole input that tracks line numbers.
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    /// Skip spaces and tabs (not newlines).
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.next();
+        }
+    }
+
+    /// Skip whitespace, newlines and comments.
+    fn skip_all(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => {
+                    self.next();
+                }
+                Some('#') => self.skip_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.next();
+        }
+    }
+
+    fn err(&self, message: impl Into<String>) -> AnswerFileError {
+        AnswerFileError::new(self.line, message)
+    }
+
+    /// After a value or header: only a comment may follow on the same line.
+    fn end_of_line(&mut self) -> Result<(), AnswerFileError> {
+        self.skip_blank();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some('#') => {
+                self.skip_comment();
+                Ok(())
+            }
+            Some(c) => Err(self.err(format!("unexpected '{}' after value", c))),
+        }
+    }
+
+    fn bare_word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+' || c == '.' {
+                word.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        word
+    }
+
+    fn value(&mut self) -> Result<Value, AnswerFileError> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::Str),
+            Some('\'') => self.literal_string().map(Value::Str),
+            Some('[') => self.array(),
+            Some('{') => Err(self.err("inline tables are not supported in answer files")),
+            Some(_) => {
+                let word = self.bare_word();
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "" => Err(self.err("expected a value")),
+                    w => w
+                        .replace('_', "")
+                        .parse::<i64>()
+                        .map(Value::Int)
+                        .map_err(|_| {
+                            self.err(format!("invalid value '{}' (strings must be quoted)", w))
+                        }),
+                }
+            }
+            None => Err(self.err("expected a value")),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, AnswerFileError> {
+        self.next(); // opening quote
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| {
+                                self.err(format!("invalid unicode escape \\u{}", hex))
+                            })?;
+                        s.push(c);
+                    }
+                    Some(c) => return Err(self.err(format!("invalid escape sequence \\{}", c))),
+                    None => return Err(self.err("unterminated string")),
+                },
+                Some('\n') | None => {
+                    return Err(AnswerFileError::new(
+                        self.line.saturating_sub(1).max(1),
+                        "unterminated string",
+                    ))
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, AnswerFileError> {
+        self.next(); // opening quote
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(s),
+                Some('\n') | None => {
+                    return Err(AnswerFileError::new(
+                        self.line.saturating_sub(1).max(1),
+                        "unterminated string",
+                    ))
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, AnswerFileError> {
+        let start = self.line;
+        self.next(); // [
+        let mut items = Vec::new();
+        loop {
+            self.skip_all();
+            match self.peek() {
+                Some(']') => {
+                    self.next();
+                    return Ok(Value::Array(items));
+                }
+                None => return Err(AnswerFileError::new(start, "unterminated array")),
+                _ => {}
+            }
+            let line = self.line;
+            items.push((self.value()?, line));
+            self.skip_all();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some(']') => {}
+                None => return Err(AnswerFileError::new(start, "unterminated array")),
+                Some(c) => {
+                    return Err(self.err(format!("expected ',' or ']' in array, found '{}'", c)))
+                }
+            }
+        }
+    }
+}
+
+fn parse_document(input: &str) -> Result<Document, AnswerFileError> {
+    let mut cur = Cursor {
+        chars: input.chars().peekable(),
+        line: 1,
+    };
+    let mut doc = Document {
+        tables: vec![Table::default()],
+    };
+
+    loop {
+        cur.skip_all();
+        let line = cur.line;
+        match cur.peek() {
+            None => break,
+            Some('[') => {
+                cur.next();
+                let is_array = cur.peek() == Some('[');
+                if is_array {
+                    cur.next();
+                }
+                cur.skip_blank();
+                let name = cur.bare_word();
+                cur.skip_blank();
+                let close = if is_array { "]]" } else { "]" };
+                for _ in 0..close.len() {
+                    if cur.next() != Some(']') {
+                        return Err(AnswerFileError::new(
+                            line,
+                            format!("malformed table header (expected '{}')", close),
+                        ));
+                    }
+                }
+                if name.is_empty() || name.contains('.') {
+                    return Err(AnswerFileError::new(
+                        line,
+                        "table names must be a single bare key",
+                    ));
+                }
+                if !is_array {
+                    if let Some(prev) = doc.tables.iter().find(|t| t.name == name && !t.is_array) {
+                        return Err(AnswerFileError::new(
+                            line,
+                            format!("table [{}] is already defined on line {}", name, prev.line),
+                        ));
+                    }
+                }
+                cur.end_of_line()?;
+                doc.tables.push(Table {
+                    name,
+                    is_array,
+                    line,
+                    entries: Vec::new(),
+                });
+            }
+            Some(_) => {
+                let key = cur.bare_word();
+                if key.is_empty() {
+                    return Err(AnswerFileError::new(line, "expected a key or table header"));
+                }
+                if key.contains('.') {
+                    return Err(AnswerFileError::new(
+                        line,
+                        format!("dotted keys are not supported ('{}')", key),
+                    ));
+                }
+                cur.skip_blank();
+                if cur.next() != Some('=') {
+                    return Err(AnswerFileError::new(
+                        line,
+                        format!("expected '=' after key '{}'", key),
+                    ));
+                }
+                cur.skip_blank();
+                let value = cur.value()?;
+                cur.end_of_line()?;
+                let table = doc
+                    .tables
+                    .last_mut()
+                    .expect("top-level table always present");
+                if let Some(prev) = table.get(&key) {
+                    return Err(AnswerFileError::new(
+                        line,
+                        format!(
+                            "duplicate key '{}' (first defined on line {})",
+                            key, prev.line
+                        ),
+                    ));
+                }
+                table.entries.push(Entry { key, value, line });
+            }
+        }
+    }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::install::{StepAction, StepKind};
+
+    const EXAMPLE: &str = r#"
+# Workstation install
+variant = "levitate"
+
+[disk]
+device = "/dev/nvme0n1"
+filesystem = "xfs"
+encryption = true
+
+[system]
+hostname = "ws01"
+locale = "en_US.UTF-8"
+timezone = "Europe/Amsterdam"
+keymap = "us"
+
+[services]
+enable = [
+    "bluetooth",
+    "sshd",     # already a default
+]
+
+[[users]]
+name = "alice"
+full_name = "Alice Example"
+ssh_keys = ["ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAA alice@laptop"]
+
+[[users]]
+name = "bob"
+groups = ["wheel"]
+"#;
+
+    fn errors(input: &str) -> Vec<AnswerFileError> {
+        InstallRequest::from_toml(input).unwrap_err().0
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let req = InstallRequest::from_toml(EXAMPLE).unwrap();
+        assert_eq!(req.variant, Variant::Levitate);
+        assert_eq!(req.disk, "/dev/nvme0n1");
+        assert_eq!(req.filesystem, "xfs");
+        assert_eq!(req.encryption, Some(RootEncryption::default()));
+        assert_eq!(req.hostname, "ws01");
+        assert_eq!(req.timezone.as_deref(), Some("Europe/Amsterdam"));
+        assert_eq!(req.extra_services, ["bluetooth"]);
+        assert_eq!(req.users.len(), 2);
+        assert_eq!(req.users[0].shell, "/bin/bash");
+        assert_eq!(req.users[0].groups.len(), DEFAULT_USER_GROUPS.len());
+        assert_eq!(req.users[1].groups.as_slice(), ["wheel"]);
+        assert_eq!(req.authorized_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_plan_from_answer_file() {
+        let plan = InstallRequest::from_toml(EXAMPLE).unwrap().plan();
+        let format = plan.step(StepKind::Format).unwrap();
+        assert!(format
+            .actions
+            .iter()
+            .any(|a| a.argv().is_some_and(|argv| argv[0] == "mkfs.xfs")));
+        let services = plan.step(StepKind::Services).unwrap();
+        assert!(services.actions.contains(&StepAction::Chroot {
+            root: "/mnt".into(),
+            command: "systemctl enable bluetooth".into(),
+        }));
+    }
+
+    #[test]
+    fn test_minimal_file_uses_defaults() {
+        let req = InstallRequest::from_toml("variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\n")
+            .unwrap();
+        assert_eq!(req.hostname, "acornos");
+        assert_eq!(req.filesystem, "ext4");
+        assert_eq!(req.preset, "default");
+        assert_eq!(req.profile, Profile::Desktop);
+        assert!(req.encryption.is_none());
+    }
+
+    #[test]
+    fn test_profile() {
+        let req = InstallRequest::from_toml(
+            "variant = \"levitate\"\nprofile = \"minimal\"\n\
+             [disk]\ndevice = \"/dev/vda\"\n\
+             [services]\nenable = [\"sshd\"]\n",
+        )
+        .unwrap();
+        assert_eq!(req.profile, Profile::Minimal);
+        // Not part of the minimal profile, so it is an extra service
+        assert_eq!(req.extra_services, ["sshd"]);
+        let plan = req.plan();
+        let services = plan.step(StepKind::Services).unwrap();
+        for name in ["systemd-networkd", "sshd"] {
+            assert!(services.actions.contains(&StepAction::Chroot {
+                root: "/mnt".into(),
+                command: format!("systemctl enable {}", name),
+            }));
+        }
+
+        let errs =
+            errors("variant = \"acorn\"\nprofile = \"tiny\"\n[disk]\ndevice = \"/dev/vda\"\n");
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].line, 2);
+        assert!(errs[0].message.starts_with("unknown profile 'tiny'"));
+    }
+
+    #[test]
+    fn test_errors_have_line_numbers() {
+        let errs = errors(
+            "variant = \"levitate\"\n\
+             [disk]\n\
+             device = \"sda\"\n\
+             filesystem = \"ntfs\"\n\
+             [system]\n\
+             hostname = \"-bad-\"\n\
+             [services]\n\
+             enable = [\"bluetooth\",\n  \"nope\"]\n\
+             [[users]]\n\
+             name = \"Root\"\n",
+        );
+        let lines: Vec<usize> = errs.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [3, 4, 6, 9, 11]);
+        assert!(errs[3].message.contains("unknown service 'nope'"));
+        assert_eq!(
+            errs[0].to_string(),
+            "line 3: disk device 'sda' must be a path under /dev/"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_and_tables() {
+        let errs = errors(
+            "variant = \"acorn\"\ncolour = \"blue\"\n[disk]\ndevice = \"/dev/vda\"\n[network]\n",
+        );
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs[0].line, 2);
+        assert!(errs[1].message.contains("unknown table [network]"));
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let errs = errors("[system]\nhostname = \"x\"\n");
+        let messages: Vec<&str> = errs.iter().map(|e| e.message.as_str()).collect();
+        assert!(messages.contains(&"missing required key 'variant'"));
+        assert!(messages.contains(&"missing required table [disk]"));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert_eq!(errors("variant = levitate\n")[0].line, 1);
+        assert_eq!(errors("variant = \"acorn\"\n\n[disk\n")[0].line, 3);
+        assert_eq!(errors("variant = \"a\"\nvariant = \"b\"\n")[0].line, 2);
+        assert!(errors("x = { a = 1 }\n")[0]
+            .message
+            .contains("inline tables"));
+        assert_eq!(errors("[services]\nenable = [\"a\",\n\"b\"\n")[0].line, 2);
+    }
+
+    #[test]
+    fn test_ssh_key_validation() {
+        let errs = errors(
+            "variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\n[root]\nssh_keys = [\"ssh-dss AAAA\", \"ssh-ed25519\"]\n",
+        );
+        assert_eq!(errs.len(), 2);
+        assert!(errs[0]
+            .message
+            .contains("unsupported SSH key type 'ssh-dss'"));
+        assert!(errs[1].message.contains("malformed base64"));
+    }
+
+    #[test]
+    fn test_key_file_requires_encryption() {
+        let errs =
+            errors("variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\nkey_file = \"/k\"\n");
+        assert_eq!(errs[0].line, 4);
+    }
+
+    #[test]
+    fn test_user_fields_cannot_inject_commands() {
+        let errs = errors(
+            "variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\n[[users]]\nname = \"a\"\nshell = \"/bin/sh;reboot\"\n",
+        );
+        assert_eq!(errs[0].line, 6);
+        assert!(errs[0].message.starts_with("invalid user: shell"));
+
+        let errs = errors(
+            "variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\n[[users]]\nname = \"a\"\nfull_name = \"A $(reboot)\"\n",
+        );
+        assert_eq!(errs[0].line, 6);
+        assert!(errs[0].message.starts_with("invalid user: full name"));
+    }
+
+    #[test]
+    fn test_duplicate_users() {
+        let errs = errors(
+            "variant = \"acorn\"\n[disk]\ndevice = \"/dev/vda\"\n[[users]]\nname = \"a\"\n[[users]]\nname = \"a\"\n",
+        );
+        assert_eq!(errs[0].line, 6);
+        assert!(errs[0].message.contains("already defined on line 4"));
+    }
+
+    #[test]
+    fn test_check_disk_size() {
+        let req =
+            InstallRequest::from_toml("variant = \"levitate\"\n\n[disk]\ndevice = \"/dev/sda\"\n")
+                .unwrap();
+        let err = req.check_disk_size(32 * BYTES_PER_GB).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert!(err.message.contains("requires at least 64 GB"));
+        assert!(req.check_disk_size(64 * BYTES_PER_GB).is_ok());
+    }
+}
@@ -4,6 +4,8 @@
 //! authentication subsystem. These are extracted from `components.rs` for
 //! centralized authentication configuration.
 
+use crate::shared::registry::SSH;
+
 /// Authentication binaries for /usr/bin.
 ///
 /// Implements privilege escalation and user management.
@@ -37,12 +39,12 @@ pub const SHADOW_SBIN: &[&str] = &[
 /// SSH binaries in /usr/sbin.
 ///
 /// Implements remote login capability for the system.
-pub const SSH_SBIN: &[&str] = &["sshd"];
+pub const SSH_SBIN: &[&str] = SSH.sbin;
 
 /// SSH binaries in /usr/bin.
 ///
 /// Client-side SSH utilities for users.
-pub const SSH_BIN: &[&str] = &["ssh", "scp", "sftp", "ssh-keygen", "ssh-add", "ssh-agent"];
+pub const SSH_BIN: &[&str] = SSH.bin;
 
 /// PAM modules in /usr/lib64/security/.
 ///
@@ -1,6 +1,9 @@
 #!/bin/bash
 # LevitateOS test mode instrumentation
 # Activates ONLY on serial console (ttyS0) - test harness environment
+#
+# Generated by distro_spec::shared::test_protocol::profile_snippet.
+# Change the protocol there, not here.
 
 # Only run in interactive shells
 [[ $- != *i* ]] && return
@@ -17,9 +20,17 @@ fi
 # TEST MODE ACTIVE - Minimal instrumentation for test harness
 # ═══════════════════════════════════════════════════════════════════
 
+# Run a command between start/end markers: __lt_run <id> <argv...>
+__lt_run() {
+    local id=$1 rc
+    shift
+    printf '___CMD_START___ %s\n' "$id"
+    "$@"
+    rc=$?
+    printf '___CMD_END___ %s %s\n' "$id" "$rc"
+}
+
 # Signal shell is ready - test harness waits for this
-# recqemu's exec.rs has its own command marker protocol, so we don't need
-# DEBUG traps here. Just emit the readiness marker.
 echo "___SHELL_READY___"
 
 # Emit prompt marker for first command
@@ -5,6 +5,8 @@
 
 use std::borrow::Cow;
 
+use super::boot_counting::counted_filename;
+
 // =============================================================================
 // Constants
 // =============================================================================
@@ -145,6 +147,14 @@ impl BootEntry {
         format!("{}/{}.conf", ENTRIES_DIR, self.filename)
     }
 
+    /// Entry path with a boot counter (`/boot/loader/entries/<name>+3.conf`).
+    ///
+    /// See `boot_counting` for how systemd-boot uses the counter.
+    pub fn counted_entry_path(&self, tries: u32) -> String {
+        let filename = counted_filename(&format!("{}.conf", self.filename), tries);
+        format!("{}/{}", ENTRIES_DIR, filename)
+    }
+
     /// Generate the entry file contents.
     pub fn to_entry_file(&self) -> String {
         format!(
@@ -160,6 +170,12 @@ impl BootEntry {
         self
     }
 
+    /// Append a kernel command line option.
+    pub fn with_option(mut self, option: impl AsRef<str>) -> Self {
+        self.options = Cow::Owned(format!("{} {}", self.options, option.as_ref()));
+        self
+    }
+
     /// Update the root device in options.
     pub fn set_root(mut self, root_device: impl Into<String>) -> Self {
         self.options = Cow::Owned(format!(
@@ -179,7 +195,7 @@ impl BootEntry {
 /// Uses `Cow<'static, str>` where values are often static strings.
 #[derive(Debug, Clone)]
 pub struct LoaderConfig {
-    /// Default entry to boot (filename without .conf)
+    /// Default entry to boot (filename without .conf, a UKI name ending in .efi, or a glob)
     pub default_entry: Cow<'static, str>,
     /// Timeout in seconds (0 = no menu)
     pub timeout: u32,
@@ -187,6 +203,34 @@ pub struct LoaderConfig {
     pub console_mode: Option<Cow<'static, str>>,
     /// Editor enabled (allows kernel cmdline editing)
     pub editor: bool,
+    /// Secure Boot key enrollment from `loader/keys/` (None = systemd-boot default)
+    pub secure_boot_enroll: Option<SecureBootEnroll>,
+}
+
+/// `secure-boot-enroll` setting: what systemd-boot does with key sets in
+/// `loader/keys/` while the firmware is in setup mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecureBootEnroll {
+    /// Never enroll
+    Off,
+    /// Offer key sets in the boot menu
+    Manual,
+    /// Enroll automatically in virtual machines only
+    IfSafe,
+    /// Enroll automatically (after a 15 second countdown)
+    Force,
+}
+
+impl SecureBootEnroll {
+    /// Value as written in loader.conf.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecureBootEnroll::Off => "off",
+            SecureBootEnroll::Manual => "manual",
+            SecureBootEnroll::IfSafe => "if-safe",
+            SecureBootEnroll::Force => "force",
+        }
+    }
 }
 
 impl LoaderConfig {
@@ -197,15 +241,22 @@ impl LoaderConfig {
             timeout: DEFAULT_TIMEOUT,
             console_mode: Some(Cow::Borrowed("auto")),
             editor: true,
+            secure_boot_enroll: None,
         }
     }
 
     /// Generate the loader.conf contents.
     pub fn to_loader_conf(&self) -> String {
-        let mut conf = format!(
-            "default {}.conf\ntimeout {}\n",
-            self.default_entry, self.timeout
-        );
+        // UKIs and globs ending in `*` are written as is
+        let entry = &self.default_entry;
+        let mut conf = if entry.ends_with(".efi") || entry.ends_with('*') {
+            format!("default {}\ntimeout {}\n", self.default_entry, self.timeout)
+        } else {
+            format!(
+                "default {}.conf\ntimeout {}\n",
+                self.default_entry, self.timeout
+            )
+        };
 
         if let Some(ref mode) = self.console_mode {
             conf.push_str(&format!("console-mode {}\n", mode));
@@ -215,9 +266,19 @@ impl LoaderConfig {
             conf.push_str("editor no\n");
         }
 
+        if let Some(enroll) = self.secure_boot_enroll {
+            conf.push_str(&format!("secure-boot-enroll {}\n", enroll.as_str()));
+        }
+
         conf
     }
 
+    /// Set the default entry.
+    pub fn with_default_entry(mut self, entry: impl Into<Cow<'static, str>>) -> Self {
+        self.default_entry = entry.into();
+        self
+    }
+
     /// Set timeout.
     pub fn with_timeout(mut self, timeout: u32) -> Self {
         self.timeout = timeout;
@@ -230,6 +291,12 @@ impl LoaderConfig {
         self
     }
 
+    /// Set how Secure Boot keys on the ESP are enrolled.
+    pub fn with_secure_boot_enroll(mut self, mode: SecureBootEnroll) -> Self {
+        self.secure_boot_enroll = Some(mode);
+        self
+    }
+
     /// Disable editor.
     pub fn disable_editor(mut self) -> Self {
         self.editor = false;
@@ -267,7 +334,8 @@ mod tests {
     #[test]
     fn loader_config_size() {
         let size = std::mem::size_of::<LoaderConfig>();
-        // Cow<str> (24) + u32 (4) + Option<Cow<str>> (24) + bool (1) + padding
+        // Cow<str> (24) + u32 (4) + Option<Cow<str>> (24) + bool (1)
+        // + Option<SecureBootEnroll> (1) + padding
         assert!(
             size <= 64,
             "LoaderConfig grew too large: {} bytes (max 64)",
@@ -0,0 +1,433 @@
+//! systemd-boot automatic boot assessment (boot counting).
+//!
+//! A boot entry or UKI whose filename carries a `+LEFT[-DONE]` counter before
+//! the extension is "on probation". On every attempt systemd-boot renames it,
+//! moving one try from LEFT to DONE; once LEFT reaches 0 the entry is "bad"
+//! and sorted below every other entry, so the next boot falls back to the
+//! previous kernel. When userspace comes up cleanly the counter is removed
+//! from the filename ("blessed") and the entry is good for good.
+//!
+//! ```text
+//! levitateos-6.12.5+3.efi          -> new, 3 tries
+//! levitateos-6.12.5+2-1.efi        -> booted once, not yet blessed
+//! levitateos-6.12.5+0-3.efi        -> bad, skipped
+//! levitateos-6.12.5.efi            -> blessed
+//! ```
+//!
+//! LevitateOS blesses through `systemd-bless-boot.service`; AcornOS has no
+//! systemd and uses an OpenRC script instead (see `acorn::boot`). Both read
+//! the `LoaderBootCountPath` EFI variable that systemd-boot sets to the
+//! counted file it booted.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::boot::ESP_MOUNT_POINT;
+use super::uki::UKI_EFI_DIR;
+
+/// Tries given to a freshly installed entry.
+pub const DEFAULT_BOOT_TRIES: u32 = 3;
+
+/// Directory of EFI variables in sysfs (relative to `/`).
+pub const EFIVARS_DIR: &str = "sys/firmware/efi/efivars";
+
+/// EFI variable naming the counted file systemd-boot booted (loader vendor GUID).
+pub const LOADER_BOOT_COUNT_PATH_VAR: &str =
+    "LoaderBootCountPath-4a67b082-0a4c-41cf-b6c7-440b29bb8c4f";
+
+/// Loader entries directory relative to the ESP.
+pub const ESP_ENTRIES_DIR: &str = "loader/entries";
+
+// =============================================================================
+// Counter
+// =============================================================================
+
+/// Counter embedded in a filename.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootCounter {
+    /// Tries left before the entry is considered bad
+    pub left: u32,
+    /// Tries already used
+    pub done: u32,
+}
+
+impl BootCounter {
+    /// Counter for a new entry.
+    pub fn new(tries: u32) -> Self {
+        Self {
+            left: tries,
+            done: 0,
+        }
+    }
+
+    /// Filename suffix (`+3` or `+2-1`).
+    pub fn suffix(&self) -> String {
+        if self.done == 0 {
+            format!("+{}", self.left)
+        } else {
+            format!("+{}-{}", self.left, self.done)
+        }
+    }
+}
+
+/// Assessment state of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootAssessment {
+    /// No counter: blessed or never counted
+    Good,
+    /// Counter with tries left: not yet confirmed
+    Indeterminate { left: u32, done: u32 },
+    /// Counter exhausted: skipped unless nothing else is bootable
+    Bad { done: u32 },
+}
+
+impl fmt::Display for BootAssessment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootAssessment::Good => write!(f, "good"),
+            BootAssessment::Indeterminate { left, done } => {
+                write!(f, "indeterminate ({} left, {} done)", left, done)
+            }
+            BootAssessment::Bad { done } => write!(f, "bad (after {} tries)", done),
+        }
+    }
+}
+
+// =============================================================================
+// Counted Filenames
+// =============================================================================
+
+/// A filename split into base, optional counter and extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountedName {
+    /// Name without counter and extension (the entry ID stem)
+    pub base: String,
+    /// Counter, if any
+    pub counter: Option<BootCounter>,
+    /// Extension including the dot (".conf", ".efi")
+    pub extension: String,
+}
+
+impl CountedName {
+    /// Split a filename. Names without an extension are not entries.
+    pub fn parse(filename: &str) -> Option<Self> {
+        let dot = filename.rfind('.')?;
+        let (stem, extension) = filename.split_at(dot);
+        let counter = stem.rfind('+').and_then(|plus| {
+            let spec = &stem[plus + 1..];
+            let (left, done) = match spec.split_once('-') {
+                Some((l, d)) => (l, Some(d)),
+                None => (spec, None),
+            };
+            let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+            if !digits(left) || !done.is_none_or(digits) {
+                return None;
+            }
+            let counter = BootCounter {
+                left: left.parse().ok()?,
+                done: done.map_or(Some(0), |d| d.parse().ok())?,
+            };
+            Some((plus, counter))
+        });
+        Some(match counter {
+            Some((plus, counter)) => Self {
+                base: stem[..plus].to_string(),
+                counter: Some(counter),
+                extension: extension.to_string(),
+            },
+            None => Self {
+                base: stem.to_string(),
+                counter: None,
+                extension: extension.to_string(),
+            },
+        })
+    }
+
+    /// Reassemble the filename.
+    pub fn filename(&self) -> String {
+        match self.counter {
+            Some(c) => format!("{}{}{}", self.base, c.suffix(), self.extension),
+            None => format!("{}{}", self.base, self.extension),
+        }
+    }
+
+    /// Filename after blessing (counter removed).
+    pub fn blessed(&self) -> String {
+        format!("{}{}", self.base, self.extension)
+    }
+
+    /// Filename marked bad (no tries left).
+    pub fn marked_bad(&self) -> String {
+        let done = self.counter.map_or(0, |c| c.left + c.done);
+        let c = BootCounter { left: 0, done };
+        format!("{}{}{}", self.base, c.suffix(), self.extension)
+    }
+
+    /// Assessment state implied by the counter.
+    pub fn assessment(&self) -> BootAssessment {
+        match self.counter {
+            None => BootAssessment::Good,
+            Some(BootCounter { left: 0, done }) => BootAssessment::Bad { done },
+            Some(BootCounter { left, done }) => BootAssessment::Indeterminate { left, done },
+        }
+    }
+}
+
+/// Add a `+tries` counter to a filename (`levitateos.efi` -> `levitateos+3.efi`).
+///
+/// An existing counter is replaced.
+pub fn counted_filename(filename: &str, tries: u32) -> String {
+    match CountedName::parse(filename) {
+        Some(mut name) => {
+            name.counter = Some(BootCounter::new(tries));
+            name.filename()
+        }
+        None => format!("{}+{}", filename, tries),
+    }
+}
+
+/// loader.conf `default` glob that picks the newest good entry of the OS.
+///
+/// Matches UKIs and Type #1 entries, versioned or not (`<os_id>.efi`,
+/// `<os_id>-<kver>.efi`, `<os_id>.conf`, ...), but not recovery images,
+/// which start with `kernels::RECOVERY_PREFIX`. systemd-boot matches
+/// `default` against entry IDs with the counter stripped and sorts bad
+/// entries last, so a failed update falls back to the previous version on
+/// the next boot.
+pub fn counted_default_pattern(os_id: &str) -> String {
+    format!("{}*", os_id)
+}
+
+// =============================================================================
+// ESP State
+// =============================================================================
+
+/// A boot entry or UKI found on the ESP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountedBootFile {
+    /// Path relative to the ESP (e.g., "EFI/Linux/levitateos-6.12.5+2-1.efi")
+    pub path: String,
+    /// Parsed filename
+    pub name: CountedName,
+}
+
+impl CountedBootFile {
+    /// Assessment state of this file.
+    pub fn assessment(&self) -> BootAssessment {
+        self.name.assessment()
+    }
+}
+
+/// Boot-counting state of an ESP.
+#[derive(Debug, Clone)]
+pub struct BootCounting {
+    esp: PathBuf,
+}
+
+impl Default for BootCounting {
+    fn default() -> Self {
+        Self::new(ESP_MOUNT_POINT)
+    }
+}
+
+impl BootCounting {
+    /// Inspect the ESP mounted at `esp`.
+    pub fn new(esp: impl Into<PathBuf>) -> Self {
+        Self { esp: esp.into() }
+    }
+
+    /// All type #1 entries (`loader/entries/*.conf`) and UKIs (`EFI/Linux/*.efi`).
+    pub fn scan(&self) -> io::Result<Vec<CountedBootFile>> {
+        let mut files = Vec::new();
+        for (dir, ext) in [(ESP_ENTRIES_DIR, ".conf"), (UKI_EFI_DIR, ".efi")] {
+            let entries = match fs::read_dir(self.esp.join(dir)) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for entry in entries {
+                let entry = entry?;
+                let filename = entry.file_name().to_string_lossy().into_owned();
+                if !filename.ends_with(ext) || !entry.file_type()?.is_file() {
+                    continue;
+                }
+                if let Some(name) = CountedName::parse(&filename) {
+                    files.push(CountedBootFile {
+                        path: format!("{}/{}", dir, filename),
+                        name,
+                    });
+                }
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Files that have run out of tries.
+    pub fn bad_entries(&self) -> io::Result<Vec<CountedBootFile>> {
+        Ok(self
+            .scan()?
+            .into_iter()
+            .filter(|f| matches!(f.assessment(), BootAssessment::Bad { .. }))
+            .collect())
+    }
+
+    /// Remove the counter from `path` (relative to the ESP). Returns the new path.
+    pub fn bless(&self, path: &str) -> io::Result<String> {
+        self.rename(path, CountedName::blessed)
+    }
+
+    /// Set the tries left for `path` to zero. Returns the new path.
+    pub fn mark_bad(&self, path: &str) -> io::Result<String> {
+        self.rename(path, CountedName::marked_bad)
+    }
+
+    fn rename(&self, path: &str, f: fn(&CountedName) -> String) -> io::Result<String> {
+        let (dir, filename) = path.rsplit_once('/').unwrap_or(("", path));
+        let name = CountedName::parse(filename).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("not an entry: {}", path),
+            )
+        })?;
+        let new_path = if dir.is_empty() {
+            f(&name)
+        } else {
+            format!("{}/{}", dir, f(&name))
+        };
+        if new_path != path {
+            fs::rename(self.esp.join(path), self.esp.join(&new_path))?;
+        }
+        Ok(new_path)
+    }
+}
+
+/// Counted file the current boot came from, read from `LoaderBootCountPath`.
+///
+/// Returns the ESP-relative path, or `None` when the system wasn't booted
+/// from a counted entry. `root` is the filesystem root (for testing).
+pub fn loader_boot_count_path(root: &Path) -> Option<String> {
+    let data = fs::read(root.join(EFIVARS_DIR).join(LOADER_BOOT_COUNT_PATH_VAR)).ok()?;
+    // 4-byte attribute header, then a NUL-terminated UTF-16LE string
+    let units: Vec<u16> = data
+        .get(4..)?
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&u| u != 0)
+        .collect();
+    let path = String::from_utf16(&units).ok()?.replace('\\', "/");
+    let path = path.trim_start_matches('/');
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::boot::{BootEntry, LoaderConfig};
+    use crate::shared::kernels::{KernelVersion, VersionedBootFiles};
+    use crate::shared::test_util::TempDir;
+    use crate::shared::uki::{UKI_INSTALLED_FILENAME, UKI_INSTALLED_RECOVERY_FILENAME};
+
+    #[test]
+    fn test_parse_counted_names() {
+        let n = CountedName::parse("levitateos-6.12.5+2-1.efi").unwrap();
+        assert_eq!(n.base, "levitateos-6.12.5");
+        assert_eq!(n.counter, Some(BootCounter { left: 2, done: 1 }));
+        assert_eq!(n.filename(), "levitateos-6.12.5+2-1.efi");
+        assert_eq!(n.blessed(), "levitateos-6.12.5.efi");
+        assert_eq!(n.marked_bad(), "levitateos-6.12.5+0-3.efi");
+
+        let n = CountedName::parse("acornos+3.conf").unwrap();
+        assert_eq!(
+            n.assessment(),
+            BootAssessment::Indeterminate { left: 3, done: 0 }
+        );
+        assert_eq!(
+            CountedName::parse("x+0-3.conf").unwrap().assessment(),
+            BootAssessment::Bad { done: 3 }
+        );
+        // "+" that isn't a counter stays part of the name
+        let n = CountedName::parse("levitateos-6.12.5+rt.efi").unwrap();
+        assert_eq!(n.base, "levitateos-6.12.5+rt");
+        assert_eq!(n.assessment(), BootAssessment::Good);
+    }
+
+    #[test]
+    fn test_counted_filename() {
+        assert_eq!(counted_filename("levitateos.efi", 3), "levitateos+3.efi");
+        assert_eq!(
+            counted_filename("levitateos+0-3.efi", 2),
+            "levitateos+2.efi"
+        );
+        let entry =
+            BootEntry::with_defaults("levitateos", "LevitateOS", "vmlinuz", "initramfs.img");
+        assert_eq!(
+            entry.counted_entry_path(DEFAULT_BOOT_TRIES),
+            "/boot/loader/entries/levitateos+3.conf"
+        );
+        let conf = LoaderConfig::with_defaults("levitateos")
+            .with_default_entry(counted_default_pattern("levitateos"))
+            .to_loader_conf();
+        assert!(conf.starts_with("default levitateos*\n"));
+
+        // The glob is a plain prefix match; recovery images must fall outside it
+        let prefix = counted_default_pattern("levitateos").replace('*', "");
+        let files = VersionedBootFiles::new("levitateos", KernelVersion::parse("6.12.5").unwrap());
+        for name in [UKI_INSTALLED_FILENAME, &files.uki_filename()] {
+            assert!(name.starts_with(&prefix), "{}", name);
+        }
+        for name in [
+            UKI_INSTALLED_RECOVERY_FILENAME,
+            &files.recovery_uki_filename(),
+        ] {
+            assert!(!name.starts_with(&prefix), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_scan_and_bless() {
+        let dir = TempDir::new("boot-counting");
+        dir.write("EFI/Linux/levitateos-6.12.5+1-2.efi", b"new");
+        dir.write("EFI/Linux/levitateos-6.12.4.efi", b"old");
+        dir.write("EFI/Linux/levitateos-6.12.3+0-3.efi", b"broken");
+        dir.write("loader/entries/levitateos-6.12.5+3.conf", b"");
+        dir.write("loader/entries/README", b"");
+
+        let esp = BootCounting::new(dir.path());
+        let files = esp.scan().unwrap();
+        assert_eq!(files.len(), 4);
+        let bad = esp.bad_entries().unwrap();
+        assert_eq!(bad.len(), 1);
+        assert_eq!(bad[0].path, "EFI/Linux/levitateos-6.12.3+0-3.efi");
+
+        let blessed = esp.bless("EFI/Linux/levitateos-6.12.5+1-2.efi").unwrap();
+        assert_eq!(blessed, "EFI/Linux/levitateos-6.12.5.efi");
+        assert!(dir.path().join(&blessed).is_file());
+
+        let bad = esp
+            .mark_bad("loader/entries/levitateos-6.12.5+3.conf")
+            .unwrap();
+        assert_eq!(bad, "loader/entries/levitateos-6.12.5+0-3.conf");
+    }
+
+    #[test]
+    fn test_loader_boot_count_path() {
+        let dir = TempDir::new("boot-count-var");
+        assert_eq!(loader_boot_count_path(dir.path()), None);
+
+        let mut data = vec![6, 0, 0, 0];
+        for u in "\\EFI\\Linux\\levitateos-6.12.5+2-1.efi\0".encode_utf16() {
+            data.extend_from_slice(&u.to_le_bytes());
+        }
+        dir.write(
+            &format!("{}/{}", EFIVARS_DIR, LOADER_BOOT_COUNT_PATH_VAR),
+            &data,
+        );
+        assert_eq!(
+            loader_boot_count_path(dir.path()).as_deref(),
+            Some("EFI/Linux/levitateos-6.12.5+2-1.efi")
+        );
+    }
+}
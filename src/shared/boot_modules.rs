@@ -5,6 +5,49 @@
 //! (unlike `modprobe` which resolves them automatically).
 //!
 //! Paths are relative to `/lib/modules/<kernel-version>/`.
+//!
+//! The ordering is checked at compile time against `MODULE_DEPENDENCIES`
+//! (see `const_check`), so a list that loads a module too early does not build.
+
+use super::const_check::static_check;
+
+// =============================================================================
+// Module Dependencies
+// =============================================================================
+
+/// Load-order dependencies between the boot modules (module, its dependencies).
+///
+/// Only modules that appear in the boot lists are covered. A list must load
+/// each dependency it contains before the module that needs it.
+pub const MODULE_DEPENDENCIES: &[(&str, &[&str])] = &[
+    // Virtio
+    ("kernel/drivers/virtio/virtio_pci", &["kernel/drivers/virtio/virtio", "kernel/drivers/virtio/virtio_ring"]),
+    ("kernel/drivers/scsi/virtio_scsi", &["kernel/drivers/virtio/virtio", "kernel/drivers/virtio/virtio_ring", "kernel/drivers/scsi/scsi_mod"]),
+    ("kernel/drivers/block/virtio_blk", &["kernel/drivers/virtio/virtio", "kernel/drivers/virtio/virtio_ring"]),
+    // SCSI/CDROM
+    ("kernel/drivers/scsi/sr_mod", &["kernel/drivers/scsi/scsi_mod", "kernel/drivers/cdrom/cdrom"]),
+    ("kernel/drivers/scsi/sd_mod", &["kernel/drivers/scsi/scsi_mod"]),
+    // NVMe
+    ("kernel/drivers/nvme/host/nvme", &["kernel/drivers/nvme/host/nvme-core"]),
+    // SATA
+    ("kernel/drivers/ata/libahci", &["kernel/drivers/ata/libata"]),
+    ("kernel/drivers/ata/ahci", &["kernel/drivers/ata/libata", "kernel/drivers/ata/libahci"]),
+    ("kernel/drivers/ata/ata_piix", &["kernel/drivers/ata/libata"]),
+    // USB
+    ("kernel/drivers/usb/core/usbcore", &["kernel/drivers/usb/common/usb-common"]),
+    ("kernel/drivers/usb/host/xhci-hcd", &["kernel/drivers/usb/core/usbcore"]),
+    ("kernel/drivers/usb/host/xhci-pci", &["kernel/drivers/usb/core/usbcore", "kernel/drivers/usb/host/xhci-hcd"]),
+    ("kernel/drivers/usb/host/ehci-hcd", &["kernel/drivers/usb/core/usbcore"]),
+    ("kernel/drivers/usb/host/ehci-pci", &["kernel/drivers/usb/core/usbcore", "kernel/drivers/usb/host/ehci-hcd"]),
+    ("kernel/drivers/usb/storage/usb-storage", &["kernel/drivers/usb/core/usbcore", "kernel/drivers/scsi/scsi_mod"]),
+    // HID
+    ("kernel/drivers/hid/hid-generic", &["kernel/drivers/hid/hid"]),
+    ("kernel/drivers/hid/usbhid/usbhid", &["kernel/drivers/hid/hid", "kernel/drivers/usb/core/usbcore"]),
+    // Filesystems
+    ("kernel/fs/vfat/vfat", &["kernel/fs/fat/fat"]),
+    // Device mapper
+    ("kernel/drivers/md/dm-crypt", &["kernel/drivers/md/dm-mod"]),
+];
 
 // =============================================================================
 // Core Boot Modules (required by both distros)
@@ -124,6 +167,7 @@ pub const CORE_BOOT_MODULES: &[&str] = &[
     "kernel/fs/squashfs/squashfs",
     "kernel/fs/overlayfs/overlay",
 ];
+static_check!(boot_modules CORE_BOOT_MODULES);
 
 /// USB boot modules - add these to CORE_BOOT_MODULES for USB boot support.
 ///
@@ -146,6 +190,7 @@ pub const USB_BOOT_MODULES: &[&str] = &[
     "kernel/drivers/hid/hid-generic",
     "kernel/drivers/hid/usbhid/usbhid",
 ];
+static_check!(boot_modules USB_BOOT_MODULES);
 
 // =============================================================================
 // Install Boot Modules (for installed systems)
@@ -213,3 +258,4 @@ pub const INSTALL_BOOT_MODULES: &[&str] = &[
     "kernel/drivers/md/dm-mod",
     "kernel/drivers/md/dm-crypt",
 ];
+static_check!(boot_modules INSTALL_BOOT_MODULES);
@@ -0,0 +1,745 @@
+//! Streaming SHA-256/SHA-512 and `sha512sum`-compatible sidecar files.
+//!
+//! Release artifacts (ISO, qcow2, tarball) ship with a sidecar next to them:
+//!
+//! ```text
+//! levitateos-x86_64.iso.sha512:
+//! <128 hex digits><SHA512_SEPARATOR>levitateos-x86_64.iso
+//! ```
+//!
+//! which `sha512sum -c` accepts. The release pipeline writes these with
+//! `write_sidecar()`; the installer's media check and CI verify them with
+//! `verify_sidecar()`. Multi-entry files (`SHA512SUMS`) use the same format.
+//!
+//! The digests are implemented here (FIPS 180-4) so this crate stays free of
+//! crypto dependencies; they are not constant-time and are only meant for
+//! integrity checks.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use super::iso::{ISO_CHECKSUM_SUFFIX, SHA512_SEPARATOR};
+
+/// Sidecar suffix for SHA-256 checksums.
+pub const SHA256_CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// Read buffer size for streaming hashes.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// =============================================================================
+// Digests
+// =============================================================================
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+#[rustfmt::skip]
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+#[rustfmt::skip]
+const SHA512_INIT: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+fn sha512_compress(state: &mut [u64; 8], block: &[u8]) {
+    let mut w = [0u64; 80];
+    for (i, word) in block.chunks_exact(8).enumerate() {
+        w[i] = u64::from_be_bytes(word.try_into().unwrap());
+    }
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA512_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// Supported checksum algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Sidecar file suffix (`ISO_CHECKSUM_SUFFIX` for SHA-512).
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => SHA256_CHECKSUM_SUFFIX,
+            HashAlgorithm::Sha512 => ISO_CHECKSUM_SUFFIX,
+        }
+    }
+
+    /// Length of the hex digest.
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 64,
+            HashAlgorithm::Sha512 => 128,
+        }
+    }
+
+    /// Guess the algorithm from a hex digest's length.
+    pub fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            64 => Some(HashAlgorithm::Sha256),
+            128 => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Name as used by coreutils (`sha256sum`, `sha512sum`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+#[derive(Clone)]
+enum State {
+    Sha256([u32; 8]),
+    Sha512([u64; 8]),
+}
+
+/// Incremental SHA-256/SHA-512 hasher.
+#[derive(Clone)]
+pub struct Hasher {
+    state: State,
+    buffer: Vec<u8>,
+    length: u128,
+}
+
+impl Hasher {
+    /// Start a new digest.
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        let state = match algorithm {
+            HashAlgorithm::Sha256 => State::Sha256(SHA256_INIT),
+            HashAlgorithm::Sha512 => State::Sha512(SHA512_INIT),
+        };
+        Self {
+            state,
+            buffer: Vec::with_capacity(128),
+            length: 0,
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        match self.state {
+            State::Sha256(_) => 64,
+            State::Sha512(_) => 128,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        match &mut self.state {
+            State::Sha256(s) => sha256_compress(s, block),
+            State::Sha512(s) => sha512_compress(s, block),
+        }
+    }
+
+    /// Feed more data.
+    pub fn update(&mut self, mut data: &[u8]) {
+        let bs = self.block_size();
+        self.length += data.len() as u128;
+        if !self.buffer.is_empty() {
+            let take = (bs - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < bs {
+                return;
+            }
+            let block = std::mem::take(&mut self.buffer);
+            self.compress(&block);
+            self.buffer = block;
+            self.buffer.clear();
+        }
+        let mut blocks = data.chunks_exact(bs);
+        for block in &mut blocks {
+            self.compress(block);
+        }
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    /// Finish and return the raw digest (32 or 64 bytes).
+    pub fn finalize(mut self) -> Vec<u8> {
+        let bs = self.block_size();
+        let len_bytes = bs / 8; // 8 bytes of length for SHA-256, 16 for SHA-512
+        let bit_len = self.length * 8;
+        let mut tail = std::mem::take(&mut self.buffer);
+        tail.push(0x80);
+        while tail.len() % bs != bs - len_bytes {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&bit_len.to_be_bytes()[16 - len_bytes..]);
+        for block in tail.chunks_exact(bs) {
+            self.compress(block);
+        }
+        match self.state {
+            State::Sha256(s) => s.iter().flat_map(|w| w.to_be_bytes()).collect(),
+            State::Sha512(s) => s.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        }
+    }
+
+    /// Finish and return the lowercase hex digest.
+    pub fn finalize_hex(self) -> String {
+        self.finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Hash everything from a reader, streaming in fixed-size chunks.
+pub fn hash_reader<R: Read>(algorithm: HashAlgorithm, mut reader: R) -> io::Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(hasher.finalize_hex())
+}
+
+/// Hash a file.
+pub fn hash_file(algorithm: HashAlgorithm, path: impl AsRef<Path>) -> io::Result<String> {
+    hash_reader(algorithm, File::open(path)?)
+}
+
+// =============================================================================
+// Sidecar Files
+// =============================================================================
+
+/// One line of a checksum file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// Lowercase hex digest
+    pub digest: String,
+    /// Filename as written (relative to the checksum file's directory)
+    pub filename: String,
+}
+
+impl ChecksumEntry {
+    /// Algorithm implied by the digest length.
+    pub fn algorithm(&self) -> Option<HashAlgorithm> {
+        HashAlgorithm::from_hex_len(self.digest.len())
+    }
+}
+
+impl fmt::Display for ChecksumEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.digest, SHA512_SEPARATOR, self.filename)
+    }
+}
+
+/// Why a checksum file failed to parse or verify.
+#[derive(Debug)]
+pub enum ChecksumError {
+    /// Reading the checksum file or an artifact failed
+    Io { path: PathBuf, source: io::Error },
+    /// Malformed line (1-based)
+    Parse { line: usize, message: String },
+    /// The checksum file lists no files
+    Empty(PathBuf),
+    /// One or more entries did not verify
+    Failed(Vec<ChecksumFailure>),
+}
+
+/// A single entry that did not verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChecksumFailure {
+    /// File listed in the checksum file does not exist
+    Missing { filename: String },
+    /// File exists but its digest differs
+    Mismatch {
+        filename: String,
+        expected: String,
+        actual: String,
+    },
+    /// Sidecar has no entry for the artifact it belongs to
+    NotListed { filename: String },
+}
+
+impl fmt::Display for ChecksumFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumFailure::Missing { filename } => write!(f, "{}: missing", filename),
+            ChecksumFailure::NotListed { filename } => {
+                write!(f, "{}: not listed in the checksum file", filename)
+            }
+            ChecksumFailure::Mismatch {
+                filename,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}: checksum mismatch (expected {}, got {})",
+                filename, expected, actual
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ChecksumError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            ChecksumError::Empty(path) => write!(f, "{}: no checksum entries", path.display()),
+            ChecksumError::Failed(failures) => {
+                write!(
+                    f,
+                    "{} of the listed files failed verification",
+                    failures.len()
+                )?;
+                for failure in failures {
+                    write!(f, "\n  {}", failure)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+/// Parse a `sha256sum`/`sha512sum` file.
+///
+/// Accepts text (`<digest>  <file>`) and binary (`<digest> *<file>`) mode
+/// lines; blank lines are skipped. All digests must use the same algorithm.
+pub fn parse_checksums(contents: &str) -> Result<Vec<ChecksumEntry>, ChecksumError> {
+    let mut entries = Vec::new();
+    let mut algorithm = None;
+    for (i, line) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let err = |message: String| ChecksumError::Parse {
+            line: line_no,
+            message,
+        };
+        let (digest, rest) = line
+            .split_once(' ')
+            .ok_or_else(|| err("expected \"<digest>  <filename>\"".to_string()))?;
+        let filename = rest
+            .strip_prefix(' ')
+            .or_else(|| rest.strip_prefix('*'))
+            .ok_or_else(|| err("digest must be followed by two spaces or \" *\"".to_string()))?;
+        if filename.is_empty() {
+            return Err(err("missing filename".to_string()));
+        }
+        if !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(err(format!("digest {:?} is not hexadecimal", digest)));
+        }
+        let alg = HashAlgorithm::from_hex_len(digest.len()).ok_or_else(|| {
+            err(format!(
+                "digest has {} hex digits; expected 64 (SHA-256) or 128 (SHA-512)",
+                digest.len()
+            ))
+        })?;
+        if *algorithm.get_or_insert(alg) != alg {
+            return Err(err("mixed SHA-256 and SHA-512 digests".to_string()));
+        }
+        entries.push(ChecksumEntry {
+            digest: digest.to_ascii_lowercase(),
+            filename: filename.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Sidecar path for an artifact (e.g., `foo.iso` → `foo.iso.sha512`).
+pub fn sidecar_path(artifact: impl AsRef<Path>, algorithm: HashAlgorithm) -> PathBuf {
+    let mut path = artifact.as_ref().as_os_str().to_owned();
+    path.push(algorithm.suffix());
+    PathBuf::from(path)
+}
+
+fn io_err(path: &Path) -> impl FnOnce(io::Error) -> ChecksumError + '_ {
+    move |source| ChecksumError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn file_name(artifact: &Path) -> Result<String, ChecksumError> {
+    artifact
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| ChecksumError::Io {
+            path: artifact.to_path_buf(),
+            source: io::Error::new(io::ErrorKind::InvalidInput, "not a file path"),
+        })
+}
+
+fn entry_for(artifact: &Path, algorithm: HashAlgorithm) -> Result<ChecksumEntry, ChecksumError> {
+    Ok(ChecksumEntry {
+        digest: hash_file(algorithm, artifact).map_err(io_err(artifact))?,
+        filename: file_name(artifact)?,
+    })
+}
+
+/// Hash an artifact and write its sidecar next to it. Returns the sidecar path.
+pub fn write_sidecar(
+    artifact: impl AsRef<Path>,
+    algorithm: HashAlgorithm,
+) -> Result<PathBuf, ChecksumError> {
+    let artifact = artifact.as_ref();
+    let entry = entry_for(artifact, algorithm)?;
+    let sidecar = sidecar_path(artifact, algorithm);
+    fs::write(&sidecar, format!("{}\n", entry)).map_err(io_err(&sidecar))?;
+    Ok(sidecar)
+}
+
+/// Write one checksum file (e.g., `SHA512SUMS`) covering several artifacts.
+///
+/// Artifacts must live in the same directory as `output`, since entries are
+/// written by file name.
+pub fn write_checksums(
+    output: impl AsRef<Path>,
+    artifacts: &[impl AsRef<Path>],
+    algorithm: HashAlgorithm,
+) -> Result<(), ChecksumError> {
+    let output = output.as_ref();
+    let mut contents = String::new();
+    for artifact in artifacts {
+        contents.push_str(&entry_for(artifact.as_ref(), algorithm)?.to_string());
+        contents.push('\n');
+    }
+    fs::write(output, contents).map_err(io_err(output))
+}
+
+/// Verify every entry of a checksum file, resolving names relative to its directory.
+///
+/// All entries are checked before returning, so a failure lists every bad
+/// file rather than just the first. A file with no entries is an error.
+pub fn verify_checksums(checksum_file: impl AsRef<Path>) -> Result<(), ChecksumError> {
+    verify(checksum_file.as_ref(), None)
+}
+
+/// Verify an artifact against its sidecar (`<artifact><suffix>`), which
+/// must list the artifact by its file name.
+pub fn verify_sidecar(
+    artifact: impl AsRef<Path>,
+    algorithm: HashAlgorithm,
+) -> Result<(), ChecksumError> {
+    let artifact = artifact.as_ref();
+    let filename = file_name(artifact)?;
+    verify(&sidecar_path(artifact, algorithm), Some(&filename))
+}
+
+/// Check every entry of `checksum_file`, and that `required` is one of them.
+fn verify(checksum_file: &Path, required: Option<&str>) -> Result<(), ChecksumError> {
+    let contents = fs::read_to_string(checksum_file).map_err(io_err(checksum_file))?;
+    let entries = parse_checksums(&contents)?;
+    if entries.is_empty() {
+        return Err(ChecksumError::Empty(checksum_file.to_path_buf()));
+    }
+    let dir = checksum_file.parent().unwrap_or(Path::new("."));
+
+    let mut failures = Vec::new();
+    if let Some(filename) = required {
+        if !entries.iter().any(|e| e.filename == filename) {
+            failures.push(ChecksumFailure::NotListed {
+                filename: filename.to_string(),
+            });
+        }
+    }
+    for entry in &entries {
+        let path = dir.join(&entry.filename);
+        if !path.is_file() {
+            failures.push(ChecksumFailure::Missing {
+                filename: entry.filename.clone(),
+            });
+            continue;
+        }
+        // parse_checksums only accepts digests of a known length
+        let algorithm = entry.algorithm().unwrap();
+        let actual = hash_file(algorithm, &path).map_err(io_err(&path))?;
+        if actual != entry.digest {
+            failures.push(ChecksumFailure::Mismatch {
+                filename: entry.filename.clone(),
+                expected: entry.digest.clone(),
+                actual,
+            });
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(ChecksumError::Failed(failures))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    fn hex(alg: HashAlgorithm, data: &[u8]) -> String {
+        let mut h = Hasher::new(alg);
+        h.update(data);
+        h.finalize_hex()
+    }
+
+    #[test]
+    fn test_known_vectors() {
+        use HashAlgorithm::*;
+        assert_eq!(
+            hex(Sha256, b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(
+                Sha256,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hex(Sha512, b""),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            hex(Sha512, b"abc"),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            hex(
+                Sha512,
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmno\
+                  ijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+            ),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        for alg in [HashAlgorithm::Sha256, HashAlgorithm::Sha512] {
+            let mut h = Hasher::new(alg);
+            for chunk in data.chunks(37) {
+                h.update(chunk);
+            }
+            assert_eq!(h.finalize_hex(), hex(alg, &data));
+            assert_eq!(hash_reader(alg, &data[..]).unwrap(), hex(alg, &data));
+        }
+    }
+
+    #[test]
+    fn test_sidecar_roundtrip() {
+        let dir = TempDir::new("checksum");
+        let iso = dir.write("levitateos-x86_64.iso", b"iso contents");
+        let sidecar = write_sidecar(&iso, HashAlgorithm::Sha512).unwrap();
+        assert!(sidecar.ends_with("levitateos-x86_64.iso.sha512"));
+
+        let contents = fs::read_to_string(&sidecar).unwrap();
+        assert_eq!(contents.len(), 128 + 2 + "levitateos-x86_64.iso".len() + 1);
+        assert!(contents.ends_with("  levitateos-x86_64.iso\n"));
+        verify_sidecar(&iso, HashAlgorithm::Sha512).unwrap();
+
+        fs::write(&iso, b"tampered").unwrap();
+        let err = verify_sidecar(&iso, HashAlgorithm::Sha512).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("levitateos-x86_64.iso: checksum mismatch"));
+    }
+
+    #[test]
+    fn test_sidecar_must_list_artifact() {
+        let dir = TempDir::new("checksum-other");
+        let iso = dir.write("a.iso", b"a");
+        let other = dir.write("b.iso", b"b");
+        let sidecar = write_sidecar(&other, HashAlgorithm::Sha256).unwrap();
+        fs::rename(&sidecar, sidecar_path(&iso, HashAlgorithm::Sha256)).unwrap();
+
+        // b.iso verifies, but the sidecar says nothing about a.iso
+        match verify_sidecar(&iso, HashAlgorithm::Sha256).unwrap_err() {
+            ChecksumError::Failed(failures) => assert_eq!(
+                failures,
+                [ChecksumFailure::NotListed {
+                    filename: "a.iso".into()
+                }]
+            ),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_empty_checksum_file() {
+        let dir = TempDir::new("checksum-empty");
+        let iso = dir.write("a.iso", b"a");
+        let sums = dir.write("SHA256SUMS", b"\n\n");
+        assert!(matches!(
+            verify_checksums(&sums),
+            Err(ChecksumError::Empty(_))
+        ));
+        dir.write("a.iso.sha256", b"");
+        assert!(matches!(
+            verify_sidecar(&iso, HashAlgorithm::Sha256),
+            Err(ChecksumError::Empty(_))
+        ));
+    }
+
+    #[test]
+    fn test_multi_entry_verification() {
+        let dir = TempDir::new("checksums");
+        let iso = dir.write("a.iso", b"a");
+        let qcow = dir.write("a.qcow2", b"b");
+        let tar = dir.write("a.tar.xz", b"c");
+        let sums = dir.path().join("SHA256SUMS");
+        write_checksums(&sums, &[&iso, &qcow, &tar], HashAlgorithm::Sha256).unwrap();
+        verify_checksums(&sums).unwrap();
+
+        fs::remove_file(&qcow).unwrap();
+        fs::write(&tar, b"changed").unwrap();
+        match verify_checksums(&sums).unwrap_err() {
+            ChecksumError::Failed(failures) => {
+                assert_eq!(failures.len(), 2);
+                assert_eq!(
+                    failures[0],
+                    ChecksumFailure::Missing {
+                        filename: "a.qcow2".into()
+                    }
+                );
+                assert!(matches!(failures[1], ChecksumFailure::Mismatch { .. }));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let digest = "a".repeat(128);
+        let ok = parse_checksums(&format!("{}  x.iso\n\n{} *y.iso\n", digest, digest)).unwrap();
+        assert_eq!(ok[1].filename, "y.iso");
+
+        let err = parse_checksums(&format!("{}  x\nabc  y\n", digest)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: digest has 3 hex digits; expected 64 (SHA-256) or 128 (SHA-512)"
+        );
+
+        let mixed = format!("{}  x\n{}  y\n", digest, "b".repeat(64));
+        assert!(matches!(
+            parse_checksums(&mixed),
+            Err(ChecksumError::Parse { line: 2, .. })
+        ));
+        assert!(parse_checksums(&format!("{} x\n", digest)).is_err());
+        assert!(parse_checksums(&format!("{}  x\n", "z".repeat(128))).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Helpers for rendering argv lists as shell command lines.
+//!
+//! Builders in this crate produce commands as argv (`Vec<String>`) so they can
+//! be executed directly without a shell. These helpers turn that argv into a
+//! copy-pasteable shell line for dry runs and logs.
+
+/// Quote a single argument for POSIX sh.
+///
+/// Arguments made only of safe characters are returned unchanged; everything
+/// else is wrapped in single quotes.
+pub fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_./=:,+@%".contains(&b));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Render an argv list as a single shell command line.
+pub fn render_argv<S: AsRef<str>>(argv: &[S]) -> String {
+    argv.iter()
+        .map(|a| shell_quote(a.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/dev/sda1"), "/dev/sda1");
+        assert_eq!(shell_quote("LABEL=root"), "LABEL=root");
+        assert_eq!(shell_quote("two words"), "'two words'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_render_argv() {
+        assert_eq!(
+            render_argv(&["mount", "/dev/vda2", "/mnt"]),
+            "mount /dev/vda2 /mnt"
+        );
+    }
+}
@@ -3,14 +3,27 @@
 //! These lists define what a complete LevitateOS rootfs should contain.
 //! Used by:
 //! - `leviso` - builds the rootfs from these definitions
-//! - `fsdbg` - verifies the rootfs contains these items
+//! - `fsdbg` - verifies the rootfs contains these items, via
+//!   `rootfs_manifest::Manifest::from_components()`
+//!
+//! Lists that belong to one feature (NetworkManager, SSH, PipeWire, ...) are
+//! defined once in `registry.rs`; the constants here include them. The
+//! lists describe the desktop profile; `profile::Profile` filters them for
+//! smaller installs (minimal, server).
 //!
 //! # Adding New Items
 //!
-//! 1. Add to the appropriate list here
+//! 1. Add to the appropriate list here, or to the feature's `Component`
 //! 2. Both leviso and fsdbg will automatically pick up the change
 //! 3. Run `cargo build --workspace` to verify
 
+use super::const_check::static_check;
+use super::registry::{
+    dbus_aliases_view, names_view, FlatList, BLUETOOTH, NETWORKMANAGER, PIPEWIRE, POLKIT, SSH,
+    UDISKS, UPOWER, WPA_SUPPLICANT,
+};
+use crate::arch::Arch;
+
 // =============================================================================
 // FILESYSTEM HIERARCHY
 // =============================================================================
@@ -143,7 +156,18 @@ pub const LEVITATE_CARGO_TOOLS: &[&str] = &[
 ///
 /// These are the core utilities expected in a daily-driver Linux distribution.
 /// Comparable to Arch Linux base + base-devel.
-pub const BIN_UTILS: &[&str] = &[
+///
+/// Core utilities followed by the `bin` of the components feeding
+/// `FlatList::BinUtils`. NetworkManager and SSH binaries are copied from
+/// `NM_BIN` and `auth::SSH_BIN` instead.
+pub const BIN_UTILS: &[&str] = names_view!(
+    Part::List(CORE_BIN_UTILS),
+    Part::Feeding(FlatList::BinUtils, Field::Bin),
+);
+static_check!(names BIN_UTILS);
+
+/// /usr/bin utilities that are not part of a registered component.
+const CORE_BIN_UTILS: &[&str] = &[
     // === COREUTILS ===
     "ls", "cat", "cp", "mv", "rm", "mkdir", "rmdir", "touch",
     "chmod", "chown", "chgrp", "ln", "readlink", "realpath",
@@ -235,28 +259,15 @@ pub const BIN_UTILS: &[&str] = &[
     "7za",  // Note: p7zip only provides 7za wrapper script, not 7z/7zr
     // === DIRECTORY TOOLS ===
     "tree",
-    // === BLUETOOTH ===
-    "bluetoothctl",
-    // === PIPEWIRE AUDIO ===
-    "pw-cli", "pw-dump", "pw-cat", "pw-play", "pw-record",
-    "pw-top", "pw-metadata", "pw-mon", "pw-link",
-    "wpctl",  // WirePlumber control
-    // === PULSEAUDIO COMPAT (pipewire-pulse) ===
-    "pactl", "paplay", "parecord",  // Note: pacmd not provided by pipewire-pulseaudio
-    // === POLKIT ===
-    "pkexec", "pkaction", "pkcheck",
-    // === UDISKS2 ===
-    "udisksctl",
-    // === POWER MANAGEMENT ===
-    "upower",
+    // Bluetooth, PipeWire, polkit, UDisks2, UPower: see registry.rs
 ];
 
-// Authentication and SSH binaries have been moved to the auth subsystem.
+// Authentication binaries have been moved to the auth subsystem.
 // See: distro-spec/src/shared/auth/components.rs
 // Import directly from there when needed.
 
 /// NetworkManager binaries for /usr/bin.
-pub const NM_BIN: &[&str] = &["nmcli", "nm-online", "nmtui"];
+pub const NM_BIN: &[&str] = NETWORKMANAGER.bin;
 
 // =============================================================================
 // BINARIES - /usr/sbin
@@ -265,7 +276,18 @@ pub const NM_BIN: &[&str] = &["nmcli", "nm-online", "nmtui"];
 /// Binaries for /usr/sbin.
 ///
 /// System administration utilities requiring elevated privileges.
-pub const SBIN_UTILS: &[&str] = &[
+///
+/// Core utilities followed by the `sbin` of the components feeding
+/// `FlatList::SbinUtils`. The others are copied from their own lists
+/// (`NM_SBIN`, `WPA_SBIN`, `PIPEWIRE_SBIN`, `auth::SSH_SBIN`).
+pub const SBIN_UTILS: &[&str] = names_view!(
+    Part::List(CORE_SBIN_UTILS),
+    Part::Feeding(FlatList::SbinUtils, Field::Sbin),
+);
+static_check!(names SBIN_UTILS);
+
+/// /usr/sbin utilities that are not part of a registered component.
+const CORE_SBIN_UTILS: &[&str] = &[
     // === UTIL-LINUX ===
     "fsck", "blkid", "losetup", "mkswap", "swapon", "swapoff",
     "fdisk", "sfdisk", "wipefs", "blockdev", "pivot_root", "chroot",
@@ -293,7 +315,7 @@ pub const SBIN_UTILS: &[&str] = &[
     "reboot", "shutdown", "poweroff", "halt", "efibootmgr",
     // === OTHER ===
     "ldconfig", "hwclock", "lspci", "ifconfig", "route",
-    "agetty", "login", "sulogin", "nologin", "chronyd",
+    "agetty", "login", "sulogin", "nologin",
     // === SQUASHFS-TOOLS ===
     "unsquashfs",
     // === CRYPTSETUP (LUKS) ===
@@ -319,32 +341,32 @@ pub const SBIN_UTILS: &[&str] = &[
 // See: distro-spec/src/shared/auth/components.rs
 
 /// NetworkManager binaries for /usr/sbin.
-pub const NM_SBIN: &[&str] = &["NetworkManager"];
+pub const NM_SBIN: &[&str] = NETWORKMANAGER.sbin;
 
 /// wpa_supplicant binaries for /usr/sbin.
-pub const WPA_SBIN: &[&str] = &["wpa_supplicant", "wpa_cli", "wpa_passphrase"];
+pub const WPA_SBIN: &[&str] = WPA_SUPPLICANT.sbin;
 
 // SSH server binaries have been moved to the auth subsystem.
 // See: distro-spec/src/shared/auth/components.rs
 
 /// Bluetooth binaries for /usr/sbin (from bluez).
 /// Note: bluetoothd is in /usr/libexec/bluetooth/, not /usr/sbin - handled via CopyTree
-pub const BLUETOOTH_SBIN: &[&str] = &[];
+pub const BLUETOOTH_SBIN: &[&str] = BLUETOOTH.sbin;
 
 /// PipeWire binaries for /usr/sbin.
-pub const PIPEWIRE_SBIN: &[&str] = &["pipewire", "pipewire-pulse", "wireplumber"];
+pub const PIPEWIRE_SBIN: &[&str] = PIPEWIRE.sbin;
 
 /// Polkit binaries for /usr/sbin.
 /// Note: polkitd is in /usr/lib/polkit-1/, not /usr/sbin - handled via config_trees
-pub const POLKIT_SBIN: &[&str] = &[];
+pub const POLKIT_SBIN: &[&str] = POLKIT.sbin;
 
 /// UDisks2 binaries for /usr/sbin.
 /// Note: udisksd is in /usr/libexec/udisks2/, not /usr/sbin - handled via config_trees
-pub const UDISKS_SBIN: &[&str] = &[];
+pub const UDISKS_SBIN: &[&str] = UDISKS.sbin;
 
 /// UPower binaries for /usr/sbin.
 /// Note: upowerd is in /usr/libexec/, not /usr/sbin - handled via config_trees
-pub const UPOWER_SBIN: &[&str] = &[];
+pub const UPOWER_SBIN: &[&str] = UPOWER.sbin;
 
 // =============================================================================
 // SYSTEMD BINARIES
@@ -373,7 +395,9 @@ pub const SYSTEMD_BINARIES: &[&str] = &[
     "systemd-makefs",  // For creating/formatting filesystems during boot
     "systemd-vconsole-setup",
     "systemd-random-seed",
+    "systemd-bless-boot",  // Boot assessment (boot counting)
 ];
+static_check!(names SYSTEMD_BINARIES);
 
 // =============================================================================
 // SYSTEMD UNITS
@@ -439,52 +463,36 @@ pub const ESSENTIAL_UNITS: &[&str] = &[
 ];
 
 /// NetworkManager units.
-pub const NM_UNITS: &[&str] = &["NetworkManager.service", "NetworkManager-dispatcher.service"];
+pub const NM_UNITS: &[&str] = NETWORKMANAGER.units;
 
 /// wpa_supplicant units.
-pub const WPA_UNITS: &[&str] = &["wpa_supplicant.service"];
+pub const WPA_UNITS: &[&str] = WPA_SUPPLICANT.units;
 
 /// Bluetooth units (bluez).
-pub const BLUETOOTH_UNITS: &[&str] = &["bluetooth.service", "bluetooth.target"];
+pub const BLUETOOTH_UNITS: &[&str] = BLUETOOTH.units;
 
 /// PipeWire units (user service - runs per-user, not system-wide).
 /// Note: PipeWire runs as a user service, so these are in user/ not system/.
-pub const PIPEWIRE_UNITS: &[&str] = &[
-    "pipewire.service",
-    "pipewire.socket",
-    "pipewire-pulse.service",
-    "pipewire-pulse.socket",
-    "wireplumber.service",
-];
+pub const PIPEWIRE_UNITS: &[&str] = PIPEWIRE.user_units;
 
 /// Polkit units.
-pub const POLKIT_UNITS: &[&str] = &["polkit.service"];
+pub const POLKIT_UNITS: &[&str] = POLKIT.units;
 
 /// UDisks2 units.
-pub const UDISKS_UNITS: &[&str] = &["udisks2.service"];
+pub const UDISKS_UNITS: &[&str] = UDISKS.units;
 
 /// UPower units.
-pub const UPOWER_UNITS: &[&str] = &["upower.service"];
-
-/// SSH units (for Service definition).
-pub const SSH_UNITS: &[&str] = &[
-    "sshd.service",
-    "sshd.socket",
-    "sshd@.service",
-    "sshd-keygen.target",
-    "sshd-keygen@.service",
-    "ssh-host-keys-migration.service",
-];
+pub const UPOWER_UNITS: &[&str] = UPOWER.units;
 
-/// D-Bus activation symlinks.
-pub const DBUS_ACTIVATION_SYMLINKS: &[&str] = &[
-    "dbus-org.freedesktop.timedate1.service",
-    "dbus-org.freedesktop.hostname1.service",
-    "dbus-org.freedesktop.locale1.service",
-    "dbus-org.freedesktop.login1.service",
-    "dbus-org.freedesktop.network1.service",
-    "dbus-org.freedesktop.resolve1.service",
-];
+/// SSH units (for Service definition), including the optional `sshd.socket`.
+pub const SSH_UNITS: &[&str] = names_view!(Part::List(SSH.units), Part::List(SSH.optional_units));
+
+/// D-Bus activation symlinks (`dbus-<name>.service`).
+///
+/// Derived from the `dbus_names` of the components feeding
+/// `FlatList::DbusActivation` (systemd's own daemons); the other components'
+/// daemons are started through their own units.
+pub const DBUS_ACTIVATION_SYMLINKS: &[&str] = dbus_aliases_view!();
 
 /// ALL systemd units that must be present in the rootfs.
 ///
@@ -498,8 +506,19 @@ pub const DBUS_ACTIVATION_SYMLINKS: &[&str] = &[
 /// - SSH (sshd)
 /// - D-Bus activation symlinks
 ///
-/// Used by fsdbg to verify the rootfs contains all required systemd units.
-pub const ALL_SYSTEMD_UNITS: &[&str] = &[
+/// Core units followed by the units of the components feeding
+/// `FlatList::SystemdUnits`; their `optional_units` are not required.
+/// Checked by `rootfs_manifest` in any of `UNIT_SEARCH_DIRS`.
+pub const ALL_SYSTEMD_UNITS: &[&str] = names_view!(
+    Part::List(CORE_SYSTEMD_UNITS),
+    Part::Feeding(FlatList::SystemdUnits, Field::Units),
+    Part::Feeding(FlatList::SystemdUnits, Field::UserUnits),
+    Part::List(DBUS_ACTIVATION_SYMLINKS),
+);
+static_check!(unique ALL_SYSTEMD_UNITS);
+
+/// Units that are not part of a registered component.
+const CORE_SYSTEMD_UNITS: &[&str] = &[
     // Essential targets and services
     "basic.target", "sysinit.target", "multi-user.target", "default.target",
     "getty.target", "local-fs.target", "local-fs-pre.target",
@@ -535,45 +554,20 @@ pub const ALL_SYSTEMD_UNITS: &[&str] = &[
     "systemd-timedated.service", "systemd-hostnamed.service",
     "systemd-localed.service", "systemd-networkd.service",
     "systemd-resolved.service", "systemd-networkd-wait-online.service",
-    // Misc core
-    "dbus.service", "dbus-broker.service", "chronyd.service",
     // Shutdown services (CRITICAL)
     "systemd-halt.service", "systemd-poweroff.service", "systemd-reboot.service",
     "systemd-soft-reboot.service",
-    // SSH
-    "sshd.service", "sshd@.service", "sshd-keygen.target",
-    "sshd-keygen@.service", "ssh-host-keys-migration.service",
     // Sockets
     "systemd-journald.socket", "systemd-journald-dev-log.socket",
     "systemd-journald-audit.socket",
     "systemd-udevd-control.socket", "systemd-udevd-kernel.socket",
-    "dbus.socket",
     // Paths
     "systemd-ask-password-console.path", "systemd-ask-password-wall.path",
     // Slices
     "user.slice",
-    // NetworkManager
-    "NetworkManager.service", "NetworkManager-dispatcher.service",
-    // wpa_supplicant
-    "wpa_supplicant.service",
-    // Bluetooth
-    "bluetooth.service", "bluetooth.target",
-    // PipeWire (user service - in user/ dir)
-    "pipewire.service", "pipewire.socket", "pipewire-pulse.service",
-    "pipewire-pulse.socket", "wireplumber.service",
-    // polkit
-    "polkit.service",
-    // udisks2
-    "udisks2.service",
-    // upower
-    "upower.service",
-    // D-Bus activation symlinks
-    "dbus-org.freedesktop.timedate1.service",
-    "dbus-org.freedesktop.hostname1.service",
-    "dbus-org.freedesktop.locale1.service",
-    "dbus-org.freedesktop.login1.service",
-    "dbus-org.freedesktop.network1.service",
-    "dbus-org.freedesktop.resolve1.service",
+    // Boot assessment (boot counting)
+    "systemd-bless-boot.service", "systemd-boot-check-no-failures.service",
+    "boot-complete.target",
 ];
 
 // =============================================================================
@@ -605,7 +599,13 @@ pub const UDEV_HELPERS: &[&str] = &[
 // =============================================================================
 
 /// Essential /etc files for a bootable system.
-pub const ETC_FILES: &[&str] = &[
+///
+/// Core files followed by the `config` of every registered component.
+pub const ETC_FILES: &[&str] = names_view!(Part::List(CORE_ETC_FILES), Part::Field(Field::Config));
+static_check!(unique ETC_FILES);
+
+/// /etc files that are not part of a registered component.
+const CORE_ETC_FILES: &[&str] = &[
     // === USER DATABASE ===
     "etc/passwd",
     "etc/group",
@@ -630,20 +630,8 @@ pub const ETC_FILES: &[&str] = &[
     // === SUDO ===
     "etc/sudoers",
     "etc/sudo.conf",
-    // === SSH SERVER ===
-    "etc/ssh/sshd_config",
-    "etc/ssh/ssh_host_rsa_key",
-    "etc/ssh/ssh_host_rsa_key.pub",
-    "etc/ssh/ssh_host_ecdsa_key",
-    "etc/ssh/ssh_host_ecdsa_key.pub",
-    "etc/ssh/ssh_host_ed25519_key",
-    "etc/ssh/ssh_host_ed25519_key.pub",
-    // === SSH CLIENT ===
-    "etc/ssh/ssh_config",
     // === TIMEZONE ===
     "etc/localtime",
-    // === TIME SYNC ===
-    "etc/chrony.conf",
     // === LOCALE ===
     "etc/locale.conf",
     "etc/vconsole.conf",
@@ -662,7 +650,7 @@ pub const ETC_FILES: &[&str] = &[
 pub const CRITICAL_LIBS: &[&str] = &[
     // Core glibc
     "usr/lib64/libc.so.6",
-    "usr/lib64/ld-linux-x86-64.so.2",
+    Arch::DEFAULT.glibc_dynamic_linker(),
     // glibc 2.34+ compatibility stubs (REQUIRED for older binaries)
     // These are symlinks to libc.so.6 but must exist
     "usr/lib64/libpthread.so.0",
@@ -677,34 +665,46 @@ pub const CRITICAL_LIBS: &[&str] = &[
     "usr/lib64/libnss_files.so.2",
     "usr/lib64/libselinux.so.1",
 ];
+static_check!(unique CRITICAL_LIBS);
+
+/// `CRITICAL_LIBS` for `arch`.
+///
+/// Only the dynamic linker's name differs between architectures; the other
+/// libraries live in usr/lib64 on every supported one.
+pub fn critical_libs(arch: Arch) -> Vec<&'static str> {
+    CRITICAL_LIBS
+        .iter()
+        .map(|&lib| {
+            if lib == Arch::DEFAULT.glibc_dynamic_linker() {
+                arch.glibc_dynamic_linker()
+            } else {
+                lib
+            }
+        })
+        .collect()
+}
 
 // =============================================================================
 // SYSTEM USERS/GROUPS
 // =============================================================================
 
 /// System users that must exist in /etc/passwd.
-pub const SYSTEM_USERS: &[&str] = &[
-    "root",
-    "dbus",
-    "sshd",
-    "chrony",
-    "polkitd",
-    "pipewire",   // For PipeWire system mode (optional)
-];
+///
+/// root followed by the `users` of every registered component.
+pub const SYSTEM_USERS: &[&str] = names_view!(Part::List(&["root"]), Part::Field(Field::Users));
 
 /// System groups that must exist in /etc/group.
-pub const SYSTEM_GROUPS: &[&str] = &[
-    "root",
-    "wheel",
-    "dbus",
-    "sshd",
-    "chrony",
-    "polkitd",
-    "pipewire",
-    "bluetooth",  // Users in this group can use bluetooth
-    "audio",      // Users in this group can use audio
-    "video",      // Users in this group can use video devices
-];
+///
+/// Core groups followed by the `groups` of every registered component.
+pub const SYSTEM_GROUPS: &[&str] = names_view!(
+    Part::List(&[
+        "root",
+        "wheel",
+        "audio",      // Users in this group can use audio
+        "video",      // Users in this group can use video devices
+    ]),
+    Part::Field(Field::Groups),
+);
 
 // =============================================================================
 // TESTS
@@ -897,4 +897,52 @@ mod tests {
             );
         }
     }
+
+    // Builders copy NM_BIN, SSH_BIN, PIPEWIRE_SBIN, SSH_UNITS, ... on their
+    // own, so the registry must add to the flat lists exactly what the
+    // hand-written lists contained before it existed.
+    #[test]
+    fn test_registry_parts_pinned() {
+        assert_eq!(
+            &BIN_UTILS[CORE_BIN_UTILS.len()..],
+            [
+                "pkexec", "pkaction", "pkcheck", "pw-cli", "pw-dump", "pw-cat", "pw-play",
+                "pw-record", "pw-top", "pw-metadata", "pw-mon", "pw-link", "wpctl", "pactl",
+                "paplay", "parecord", "bluetoothctl", "udisksctl", "upower",
+            ]
+        );
+        assert_eq!(&SBIN_UTILS[CORE_SBIN_UTILS.len()..], ["chronyd"]);
+        assert_eq!(
+            &ALL_SYSTEMD_UNITS[CORE_SYSTEMD_UNITS.len()..],
+            [
+                "dbus.service", "dbus-broker.service", "dbus.socket",
+                "sshd.service", "sshd@.service", "sshd-keygen.target",
+                "sshd-keygen@.service", "ssh-host-keys-migration.service",
+                "chronyd.service",
+                "NetworkManager.service", "NetworkManager-dispatcher.service",
+                "wpa_supplicant.service", "polkit.service",
+                "bluetooth.service", "bluetooth.target", "udisks2.service", "upower.service",
+                "pipewire.service", "pipewire.socket", "pipewire-pulse.service",
+                "pipewire-pulse.socket", "wireplumber.service",
+                "dbus-org.freedesktop.timedate1.service",
+                "dbus-org.freedesktop.hostname1.service",
+                "dbus-org.freedesktop.locale1.service",
+                "dbus-org.freedesktop.login1.service",
+                "dbus-org.freedesktop.network1.service",
+                "dbus-org.freedesktop.resolve1.service",
+            ]
+        );
+        assert_eq!(
+            (BIN_UTILS.len(), SBIN_UTILS.len(), ALL_SYSTEMD_UNITS.len()),
+            (185, 103, 105)
+        );
+
+        for bin in NM_BIN.iter().chain(SSH.bin) {
+            assert!(!BIN_UTILS.contains(bin), "{} is copied via its own list", bin);
+        }
+        for bin in NM_SBIN.iter().chain(WPA_SBIN).chain(PIPEWIRE_SBIN).chain(SSH.sbin) {
+            assert!(!SBIN_UTILS.contains(bin), "{} is copied via its own list", bin);
+        }
+        assert!(!ALL_SYSTEMD_UNITS.contains(&"sshd.socket"));
+    }
 }
@@ -0,0 +1,327 @@
+//! Compile-time checks for the static tables.
+//!
+//! `validate_spec()` reports cross-list problems at runtime; the mistakes
+//! that can be seen in a single table (duplicates, conflicting mappings,
+//! malformed entries, modules loaded before their dependencies) are rejected
+//! here, while distro-spec itself is compiled. Each table is checked next to
+//! its definition:
+//!
+//! ```ignore
+//! pub const MODULE_PATHS: &[(&str, &str)] = &[ ... ];
+//! static_check!(module_paths MODULE_PATHS);
+//! ```
+//!
+//! A failing check is a const-evaluation error naming the table and entry.
+
+use super::boot_modules::MODULE_DEPENDENCIES;
+
+// =============================================================================
+// Const String Helpers
+// =============================================================================
+
+const fn eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn starts_with(s: &str, prefix: &str) -> bool {
+    let (s, p) = (s.as_bytes(), prefix.as_bytes());
+    if p.len() > s.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < p.len() {
+        if s[i] != p[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn ends_with(s: &str, suffix: &str) -> bool {
+    let (s, x) = (s.as_bytes(), suffix.as_bytes());
+    if x.len() > s.len() {
+        return false;
+    }
+    let off = s.len() - x.len();
+    let mut i = 0;
+    while i < x.len() {
+        if s[off + i] != x[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Non-empty, no whitespace, no `/`: a file or package name.
+const fn is_name(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.is_empty() {
+        return false;
+    }
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'/' || b[i].is_ascii_whitespace() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// `kernel/...` without a `.ko` extension or whitespace.
+const fn is_module_path(s: &str) -> bool {
+    let b = s.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        if b[i].is_ascii_whitespace() {
+            return false;
+        }
+        i += 1;
+    }
+    starts_with(s, "kernel/") && !ends_with(s, "/") && !ends_with(s, ".ko")
+}
+
+/// Index of the first entry equal to an earlier one.
+const fn duplicate(list: &[&str]) -> Option<usize> {
+    let mut j = 1;
+    while j < list.len() {
+        let mut i = 0;
+        while i < j {
+            if eq(list[i], list[j]) {
+                return Some(j);
+            }
+            i += 1;
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Index of `entry` in `list`.
+const fn position(list: &[&str], entry: &str) -> Option<usize> {
+    let mut i = 0;
+    while i < list.len() {
+        if eq(list[i], entry) {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Panic message built at compile time: `<table>: <entry>: <problem>`.
+struct Message {
+    buf: [u8; 256],
+    len: usize,
+}
+
+impl Message {
+    const fn new() -> Self {
+        Self {
+            buf: [0; 256],
+            len: 0,
+        }
+    }
+
+    const fn push(mut self, s: &str) -> Self {
+        let b = s.as_bytes();
+        let mut i = 0;
+        while i < b.len() && self.len < self.buf.len() {
+            self.buf[self.len] = b[i];
+            self.len += 1;
+            i += 1;
+        }
+        self
+    }
+}
+
+const fn fail(table: &str, entry: &str, problem: &str) -> ! {
+    let msg = Message::new()
+        .push(table)
+        .push(": ")
+        .push(entry)
+        .push(": ")
+        .push(problem);
+    let (bytes, _) = msg.buf.split_at(msg.len);
+    match core::str::from_utf8(bytes) {
+        Ok(s) => panic!("{}", s),
+        Err(_) => panic!("{}", problem),
+    }
+}
+
+// =============================================================================
+// Table Checks
+// =============================================================================
+
+/// No entry twice.
+pub(crate) const fn unique(table: &str, list: &[&str]) {
+    if let Some(i) = duplicate(list) {
+        fail(table, list[i], "listed more than once");
+    }
+}
+
+/// Unique file names (no paths, no whitespace).
+pub(crate) const fn names(table: &str, list: &[&str]) {
+    let mut i = 0;
+    while i < list.len() {
+        if !is_name(list[i]) {
+            fail(table, list[i], "not a plain file name");
+        }
+        i += 1;
+    }
+    unique(table, list);
+}
+
+/// Name → package table: valid names, each key mapped once.
+pub(crate) const fn package_map(table: &str, pairs: &[(&str, &str)]) {
+    let mut j = 0;
+    while j < pairs.len() {
+        let (key, package) = pairs[j];
+        if !is_name(key) {
+            fail(table, key, "not a plain file name");
+        }
+        if !is_name(package) {
+            fail(table, package, "not a package name");
+        }
+        let mut i = 0;
+        while i < j {
+            if eq(pairs[i].0, key) {
+                if eq(pairs[i].1, package) {
+                    fail(table, key, "mapped more than once");
+                }
+                fail(table, key, "mapped to two different packages");
+            }
+            i += 1;
+        }
+        j += 1;
+    }
+}
+
+/// Module name → path table: unique names, `kernel/.../<name>` paths.
+pub(crate) const fn module_paths(table: &str, pairs: &[(&str, &str)]) {
+    let mut j = 0;
+    while j < pairs.len() {
+        let (name, path) = pairs[j];
+        if !is_name(name) {
+            fail(table, name, "not a module name");
+        }
+        if !is_module_path(path) {
+            fail(
+                table,
+                path,
+                "not a kernel/... module path without extension",
+            );
+        }
+        let named = ends_with(path, name)
+            && path.len() > name.len()
+            && path.as_bytes()[path.len() - name.len() - 1] == b'/';
+        if !named {
+            fail(table, name, "path does not end in the module name");
+        }
+        let mut i = 0;
+        while i < j {
+            if eq(pairs[i].0, name) {
+                fail(table, name, "mapped more than once");
+            }
+            i += 1;
+        }
+        j += 1;
+    }
+}
+
+/// Boot module paths: unique, well-formed, and every module after the
+/// dependencies (from `MODULE_DEPENDENCIES`) that the list also contains.
+pub(crate) const fn boot_modules(table: &str, list: &[&str]) {
+    let mut i = 0;
+    while i < list.len() {
+        if !is_module_path(list[i]) {
+            fail(
+                table,
+                list[i],
+                "not a kernel/... module path without extension",
+            );
+        }
+        i += 1;
+    }
+    unique(table, list);
+
+    let mut d = 0;
+    while d < MODULE_DEPENDENCIES.len() {
+        let (module, deps) = MODULE_DEPENDENCIES[d];
+        if let Some(at) = position(list, module) {
+            let mut k = 0;
+            while k < deps.len() {
+                if let Some(dep_at) = position(list, deps[k]) {
+                    if dep_at > at {
+                        fail(table, module, "listed before one of its dependencies");
+                    }
+                }
+                k += 1;
+            }
+        }
+        d += 1;
+    }
+}
+
+/// Check a static table at compile time, next to its definition.
+///
+/// `static_check!(<check> TABLE)` where `<check>` is one of `unique`,
+/// `names`, `package_map`, `module_paths` or `boot_modules`.
+macro_rules! static_check {
+    ($check:ident $table:path) => {
+        const _: () = $crate::shared::const_check::$check(stringify!($table), $table);
+    };
+}
+
+pub(crate) use static_check;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(duplicate(&["a", "b", "a"]), Some(2));
+        assert_eq!(duplicate(&["a", "b"]), None);
+        assert!(is_name("nvme-core") && !is_name("usr/bin/ls") && !is_name(""));
+        assert!(is_module_path("kernel/fs/ext4/ext4"));
+        assert!(!is_module_path("kernel/fs/ext4/ext4.ko") && !is_module_path("fs/ext4"));
+    }
+
+    #[test]
+    #[should_panic(expected = "T: ls: mapped to two different packages")]
+    fn test_conflicting_mapping() {
+        package_map("T", &[("ls", "coreutils"), ("cat", "coreutils"), ("ls", "busybox")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "T: kernel/drivers/nvme/host/nvme: listed before one of its")]
+    fn test_dependency_order() {
+        boot_modules(
+            "T",
+            &[
+                "kernel/drivers/nvme/host/nvme",
+                "kernel/drivers/nvme/host/nvme-core",
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "T: vfat: path does not end in the module name")]
+    fn test_module_path_name() {
+        module_paths("T", &[("vfat", "kernel/fs/fat/fat")]);
+    }
+}
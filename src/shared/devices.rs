@@ -1,10 +1,455 @@
-//! Device paths for boot media detection.
+//! Block device enumeration and boot media detection.
+//!
+//! Devices are discovered from `/sys/class/block` rather than a fixed list of
+//! names, so `nvme1n1`, `mmcblk0`, `sdc` and USB sticks all show up. The live
+//! medium is identified by its ISO9660 volume label (`ISO_LABEL`) or by a
+//! rootfs image written directly to the device, and excluded from install
+//! targets.
+//!
+//! The filesystem root is injectable (`BlockDevices::with_root`) so tests can
+//! use a fixture tree containing `sys/class/block/*` and `dev/*`.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::hardware::{read_trimmed, VIRTUAL_BLOCK_PREFIXES};
+use super::rootfs::detect_format;
 
 /// Devices to probe for ISO/boot media (in order)
+///
+/// Legacy fixed list; misses `nvme1n1`, `mmcblk*`, `sdc` and so on. New code
+/// should use `BlockDevices::live_medium()` and `BlockDevices::install_candidates()`.
 pub const BOOT_DEVICE_PROBE_ORDER: &[&str] = &[
-    "/dev/sr0",      // CD/DVD drive
-    "/dev/sda",      // First SATA/SCSI disk
-    "/dev/sdb",      // Second SATA/SCSI disk
-    "/dev/vda",      // VirtIO disk (QEMU)
-    "/dev/nvme0n1",  // NVMe drive
+    "/dev/sr0",     // CD/DVD drive
+    "/dev/sda",     // First SATA/SCSI disk
+    "/dev/sdb",     // Second SATA/SCSI disk
+    "/dev/vda",     // VirtIO disk (QEMU)
+    "/dev/nvme0n1", // NVMe drive
 ];
+
+/// Sysfs directory listing every block device (disks and partitions).
+pub const SYS_CLASS_BLOCK: &str = "sys/class/block";
+
+/// Byte offset of the ISO9660 primary volume descriptor (sector 16).
+pub const ISO9660_PVD_OFFSET: u64 = 16 * 2048;
+
+/// ISO9660 standard identifier, at byte 1 of every volume descriptor.
+pub const ISO9660_STANDARD_ID: &[u8; 5] = b"CD001";
+
+/// How a block device is attached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Nvme,
+    Sata,
+    Usb,
+    Virtio,
+    Mmc,
+    Scsi,
+    Unknown,
+}
+
+impl Transport {
+    /// Lowercase name as shown by `lsblk -o TRAN`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transport::Nvme => "nvme",
+            Transport::Sata => "sata",
+            Transport::Usb => "usb",
+            Transport::Virtio => "virtio",
+            Transport::Mmc => "mmc",
+            Transport::Scsi => "scsi",
+            Transport::Unknown => "unknown",
+        }
+    }
+
+    /// Infer the transport from the device name and its resolved sysfs path.
+    ///
+    /// USB wins over everything else: a USB-attached SATA bridge shows up as
+    /// `sdX` under an `ata` path, but it is still a removable USB device.
+    fn detect(name: &str, sysfs_path: &str) -> Self {
+        if sysfs_path.contains("/usb") {
+            Transport::Usb
+        } else if name.starts_with("nvme") {
+            Transport::Nvme
+        } else if name.starts_with("mmcblk") {
+            Transport::Mmc
+        } else if name.starts_with("vd") || sysfs_path.contains("/virtio") {
+            Transport::Virtio
+        } else if sysfs_path.contains("/ata") {
+            Transport::Sata
+        } else if name.starts_with("sd") || name.starts_with("sr") {
+            Transport::Scsi
+        } else {
+            Transport::Unknown
+        }
+    }
+}
+
+/// A partition on a block device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockPartition {
+    /// Kernel name (e.g., "nvme0n1p2")
+    pub name: String,
+    /// Partition number
+    pub number: u32,
+    /// Size in bytes
+    pub size_bytes: u64,
+}
+
+/// A whole block device from `/sys/class/block`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDevice {
+    /// Kernel name (e.g., "nvme0n1")
+    pub name: String,
+    /// Size in bytes
+    pub size_bytes: u64,
+    /// Kernel reports the medium as removable
+    pub removable: bool,
+    /// Spinning disk (false for SSD/NVMe/virtual)
+    pub rotational: bool,
+    /// Read-only (e.g., write-protected SD card, optical drive)
+    pub read_only: bool,
+    /// Model string, if the driver exposes one
+    pub model: Option<String>,
+    /// How the device is attached
+    pub transport: Transport,
+    /// Partitions, ordered by number
+    pub partitions: Vec<BlockPartition>,
+}
+
+impl BlockDevice {
+    /// Device node path (e.g., "/dev/nvme0n1").
+    pub fn dev_path(&self) -> String {
+        format!("/dev/{}", self.name)
+    }
+
+    /// Whether this device can be offered as an installation target.
+    pub fn is_install_candidate(&self) -> bool {
+        !VIRTUAL_BLOCK_PREFIXES
+            .iter()
+            .any(|p| self.name.starts_with(p))
+            && !self.read_only
+            && self.size_bytes > 0
+    }
+}
+
+/// What identified the live medium.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveMediumMatch {
+    /// ISO9660 volume ID matched the expected label
+    IsoLabel,
+    /// An EROFS or squashfs image starts at the beginning of the device
+    RootfsMagic,
+}
+
+/// The device the live system booted from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveMedium {
+    /// Whole-disk device containing the medium
+    pub device: BlockDevice,
+    /// Device (whole disk or partition) where the match was found
+    pub matched_name: String,
+    /// How it was identified
+    pub matched_by: LiveMediumMatch,
+}
+
+/// Enumerates block devices from a sysfs root.
+#[derive(Debug, Clone)]
+pub struct BlockDevices {
+    root: PathBuf,
+}
+
+impl Default for BlockDevices {
+    fn default() -> Self {
+        Self::with_root("/")
+    }
+}
+
+impl BlockDevices {
+    /// Enumerate devices on the running system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enumerate devices in a fixture tree containing `sys/class/block` and `dev/`.
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// All whole-disk block devices, sorted by name.
+    ///
+    /// Partitions are attached to their parent device instead of being listed
+    /// separately.
+    pub fn scan(&self) -> io::Result<Vec<BlockDevice>> {
+        let mut devices = Vec::new();
+        for entry in fs::read_dir(self.root.join(SYS_CLASS_BLOCK))? {
+            let entry = entry?;
+            let dir = entry.path();
+            if dir.join("partition").exists() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            devices.push(read_device(&name, &dir));
+        }
+        devices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(devices)
+    }
+
+    /// Find the live medium: the device whose ISO9660 volume ID is `iso_label`,
+    /// or whose contents start with an EROFS/squashfs image.
+    ///
+    /// Partitions are checked too, since a hybrid ISO written to a USB stick may
+    /// expose the filesystem on a partition.
+    pub fn live_medium(&self, iso_label: &str) -> io::Result<Option<LiveMedium>> {
+        let devices = self.scan()?;
+        for device in &devices {
+            let names = std::iter::once(device.name.as_str())
+                .chain(device.partitions.iter().map(|p| p.name.as_str()));
+            for name in names {
+                let node = self.root.join("dev").join(name);
+                if let Some(matched_by) = identify_medium(&node, iso_label) {
+                    return Ok(Some(LiveMedium {
+                        device: device.clone(),
+                        matched_name: name.to_string(),
+                        matched_by,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Devices suitable for installation, excluding the live medium.
+    ///
+    /// Non-removable disks are listed first, then by name.
+    pub fn install_candidates(&self, iso_label: &str) -> io::Result<Vec<BlockDevice>> {
+        let live = self.live_medium(iso_label)?.map(|m| m.device.name);
+        let mut candidates: Vec<_> = self
+            .scan()?
+            .into_iter()
+            .filter(|d| d.is_install_candidate() && Some(&d.name) != live.as_ref())
+            .collect();
+        candidates.sort_by(|a, b| (a.removable, &a.name).cmp(&(b.removable, &b.name)));
+        Ok(candidates)
+    }
+}
+
+fn read_device(name: &str, dir: &Path) -> BlockDevice {
+    let resolved = fs::canonicalize(dir)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let model = read_trimmed(&dir.join("device/model"))
+        .or_else(|| read_trimmed(&dir.join("device/name")))
+        .filter(|m| !m.is_empty());
+
+    let mut partitions: Vec<BlockPartition> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let number = read_trimmed(&e.path().join("partition"))?.parse().ok()?;
+            Some(BlockPartition {
+                name: e.file_name().to_string_lossy().into_owned(),
+                number,
+                size_bytes: read_sectors(&e.path()),
+            })
+        })
+        .collect();
+    partitions.sort_by_key(|p| p.number);
+
+    BlockDevice {
+        name: name.to_string(),
+        size_bytes: read_sectors(dir),
+        removable: read_trimmed(&dir.join("removable")).as_deref() == Some("1"),
+        rotational: read_trimmed(&dir.join("queue/rotational")).as_deref() == Some("1"),
+        read_only: read_trimmed(&dir.join("ro")).as_deref() == Some("1"),
+        model,
+        transport: Transport::detect(name, &resolved),
+        partitions,
+    }
+}
+
+/// Size in bytes from a sysfs `size` file (always 512-byte sectors).
+fn read_sectors(dir: &Path) -> u64 {
+    read_trimmed(&dir.join("size"))
+        .and_then(|s| s.parse::<u64>().ok())
+        .map_or(0, |s| s * 512)
+}
+
+fn identify_medium(node: &Path, iso_label: &str) -> Option<LiveMediumMatch> {
+    let mut file = File::open(node).ok()?;
+    if iso_volume_id(&mut file).as_deref() == Some(iso_label) {
+        return Some(LiveMediumMatch::IsoLabel);
+    }
+    if matches!(detect_format(&mut file), Ok(Some(_))) {
+        return Some(LiveMediumMatch::RootfsMagic);
+    }
+    None
+}
+
+/// Read the volume ID from an ISO9660 primary volume descriptor.
+///
+/// Returns `None` if the device does not contain an ISO9660 filesystem.
+pub fn iso_volume_id<R: Read + Seek>(reader: &mut R) -> Option<String> {
+    let mut pvd = [0u8; 72];
+    reader.seek(SeekFrom::Start(ISO9660_PVD_OFFSET)).ok()?;
+    reader.read_exact(&mut pvd).ok()?;
+    // Type 1 = primary volume descriptor, volume ID is a-characters at 40..72
+    if pvd[0] != 1 || &pvd[1..6] != ISO9660_STANDARD_ID {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&pvd[40..72]).trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::rootfs::{EROFS_MAGIC, EROFS_MAGIC_OFFSET};
+    use crate::shared::test_util::TempDir;
+
+    fn iso_image(label: &str) -> Vec<u8> {
+        let mut image = vec![0u8; ISO9660_PVD_OFFSET as usize + 2048];
+        let pvd = &mut image[ISO9660_PVD_OFFSET as usize..];
+        pvd[0] = 1;
+        pvd[1..6].copy_from_slice(ISO9660_STANDARD_ID);
+        pvd[40..72].fill(b' ');
+        pvd[40..40 + label.len()].copy_from_slice(label.as_bytes());
+        image
+    }
+
+    /// Fixture mirroring real sysfs: class/block entries are symlinks into /sys/devices.
+    fn add_disk(dir: &TempDir, name: &str, devpath: &str, sectors: u64, removable: bool) {
+        let real = format!("sys/devices/{}/block/{}", devpath, name);
+        dir.write(&format!("{}/size", real), format!("{}\n", sectors));
+        dir.write(
+            &format!("{}/removable", real),
+            if removable { "1\n" } else { "0\n" },
+        );
+        dir.write(&format!("{}/ro", real), "0\n");
+        dir.write(&format!("{}/queue/rotational", real), "0\n");
+        dir.symlink(
+            &format!("sys/class/block/{}", name),
+            &format!("../../devices/{}/block/{}", devpath, name),
+        );
+    }
+
+    fn add_partition(dir: &TempDir, disk: &str, devpath: &str, name: &str, number: u32) {
+        let real = format!("sys/devices/{}/block/{}/{}", devpath, disk, name);
+        dir.write(&format!("{}/partition", real), format!("{}\n", number));
+        dir.write(&format!("{}/size", real), "2048\n");
+        dir.symlink(
+            &format!("sys/class/block/{}", name),
+            &format!("../../devices/{}/block/{}/{}", devpath, disk, name),
+        );
+    }
+
+    fn fixture() -> TempDir {
+        let dir = TempDir::new("devices");
+        add_disk(
+            &dir,
+            "nvme0n1",
+            "pci0000:00/0000:00:01.0/nvme/nvme0",
+            976_562_500,
+            false,
+        );
+        dir.write(
+            "sys/devices/pci0000:00/0000:00:01.0/nvme/nvme0/block/nvme0n1/device/model",
+            "Samsung SSD 980 PRO 500GB               \n",
+        );
+        add_partition(
+            &dir,
+            "nvme0n1",
+            "pci0000:00/0000:00:01.0/nvme/nvme0",
+            "nvme0n1p2",
+            2,
+        );
+        add_partition(
+            &dir,
+            "nvme0n1",
+            "pci0000:00/0000:00:01.0/nvme/nvme0",
+            "nvme0n1p1",
+            1,
+        );
+        add_disk(
+            &dir,
+            "nvme1n1",
+            "pci0000:00/0000:00:02.0/nvme/nvme1",
+            1_953_125_000,
+            false,
+        );
+        add_disk(&dir, "mmcblk0", "platform/mmc0/mmc0:0001", 62_333_952, true);
+        add_disk(
+            &dir,
+            "sdc",
+            "pci0000:00/0000:00:14.0/usb2/2-1/host3/target3:0:0/3:0:0:0",
+            62_500_000,
+            true,
+        );
+        add_partition(
+            &dir,
+            "sdc",
+            "pci0000:00/0000:00:14.0/usb2/2-1/host3/target3:0:0/3:0:0:0",
+            "sdc1",
+            1,
+        );
+        add_disk(&dir, "loop0", "virtual", 1000, false);
+        dir.write("dev/nvme0n1", vec![0u8; 4096]);
+        dir.write("dev/nvme1n1", vec![0u8; 4096]);
+        dir
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = fixture();
+        let devices = BlockDevices::with_root(dir.path()).scan().unwrap();
+        let names: Vec<_> = devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["loop0", "mmcblk0", "nvme0n1", "nvme1n1", "sdc"]);
+
+        let nvme = &devices[2];
+        assert_eq!(nvme.size_bytes, 500_000_000_000);
+        assert_eq!(nvme.transport, Transport::Nvme);
+        assert_eq!(nvme.model.as_deref(), Some("Samsung SSD 980 PRO 500GB"));
+        assert_eq!(nvme.partitions.len(), 2);
+        assert_eq!(nvme.partitions[0].name, "nvme0n1p1");
+        assert_eq!(nvme.partitions[1].size_bytes, 2048 * 512);
+
+        assert_eq!(devices[4].transport, Transport::Usb);
+        assert!(devices[4].removable);
+        assert_eq!(devices[1].transport, Transport::Mmc);
+    }
+
+    #[test]
+    fn test_live_medium_by_label() {
+        let dir = fixture();
+        // Hybrid ISO written to the USB stick; label is visible on the whole disk
+        dir.write("dev/sdc", iso_image("LEVITATEOS"));
+        let devices = BlockDevices::with_root(dir.path());
+
+        let live = devices.live_medium("LEVITATEOS").unwrap().unwrap();
+        assert_eq!(live.device.name, "sdc");
+        assert_eq!(live.matched_by, LiveMediumMatch::IsoLabel);
+
+        let candidates = devices.install_candidates("LEVITATEOS").unwrap();
+        let names: Vec<_> = candidates.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["nvme0n1", "nvme1n1", "mmcblk0"]);
+
+        assert!(devices.live_medium("ACORNOS").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_live_medium_by_rootfs_magic() {
+        let dir = fixture();
+        let mut image = vec![0u8; 4096];
+        image[EROFS_MAGIC_OFFSET as usize..][..4].copy_from_slice(&EROFS_MAGIC.to_le_bytes());
+        dir.write("dev/sdc1", image);
+
+        let live = BlockDevices::with_root(dir.path())
+            .live_medium("LEVITATEOS")
+            .unwrap()
+            .unwrap();
+        assert_eq!(live.device.name, "sdc");
+        assert_eq!(live.matched_name, "sdc1");
+        assert_eq!(live.matched_by, LiveMediumMatch::RootfsMagic);
+    }
+}
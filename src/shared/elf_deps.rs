@@ -0,0 +1,924 @@
+//! Shared-library dependency closure of binaries in a sysroot.
+//!
+//! `CRITICAL_LIBS` is maintained by hand and the builders used to find
+//! library dependencies by running `ldd`, which executes the target's dynamic
+//! linker. This module reads the ELF headers instead: `ElfFile` extracts the
+//! interpreter (`PT_INTERP`), `DT_NEEDED`, `DT_RPATH` and `DT_RUNPATH`, and
+//! `DependencyResolver` follows them through a sysroot the way `ld.so` would,
+//! resolving symlinks inside the sysroot. Nothing in the sysroot is executed.
+//!
+//! Libraries loaded with `dlopen()` (NSS modules, PAM modules, glibc's
+//! compatibility stubs such as `libpthread.so.0`) are not `DT_NEEDED` by
+//! anything, so `unreachable_critical_libs()` lists them; that is expected.
+//!
+//! `ld.so.conf` is not read; `DEFAULT_LIBRARY_DIRS` covers the trusted
+//! directories of both variants. `$LIB` in it (and in `DT_RPATH` /
+//! `DT_RUNPATH`) expands per object, from its class and machine, the way
+//! glibc's loader does.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::components::{critical_libs, BIN_UTILS, SBIN_UTILS};
+use super::rootfs_manifest::{resolve_traced, Resolved};
+use crate::arch::Arch;
+
+/// Directories searched after `DT_RPATH`/`DT_RUNPATH`, in order.
+///
+/// `$LIB` is the object's library directory (see `lib_dir`).
+pub const DEFAULT_LIBRARY_DIRS: &[&str] = &["usr/$LIB", "$LIB", "usr/lib", "lib"];
+
+// Program header types
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+
+// Dynamic section tags
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_STRSZ: u64 = 10;
+const DT_RPATH: u64 = 15;
+const DT_RUNPATH: u64 = 29;
+
+// =============================================================================
+// ELF Reader
+// =============================================================================
+
+/// Why a file could not be read as ELF.
+#[derive(Debug)]
+pub enum ElfError {
+    /// Reading the file failed
+    Io(io::Error),
+    /// No ELF magic (scripts, data files)
+    NotElf,
+    /// A header or table points past the end of the file
+    Truncated,
+    /// Valid ELF this reader does not handle
+    Unsupported(&'static str),
+    /// Headers whose values cannot be right (e.g. a segment wrapping the
+    /// address space)
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElfError::Io(e) => write!(f, "{}", e),
+            ElfError::NotElf => f.write_str("not an ELF file"),
+            ElfError::Truncated => f.write_str("truncated ELF file"),
+            ElfError::Unsupported(what) => write!(f, "unsupported ELF: {}", what),
+            ElfError::Invalid(what) => write!(f, "invalid ELF: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ElfError {}
+
+/// ELF word size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfClass {
+    Elf32,
+    Elf64,
+}
+
+/// Dynamic-linking information of one ELF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfFile {
+    /// Word size
+    pub class: ElfClass,
+    /// `e_machine` (see `Arch::elf_machine`)
+    pub machine: u16,
+    /// `PT_INTERP` (absolute path of the dynamic linker), if any
+    pub interpreter: Option<String>,
+    /// `DT_NEEDED` entries, in order
+    pub needed: Vec<String>,
+    /// `DT_RPATH` directories
+    pub rpath: Vec<String>,
+    /// `DT_RUNPATH` directories
+    pub runpath: Vec<String>,
+}
+
+impl ElfFile {
+    /// Read and parse `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ElfError> {
+        Self::parse(&fs::read(path).map_err(ElfError::Io)?)
+    }
+
+    /// Parse an ELF image.
+    pub fn parse(data: &[u8]) -> Result<Self, ElfError> {
+        if data.len() < 6 || &data[..4] != b"\x7fELF" {
+            return Err(ElfError::NotElf);
+        }
+        let class = match data[4] {
+            1 => ElfClass::Elf32,
+            2 => ElfClass::Elf64,
+            _ => return Err(ElfError::Unsupported("unknown class")),
+        };
+        let le = match data[5] {
+            1 => true,
+            2 => false,
+            _ => return Err(ElfError::Unsupported("unknown byte order")),
+        };
+        let r = Reader { data, le, class };
+
+        let machine = r.u16(18)?;
+        let (phoff, phentsize, phnum) = match class {
+            ElfClass::Elf64 => (r.u64(32)?, r.u16(54)?, r.u16(56)?),
+            ElfClass::Elf32 => (r.u32(28)? as u64, r.u16(42)?, r.u16(44)?),
+        };
+        if phnum == 0xffff {
+            return Err(ElfError::Unsupported("extended program header count"));
+        }
+
+        let mut loads = Vec::new();
+        let mut dynamic = None;
+        let mut interpreter = None;
+        for i in 0..phnum as u64 {
+            let ph = r.offset(phoff, i * phentsize as u64)?;
+            let p_type = r.u32(ph)?;
+            let (offset, vaddr, filesz) = match class {
+                ElfClass::Elf64 => (r.u64(ph + 8)?, r.u64(ph + 16)?, r.u64(ph + 32)?),
+                ElfClass::Elf32 => (
+                    r.u32(ph + 4)? as u64,
+                    r.u32(ph + 8)? as u64,
+                    r.u32(ph + 16)? as u64,
+                ),
+            };
+            match p_type {
+                PT_LOAD => {
+                    let end = vaddr
+                        .checked_add(filesz)
+                        .ok_or(ElfError::Invalid("PT_LOAD segment wraps the address space"))?;
+                    loads.push((offset, vaddr, end));
+                }
+                PT_DYNAMIC => dynamic = Some((offset, filesz)),
+                PT_INTERP => interpreter = Some(r.string(offset, filesz)?),
+                _ => {}
+            }
+        }
+
+        let mut elf = ElfFile {
+            class,
+            machine,
+            interpreter,
+            needed: Vec::new(),
+            rpath: Vec::new(),
+            runpath: Vec::new(),
+        };
+        let Some((dyn_off, dyn_size)) = dynamic else {
+            // Statically linked
+            return Ok(elf);
+        };
+
+        let entsize = match class {
+            ElfClass::Elf64 => 16,
+            ElfClass::Elf32 => 8,
+        };
+        let mut entries = Vec::new();
+        let (mut strtab, mut strsz) = (None, None);
+        for i in 0..dyn_size / entsize {
+            let at = r.offset(dyn_off, i * entsize)?;
+            let tag = r.word(at)?;
+            let val = r.word(at + entsize / 2)?;
+            match tag {
+                DT_NULL => break,
+                DT_STRTAB => strtab = Some(val),
+                DT_STRSZ => strsz = Some(val),
+                DT_NEEDED | DT_RPATH | DT_RUNPATH => entries.push((tag, val)),
+                _ => {}
+            }
+        }
+        if entries.is_empty() {
+            return Ok(elf);
+        }
+
+        // DT_STRTAB is an address; map it back to a file offset
+        let strtab = strtab.ok_or(ElfError::Unsupported("DT_NEEDED without DT_STRTAB"))?;
+        let str_off = loads
+            .iter()
+            .find(|(_, vaddr, end)| (*vaddr..*end).contains(&strtab))
+            .map(|(offset, vaddr, _)| offset + (strtab - vaddr))
+            .ok_or(ElfError::Truncated)?;
+        let str_end = match strsz {
+            Some(size) => str_off.checked_add(size).ok_or(ElfError::Truncated)?,
+            None => data.len() as u64,
+        };
+        for (tag, val) in entries {
+            let start = r.offset(str_off, val)?;
+            let s = r.string(start, str_end.saturating_sub(start))?;
+            match tag {
+                DT_NEEDED => elf.needed.push(s),
+                DT_RPATH => elf.rpath.extend(split_path_list(&s)),
+                _ => elf.runpath.extend(split_path_list(&s)),
+            }
+        }
+        Ok(elf)
+    }
+
+    /// Whether a library with this header can satisfy `other`'s dependencies.
+    pub fn compatible_with(&self, other: &ElfFile) -> bool {
+        self.class == other.class && self.machine == other.machine
+    }
+
+    /// What `$LIB` expands to for this object: its multilib directory,
+    /// relative to `/` or `/usr`.
+    pub fn lib_dir(&self) -> &'static str {
+        match self.class {
+            ElfClass::Elf64 => "lib64",
+            // x32: 32-bit pointers on x86_64
+            ElfClass::Elf32 if self.machine == Arch::X86_64.elf_machine() => "libx32",
+            ElfClass::Elf32 => "lib",
+        }
+    }
+}
+
+fn split_path_list(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(':').filter(|d| !d.is_empty()).map(String::from)
+}
+
+/// Bounds-checked field access.
+struct Reader<'a> {
+    data: &'a [u8],
+    le: bool,
+    class: ElfClass,
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, at: u64) -> Result<[u8; N], ElfError> {
+        let at = usize::try_from(at).map_err(|_| ElfError::Truncated)?;
+        let end = at.checked_add(N).ok_or(ElfError::Truncated)?;
+        let slice = self.data.get(at..end).ok_or(ElfError::Truncated)?;
+        let mut out = [0; N];
+        out.copy_from_slice(slice);
+        if !self.le {
+            out.reverse();
+        }
+        Ok(out)
+    }
+
+    fn u16(&self, at: u64) -> Result<u16, ElfError> {
+        self.bytes(at).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, at: u64) -> Result<u32, ElfError> {
+        self.bytes(at).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, at: u64) -> Result<u64, ElfError> {
+        self.bytes(at).map(u64::from_le_bytes)
+    }
+
+    /// Address-sized field.
+    fn word(&self, at: u64) -> Result<u64, ElfError> {
+        match self.class {
+            ElfClass::Elf64 => self.u64(at),
+            ElfClass::Elf32 => self.u32(at).map(u64::from),
+        }
+    }
+
+    fn offset(&self, base: u64, add: u64) -> Result<u64, ElfError> {
+        base.checked_add(add).ok_or(ElfError::Truncated)
+    }
+
+    /// NUL-terminated string starting at `at`, at most `max` bytes long.
+    fn string(&self, at: u64, max: u64) -> Result<String, ElfError> {
+        let at = usize::try_from(at).map_err(|_| ElfError::Truncated)?;
+        let rest = self.data.get(at..).ok_or(ElfError::Truncated)?;
+        let rest = &rest[..rest.len().min(usize::try_from(max).unwrap_or(usize::MAX))];
+        let end = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(ElfError::Truncated)?;
+        Ok(String::from_utf8_lossy(&rest[..end]).into_owned())
+    }
+}
+
+// =============================================================================
+// Closure
+// =============================================================================
+
+/// A resolved shared library (or dynamic linker).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Library {
+    /// Sysroot-relative path of the file itself (all symlinks resolved)
+    pub path: String,
+    /// Names it was requested by (`DT_NEEDED` sonames or the `PT_INTERP` path)
+    pub names: BTreeSet<String>,
+    /// Symlinks followed to reach it, sysroot-relative
+    pub links: BTreeSet<String>,
+    /// Objects (`path`s of binaries or libraries) that need it
+    pub needed_by: BTreeSet<String>,
+}
+
+/// Something the closure could not resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyIssue {
+    /// A requested binary does not exist in the sysroot
+    MissingBinary { path: String },
+    /// No search directory contains a compatible `name`
+    Unresolved { name: String, needed_by: String },
+    /// The `PT_INTERP` dynamic linker does not exist
+    MissingInterpreter { path: String, needed_by: String },
+    /// A file exists but could not be parsed
+    Unreadable { path: String, reason: String },
+}
+
+impl fmt::Display for DependencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyIssue::MissingBinary { path } => write!(f, "{}: missing", path),
+            DependencyIssue::Unresolved { name, needed_by } => {
+                write!(f, "{}: {} not found", needed_by, name)
+            }
+            DependencyIssue::MissingInterpreter { path, needed_by } => {
+                write!(f, "{}: interpreter {} not found", needed_by, path)
+            }
+            DependencyIssue::Unreadable { path, reason } => write!(f, "{}: {}", path, reason),
+        }
+    }
+}
+
+/// Result of `DependencyResolver::resolve`.
+#[derive(Debug, Clone, Default)]
+pub struct LibraryClosure {
+    root: PathBuf,
+    /// Requested binaries that were found (sysroot-relative, symlinks resolved)
+    pub binaries: Vec<String>,
+    /// Every library reached, keyed by `Library::path`
+    pub libraries: BTreeMap<String, Library>,
+    /// Requested files that are not ELF (scripts); they need no libraries
+    pub non_elf: Vec<String>,
+    /// Everything that could not be resolved
+    pub issues: Vec<DependencyIssue>,
+}
+
+impl LibraryClosure {
+    /// Whether every dependency was resolved.
+    pub fn is_complete(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// `DT_NEEDED` names that were not found, with the object needing them.
+    pub fn unresolved(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.issues.iter().filter_map(|i| match i {
+            DependencyIssue::Unresolved { name, needed_by } => {
+                Some((name.as_str(), needed_by.as_str()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Sysroot-relative paths to copy to reproduce the closure: every
+    /// library file and every symlink on the way to it.
+    pub fn files(&self) -> BTreeSet<&str> {
+        let mut files = BTreeSet::new();
+        for lib in self.libraries.values() {
+            files.insert(lib.path.as_str());
+            files.extend(lib.links.iter().map(String::as_str));
+        }
+        files
+    }
+
+    /// Whether the loader touches `path` (sysroot-relative) while loading the
+    /// binaries. Directory symlinks in `path` are resolved first, so
+    /// `lib64/libc.so.6` and `usr/lib64/libc.so.6` are the same file on a
+    /// merged-usr sysroot.
+    pub fn reaches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches('/');
+        let files = self.files();
+        if files.contains(path) {
+            return true;
+        }
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        match resolve_traced(&self.root, dir, &mut Vec::new()) {
+            Ok(Resolved::Found(host)) => {
+                let dir = relative(&self.root, &host);
+                let canonical = if dir.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", dir, name)
+                };
+                files.contains(canonical.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// Entries of `paths` the loader never touches.
+    pub fn unreachable<'a>(&self, paths: &[&'a str]) -> Vec<&'a str> {
+        paths.iter().copied().filter(|p| !self.reaches(p)).collect()
+    }
+
+    /// `critical_libs(arch)` entries the loader never touches.
+    pub fn unreachable_critical_libs(&self, arch: Arch) -> Vec<&'static str> {
+        self.unreachable(&critical_libs(arch))
+    }
+}
+
+impl fmt::Display for LibraryClosure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} binaries, {} libraries",
+            self.binaries.len(),
+            self.libraries.len()
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Resolver
+// =============================================================================
+
+/// Follows ELF dependencies through a sysroot.
+#[derive(Debug, Clone)]
+pub struct DependencyResolver {
+    root: PathBuf,
+    library_dirs: Vec<String>,
+}
+
+impl DependencyResolver {
+    /// Resolver for the sysroot at `root`, searching `DEFAULT_LIBRARY_DIRS`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            library_dirs: DEFAULT_LIBRARY_DIRS.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    /// Search these sysroot-relative directories instead of the defaults;
+    /// `$LIB` is expanded as in `DEFAULT_LIBRARY_DIRS`.
+    pub fn with_library_dirs(mut self, dirs: &[&str]) -> Self {
+        self.library_dirs = dirs
+            .iter()
+            .map(|d| d.trim_matches('/').to_string())
+            .collect();
+        self
+    }
+
+    /// Library closure of `binaries` (sysroot-relative paths).
+    pub fn resolve<S: AsRef<str>>(&self, binaries: &[S]) -> LibraryClosure {
+        let mut closure = LibraryClosure {
+            root: self.root.clone(),
+            ..LibraryClosure::default()
+        };
+        // Objects whose dependencies still have to be followed
+        let mut queue: VecDeque<(String, ElfFile)> = VecDeque::new();
+        let mut seen = BTreeSet::new();
+
+        for bin in binaries {
+            let rel = bin.as_ref().trim_start_matches('/');
+            let Some((path, _)) = self.locate(rel) else {
+                closure.issues.push(DependencyIssue::MissingBinary {
+                    path: rel.to_string(),
+                });
+                continue;
+            };
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            match ElfFile::read(self.root.join(&path)) {
+                Ok(elf) => {
+                    closure.binaries.push(path.clone());
+                    queue.push_back((path, elf));
+                }
+                Err(ElfError::NotElf) => closure.non_elf.push(path),
+                Err(e) => closure.issues.push(DependencyIssue::Unreadable {
+                    path,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        while let Some((object, elf)) = queue.pop_front() {
+            if let Some(interp) = &elf.interpreter {
+                match self.locate(interp.trim_start_matches('/')) {
+                    Some((path, links)) => {
+                        // Read the located file: the interpreter path may be an
+                        // absolute symlink that only resolves inside the sysroot
+                        let elf = ElfFile::read(self.root.join(&path));
+                        let lib = (path, links, elf);
+                        self.add(&mut closure, &mut queue, &mut seen, &object, interp, lib);
+                    }
+                    None => closure.issues.push(DependencyIssue::MissingInterpreter {
+                        path: interp.clone(),
+                        needed_by: object.clone(),
+                    }),
+                }
+            }
+            for name in &elf.needed {
+                match self.find_library(&object, &elf, name) {
+                    Some(lib) => self.add(&mut closure, &mut queue, &mut seen, &object, name, lib),
+                    None => closure.issues.push(DependencyIssue::Unresolved {
+                        name: name.clone(),
+                        needed_by: object.clone(),
+                    }),
+                }
+            }
+        }
+        closure
+    }
+
+    /// Record a resolved library and queue it if it is new.
+    fn add(
+        &self,
+        closure: &mut LibraryClosure,
+        queue: &mut VecDeque<(String, ElfFile)>,
+        seen: &mut BTreeSet<String>,
+        object: &str,
+        name: &str,
+        (path, links, elf): (String, Vec<String>, Result<ElfFile, ElfError>),
+    ) {
+        let lib = closure.libraries.entry(path.clone()).or_default();
+        lib.path = path.clone();
+        lib.names.insert(name.to_string());
+        lib.links.extend(links);
+        lib.needed_by.insert(object.to_string());
+        if !seen.insert(path.clone()) {
+            return;
+        }
+        match elf {
+            Ok(elf) => queue.push_back((path, elf)),
+            Err(e) => closure.issues.push(DependencyIssue::Unreadable {
+                path,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    /// Search for `name` needed by `object`, in `ld.so` order: `DT_RPATH`
+    /// (only without `DT_RUNPATH`), `DT_RUNPATH`, then the library dirs.
+    /// Libraries of another class or machine are skipped.
+    fn find_library(
+        &self,
+        object: &str,
+        elf: &ElfFile,
+        name: &str,
+    ) -> Option<(String, Vec<String>, Result<ElfFile, ElfError>)> {
+        if name.contains('/') {
+            let (path, links) = self.locate(name.trim_start_matches('/'))?;
+            let lib = ElfFile::read(self.root.join(&path));
+            return Some((path, links, lib));
+        }
+        let origin = object.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let rpath = if elf.runpath.is_empty() {
+            &elf.rpath[..]
+        } else {
+            &[]
+        };
+        let mut dirs: Vec<String> = Vec::new();
+        for dir in rpath
+            .iter()
+            .chain(&elf.runpath)
+            .map(|d| expand_dir(d, origin, elf))
+            .chain(self.library_dirs.iter().map(|d| expand_dir(d, "", elf)))
+        {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        for dir in dirs {
+            let rel = if dir.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", dir, name)
+            };
+            let Some((path, links)) = self.locate(&rel) else {
+                continue;
+            };
+            match ElfFile::read(self.root.join(&path)) {
+                Ok(lib) if !lib.compatible_with(elf) => continue,
+                lib => return Some((path, links, lib)),
+            }
+        }
+        None
+    }
+
+    /// Resolve `rel` inside the sysroot: (real path, symlinks followed).
+    fn locate(&self, rel: &str) -> Option<(String, Vec<String>)> {
+        let mut links = Vec::new();
+        match resolve_traced(&self.root, rel, &mut links) {
+            Ok(Resolved::Found(host)) if host.is_file() => {
+                Some((relative(&self.root, &host), links))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Expand `$ORIGIN` and `$LIB` in a search directory of `elf`; the result
+/// is sysroot-relative.
+fn expand_dir(dir: &str, origin: &str, elf: &ElfFile) -> String {
+    let dir = dir
+        .replace("${ORIGIN}", "$ORIGIN")
+        .replace("${LIB}", "$LIB")
+        .replace("$LIB", elf.lib_dir());
+    let dir = match dir.strip_prefix("$ORIGIN") {
+        Some(rest) => format!("{}{}", origin, rest),
+        None => dir,
+    };
+    dir.trim_matches('/').to_string()
+}
+
+fn relative(root: &Path, host: &Path) -> String {
+    host.strip_prefix(root)
+        .unwrap_or(host)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Sysroot-relative paths of every `BIN_UTILS` and `SBIN_UTILS` entry.
+pub fn component_binaries() -> Vec<String> {
+    BIN_UTILS
+        .iter()
+        .map(|b| format!("usr/bin/{}", b))
+        .chain(SBIN_UTILS.iter().map(|b| format!("usr/sbin/{}", b)))
+        .collect()
+}
+
+/// Library closure of `component_binaries()` in the sysroot at `root`.
+pub fn resolve_components(root: impl Into<PathBuf>) -> LibraryClosure {
+    DependencyResolver::new(root).resolve(&component_binaries())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    /// Minimal dynamically linked ELF image (little endian).
+    fn elf(
+        class: ElfClass,
+        interp: Option<&str>,
+        needed: &[&str],
+        runpath: Option<&str>,
+    ) -> Vec<u8> {
+        let is64 = class == ElfClass::Elf64;
+        let (ehsize, phsize, dynsize) = if is64 { (64, 56, 16) } else { (52, 32, 8) };
+        let base: u64 = 0x40_0000;
+
+        // String table
+        let mut strtab = vec![0u8];
+        let mut add = |s: &str| {
+            let at = strtab.len() as u64;
+            strtab.extend_from_slice(s.as_bytes());
+            strtab.push(0);
+            at
+        };
+        let mut dynamic: Vec<(u64, u64)> = needed.iter().map(|n| (DT_NEEDED, add(n))).collect();
+        if let Some(r) = runpath {
+            dynamic.push((DT_RUNPATH, add(r)));
+        }
+
+        let phnum = 3u64;
+        let interp_off = ehsize + phnum * phsize;
+        let interp_bytes: Vec<u8> = interp
+            .map(|i| [i.as_bytes(), &[0]].concat())
+            .unwrap_or_default();
+        let str_off = interp_off + interp_bytes.len() as u64;
+        let dyn_off = str_off + strtab.len() as u64;
+        dynamic.push((DT_STRTAB, base + str_off));
+        dynamic.push((DT_STRSZ, strtab.len() as u64));
+        dynamic.push((DT_NULL, 0));
+        let total = dyn_off + dynamic.len() as u64 * dynsize;
+
+        let mut out = Vec::new();
+        let word = |out: &mut Vec<u8>, v: u64| {
+            if is64 {
+                out.extend_from_slice(&v.to_le_bytes())
+            } else {
+                out.extend_from_slice(&(v as u32).to_le_bytes())
+            }
+        };
+        // ELF header
+        out.extend_from_slice(b"\x7fELF");
+        out.extend_from_slice(&[if is64 { 2 } else { 1 }, 1, 1, 0]);
+        out.resize(16, 0);
+        out.extend_from_slice(&3u16.to_le_bytes()); // ET_DYN
+        out.extend_from_slice(&(if is64 { 62u16 } else { 3 }).to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        word(&mut out, 0); // e_entry
+        word(&mut out, ehsize); // e_phoff
+        word(&mut out, 0); // e_shoff
+        out.extend_from_slice(&0u32.to_le_bytes());
+        for v in [ehsize, phsize, phnum, 0, 0, 0] {
+            out.extend_from_slice(&(v as u16).to_le_bytes());
+        }
+        // Program headers
+        let interp_len = interp_bytes.len() as u64;
+        let dyn_len = total - dyn_off;
+        for (p_type, offset, filesz) in [
+            (PT_LOAD, 0, total),
+            (
+                if interp.is_some() { PT_INTERP } else { 0 },
+                interp_off,
+                interp_len,
+            ),
+            (PT_DYNAMIC, dyn_off, dyn_len),
+        ] {
+            out.extend_from_slice(&p_type.to_le_bytes());
+            if is64 {
+                out.extend_from_slice(&0u32.to_le_bytes()); // p_flags
+            }
+            word(&mut out, offset);
+            word(&mut out, base + offset); // p_vaddr
+            word(&mut out, base + offset); // p_paddr
+            word(&mut out, filesz);
+            word(&mut out, filesz);
+            if !is64 {
+                out.extend_from_slice(&0u32.to_le_bytes()); // p_flags
+            }
+            word(&mut out, 0x1000); // p_align
+        }
+        out.extend_from_slice(&interp_bytes);
+        out.extend_from_slice(&strtab);
+        for (tag, val) in dynamic {
+            word(&mut out, tag);
+            word(&mut out, val);
+        }
+        assert_eq!(out.len() as u64, total);
+        out
+    }
+
+    const LD: &str = "/lib64/ld-linux-x86-64.so.2";
+
+    fn sysroot() -> TempDir {
+        let dir = TempDir::new("elf-deps");
+        let lib = |rel: &str, needed: &[&str], runpath: Option<&str>| {
+            dir.write(rel, "");
+            fs::write(
+                dir.path().join(rel),
+                elf(ElfClass::Elf64, None, needed, runpath),
+            )
+            .unwrap();
+        };
+        dir.mkdir("usr/lib64");
+        dir.symlink("lib64", "usr/lib64");
+        lib("usr/lib64/ld-linux-x86-64.so.2", &[], None);
+        lib("usr/lib64/libc-2.39.so", &[], None);
+        dir.symlink("usr/lib64/libc.so.6", "libc-2.39.so");
+        lib(
+            "usr/lib64/libfoo.so.1",
+            &["libbar.so.1", "libc.so.6"],
+            Some("$ORIGIN/i386:$ORIGIN/foo"),
+        );
+        lib("usr/lib64/foo/libbar.so.1", &["libc.so.6"], None);
+        // Wrong class, first in libfoo's RUNPATH: must be skipped
+        dir.write("usr/lib64/i386/libbar.so.1", "");
+        fs::write(
+            dir.path().join("usr/lib64/i386/libbar.so.1"),
+            elf(ElfClass::Elf32, None, &[], None),
+        )
+        .unwrap();
+        dir.write("usr/bin/ls", "");
+        fs::write(
+            dir.path().join("usr/bin/ls"),
+            elf(
+                ElfClass::Elf64,
+                Some(LD),
+                &["libc.so.6", "libfoo.so.1", "libmissing.so.0"],
+                None,
+            ),
+        )
+        .unwrap();
+        dir.write("usr/bin/script", "#!/bin/sh\necho hi\n");
+        dir
+    }
+
+    #[test]
+    fn test_parse_elf() {
+        let elf64 = ElfFile::parse(&elf(
+            ElfClass::Elf64,
+            Some(LD),
+            &["libc.so.6"],
+            Some("$ORIGIN:/opt/lib"),
+        ))
+        .unwrap();
+        assert_eq!(elf64.class, ElfClass::Elf64);
+        assert_eq!(elf64.machine, 62);
+        assert_eq!(elf64.interpreter.as_deref(), Some(LD));
+        assert_eq!(elf64.needed, vec!["libc.so.6"]);
+        assert_eq!(elf64.runpath, vec!["$ORIGIN", "/opt/lib"]);
+
+        let elf32 = ElfFile::parse(&elf(ElfClass::Elf32, None, &["libm.so.6"], None)).unwrap();
+        assert_eq!(elf32.class, ElfClass::Elf32);
+        assert_eq!(elf32.needed, vec!["libm.so.6"]);
+        assert!(!elf32.compatible_with(&elf64));
+
+        assert!(matches!(
+            ElfFile::parse(b"#!/bin/sh\n"),
+            Err(ElfError::NotElf)
+        ));
+        let mut image = elf(ElfClass::Elf64, None, &["libc.so.6"], None);
+        assert!(matches!(
+            ElfFile::parse(&image[..100]),
+            Err(ElfError::Truncated)
+        ));
+        // p_vaddr of the PT_LOAD header, so that vaddr + filesz overflows
+        image[64 + 16..64 + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(ElfFile::parse(&image), Err(ElfError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_resolve_closure() {
+        let dir = sysroot();
+        let closure = DependencyResolver::new(dir.path()).resolve(&[
+            "usr/bin/ls",
+            "/usr/bin/script",
+            "usr/bin/nope",
+        ]);
+
+        assert_eq!(closure.binaries, vec!["usr/bin/ls"]);
+        assert_eq!(closure.non_elf, vec!["usr/bin/script"]);
+
+        let libc = &closure.libraries["usr/lib64/libc-2.39.so"];
+        assert!(libc.names.contains("libc.so.6"));
+        assert!(libc.links.contains("usr/lib64/libc.so.6"));
+        assert_eq!(libc.needed_by.len(), 3);
+
+        let ld = &closure.libraries["usr/lib64/ld-linux-x86-64.so.2"];
+        assert!(ld.links.contains("lib64"));
+        assert!(closure.libraries.contains_key("usr/lib64/foo/libbar.so.1"));
+
+        assert_eq!(
+            closure.unresolved().collect::<Vec<_>>(),
+            vec![("libmissing.so.0", "usr/bin/ls")]
+        );
+        assert!(closure.issues.contains(&DependencyIssue::MissingBinary {
+            path: "usr/bin/nope".into()
+        }));
+        assert!(!closure.is_complete());
+    }
+
+    #[test]
+    fn test_absolute_interpreter_link() {
+        let dir = TempDir::new("elf-deps-interp");
+        dir.write(
+            "usr/lib64/ld-2.39.so",
+            elf(ElfClass::Elf64, None, &[], None),
+        );
+        dir.symlink("lib64/ld-linux-x86-64.so.2", "/usr/lib64/ld-2.39.so");
+        dir.write("usr/bin/true", elf(ElfClass::Elf64, Some(LD), &[], None));
+
+        let closure = DependencyResolver::new(dir.path()).resolve(&["usr/bin/true"]);
+        assert!(closure.issues.is_empty(), "{:?}", closure.issues);
+        let ld = &closure.libraries["usr/lib64/ld-2.39.so"];
+        assert!(ld.links.contains("lib64/ld-linux-x86-64.so.2"));
+        assert!(ld.names.contains(LD));
+    }
+
+    #[test]
+    fn test_reachability() {
+        let dir = sysroot();
+        let closure = DependencyResolver::new(dir.path()).resolve(&["usr/bin/ls"]);
+
+        assert!(closure.reaches("usr/lib64/libc.so.6"));
+        assert!(closure.reaches("/lib64/libc.so.6"));
+        assert!(closure.reaches("usr/lib64/ld-linux-x86-64.so.2"));
+        assert!(!closure.reaches("usr/lib64/libpthread.so.0"));
+        assert_eq!(
+            closure.unreachable(&["usr/lib64/libc.so.6", "usr/lib64/libpam.so.0"]),
+            vec!["usr/lib64/libpam.so.0"]
+        );
+        // Only glibc and the linker exist in this sysroot
+        assert_eq!(
+            closure.unreachable_critical_libs(Arch::X86_64).len(),
+            critical_libs(Arch::X86_64).len() - 2
+        );
+        // The aarch64 linker is not the one this sysroot has
+        assert_eq!(
+            closure.unreachable_critical_libs(Arch::Aarch64).len(),
+            critical_libs(Arch::Aarch64).len() - 1
+        );
+    }
+
+    #[test]
+    fn test_expand_dir() {
+        let elf64 = ElfFile::parse(&elf(ElfClass::Elf64, None, &[], None)).unwrap();
+        let i386 = ElfFile::parse(&elf(ElfClass::Elf32, None, &[], None)).unwrap();
+        let x32 = ElfFile {
+            machine: Arch::X86_64.elf_machine(),
+            ..i386.clone()
+        };
+        assert_eq!(
+            expand_dir("$ORIGIN/../lib", "usr/bin", &elf64),
+            "usr/bin/../lib"
+        );
+        assert_eq!(expand_dir("${ORIGIN}", "usr/lib64", &elf64), "usr/lib64");
+        assert_eq!(expand_dir("/usr/$LIB/foo", "", &elf64), "usr/lib64/foo");
+        assert_eq!(expand_dir("usr/${LIB}", "", &x32), "usr/libx32");
+        assert_eq!(expand_dir("usr/$LIB", "", &i386), "usr/lib");
+        assert_eq!(
+            component_binaries().len(),
+            BIN_UTILS.len() + SBIN_UTILS.len()
+        );
+    }
+}
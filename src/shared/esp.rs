@@ -0,0 +1,378 @@
+//! EFI system partition space budget.
+//!
+//! `EFIBOOT_SIZE_MB` (the ISO's efiboot.img) and `EFI_PARTITION_SIZE_MB` (an
+//! installed ESP) are fixed sizes. This module checks real file sizes against
+//! them before anything is written, so a builder fails with "UKIs need 230 MiB,
+//! efiboot.img holds 200 MiB" instead of mtools running out of space halfway.
+//!
+//! Sizes are estimated the way FAT stores them: every file is rounded up to a
+//! whole cluster, and the FAT tables plus reserved sectors are taken off the
+//! top. On top of that a headroom percentage is kept free.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::iso::EFIBOOT_SIZE_MB;
+use super::partitions::EFI_PARTITION_SIZE_MB;
+
+/// FAT cluster size assumed for rounding (mkfs.fat's FAT32 default).
+pub const FAT_CLUSTER_SIZE: u64 = 4096;
+
+/// Space lost to reserved sectors, root directory and alignment.
+pub const FAT_RESERVED_BYTES: u64 = 1024 * 1024;
+
+/// Number of FAT copies (mkfs.fat default).
+pub const FAT_COPIES: u64 = 2;
+
+/// Fraction of the ESP (percent) that must stay free after everything is placed.
+pub const ESP_HEADROOM_PERCENT: u64 = 10;
+
+/// Kernels kept installed at once (current, fallback, LTS).
+///
+/// This is what `EFI_PARTITION_SIZE_MB` is sized for.
+pub const RETAINED_KERNELS: usize = 3;
+
+/// Fixed-size allowance for loader.conf, entry files and the random seed.
+pub const LOADER_FILES_BYTES: u64 = 64 * 1024;
+
+/// Round a file size up to whole FAT clusters.
+pub fn fat_allocated_size(size: u64) -> u64 {
+    size.div_ceil(FAT_CLUSTER_SIZE) * FAT_CLUSTER_SIZE
+}
+
+/// Bytes available for files on a FAT32 filesystem of `capacity` bytes.
+pub fn fat_usable_bytes(capacity: u64) -> u64 {
+    let clusters = capacity / FAT_CLUSTER_SIZE;
+    let fat_tables = FAT_COPIES * clusters * 4;
+    capacity.saturating_sub(fat_tables + FAT_RESERVED_BYTES)
+}
+
+/// Which ESP is being budgeted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EspTarget {
+    /// efiboot.img on the live ISO (`EFIBOOT_SIZE_MB`)
+    EfibootImage,
+    /// ESP of an installed system (`EFI_PARTITION_SIZE_MB`)
+    Installed,
+}
+
+impl EspTarget {
+    /// Filesystem size in bytes.
+    pub fn capacity_bytes(&self) -> u64 {
+        let mb = match self {
+            EspTarget::EfibootImage => EFIBOOT_SIZE_MB,
+            EspTarget::Installed => EFI_PARTITION_SIZE_MB,
+        };
+        u64::from(mb) * 1024 * 1024
+    }
+
+    /// Human-readable name for messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EspTarget::EfibootImage => "efiboot.img",
+            EspTarget::Installed => "installed ESP",
+        }
+    }
+
+    /// Copies of the systemd-boot binary this target holds.
+    ///
+    /// `bootctl install` writes both `EFI/BOOT/BOOTX64.EFI` and
+    /// `EFI/systemd/systemd-bootx64.efi`; the ISO only has the fallback path.
+    fn bootloader_copies(&self) -> u64 {
+        match self {
+            EspTarget::EfibootImage => 1,
+            EspTarget::Installed => 2,
+        }
+    }
+}
+
+/// One line of the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EspItem {
+    /// What the space is for (e.g., "UKI levitateos.efi x3")
+    pub label: String,
+    /// Bytes allocated on FAT (cluster-rounded, multiplied by copies)
+    pub allocated: u64,
+}
+
+/// Space calculation for one ESP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EspBudget {
+    /// Target being budgeted
+    pub target: EspTarget,
+    /// Filesystem size in bytes
+    pub capacity: u64,
+    /// Headroom to keep free, in percent of capacity
+    pub headroom_percent: u64,
+    /// Kernel versions whose UKIs are kept side by side
+    pub retained_kernels: usize,
+    /// systemd-boot binary size
+    bootloader: u64,
+    /// UKIs for one kernel version
+    ukis: Vec<(String, u64)>,
+    /// Separate files stored once (microcode images, etc.)
+    extras: Vec<(String, u64)>,
+}
+
+impl EspBudget {
+    /// Budget for a target with default headroom.
+    ///
+    /// Installed ESPs keep `RETAINED_KERNELS` kernels; efiboot.img holds one.
+    pub fn new(target: EspTarget) -> Self {
+        Self {
+            target,
+            capacity: target.capacity_bytes(),
+            headroom_percent: ESP_HEADROOM_PERCENT,
+            retained_kernels: match target {
+                EspTarget::EfibootImage => 1,
+                EspTarget::Installed => RETAINED_KERNELS,
+            },
+            bootloader: 0,
+            ukis: Vec::new(),
+            extras: Vec::new(),
+        }
+    }
+
+    /// Budget for the live ISO's efiboot.img.
+    pub fn efiboot_image() -> Self {
+        Self::new(EspTarget::EfibootImage)
+    }
+
+    /// Budget for an installed system's ESP.
+    pub fn installed() -> Self {
+        Self::new(EspTarget::Installed)
+    }
+
+    /// Size of the systemd-boot binary.
+    pub fn with_bootloader(mut self, size: u64) -> Self {
+        self.bootloader = size;
+        self
+    }
+
+    /// Add a UKI built for each retained kernel.
+    pub fn with_uki(mut self, name: impl Into<String>, size: u64) -> Self {
+        self.ukis.push((name.into(), size));
+        self
+    }
+
+    /// Add a file stored once (e.g., a microcode image).
+    pub fn with_file(mut self, name: impl Into<String>, size: u64) -> Self {
+        self.extras.push((name.into(), size));
+        self
+    }
+
+    /// Override the number of kernels kept side by side.
+    pub fn with_retained_kernels(mut self, count: usize) -> Self {
+        self.retained_kernels = count;
+        self
+    }
+
+    /// Override the headroom percentage.
+    pub fn with_headroom_percent(mut self, percent: u64) -> Self {
+        self.headroom_percent = percent;
+        self
+    }
+
+    /// Add a UKI using the size of a file on disk.
+    pub fn with_uki_file(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let (name, size) = stat(path.as_ref())?;
+        Ok(self.with_uki(name, size))
+    }
+
+    /// Add a stored-once file using its size on disk.
+    pub fn with_file_at(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let (name, size) = stat(path.as_ref())?;
+        Ok(self.with_file(name, size))
+    }
+
+    /// Set the bootloader size from the file on disk (e.g., `SYSTEMD_BOOT_EFI`).
+    pub fn with_bootloader_file(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let (_, size) = stat(path.as_ref())?;
+        Ok(self.with_bootloader(size))
+    }
+
+    /// Itemized allocation.
+    pub fn items(&self) -> Vec<EspItem> {
+        let mut items = Vec::new();
+        if self.bootloader > 0 {
+            let copies = self.target.bootloader_copies();
+            items.push(EspItem {
+                label: format!("systemd-boot x{}", copies),
+                allocated: fat_allocated_size(self.bootloader) * copies,
+            });
+        }
+        for (name, size) in &self.ukis {
+            items.push(EspItem {
+                label: format!("UKI {} x{}", name, self.retained_kernels),
+                allocated: fat_allocated_size(*size) * self.retained_kernels as u64,
+            });
+        }
+        for (name, size) in &self.extras {
+            items.push(EspItem {
+                label: name.clone(),
+                allocated: fat_allocated_size(*size),
+            });
+        }
+        items.push(EspItem {
+            label: "loader configuration".to_string(),
+            allocated: LOADER_FILES_BYTES,
+        });
+        items
+    }
+
+    /// Work out whether everything fits.
+    pub fn check(&self) -> Result<EspUsage, EspBudgetError> {
+        let items = self.items();
+        let used: u64 = items.iter().map(|i| i.allocated).sum();
+        let usable = fat_usable_bytes(self.capacity);
+        let headroom = self.capacity * self.headroom_percent / 100;
+        let usage = EspUsage {
+            target: self.target,
+            capacity: self.capacity,
+            usable,
+            used,
+            headroom,
+            items,
+        };
+        if used + headroom > usable {
+            Err(EspBudgetError(usage))
+        } else {
+            Ok(usage)
+        }
+    }
+}
+
+fn stat(path: &Path) -> io::Result<(String, u64)> {
+    let size = fs::metadata(path)?.len();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    Ok((name, size))
+}
+
+fn mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Result of a budget check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EspUsage {
+    /// Target that was checked
+    pub target: EspTarget,
+    /// Filesystem size
+    pub capacity: u64,
+    /// Space available for files after FAT overhead
+    pub usable: u64,
+    /// Space the files take
+    pub used: u64,
+    /// Space that must stay free
+    pub headroom: u64,
+    /// Breakdown
+    pub items: Vec<EspItem>,
+}
+
+impl EspUsage {
+    /// Free space left after headroom (negative when over budget).
+    pub fn spare(&self) -> i64 {
+        self.usable as i64 - self.used as i64 - self.headroom as i64
+    }
+}
+
+impl fmt::Display for EspUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} used + {} headroom of {} usable ({} filesystem)",
+            self.target.name(),
+            mib(self.used),
+            mib(self.headroom),
+            mib(self.usable),
+            mib(self.capacity)
+        )?;
+        for item in &self.items {
+            writeln!(f, "  {:>10}  {}", mib(item.allocated), item.label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Files do not fit on the ESP with the required headroom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EspBudgetError(pub EspUsage);
+
+impl fmt::Display for EspBudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let u = &self.0;
+        write!(
+            f,
+            "{} is too small: files need {} plus {} headroom, but only {} is usable; \
+             over by {}\n{}",
+            u.target.name(),
+            mib(u.used),
+            mib(u.headroom),
+            mib(u.usable),
+            mib((-u.spare()) as u64),
+            u
+        )
+    }
+}
+
+impl std::error::Error for EspBudgetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_fat_rounding() {
+        assert_eq!(fat_allocated_size(1), 4096);
+        assert_eq!(fat_allocated_size(4096), 4096);
+        assert_eq!(fat_allocated_size(4097), 8192);
+        assert!(fat_usable_bytes(200 * MIB) < 200 * MIB);
+    }
+
+    #[test]
+    fn test_efiboot_fits_three_live_ukis() {
+        let usage = EspBudget::efiboot_image()
+            .with_bootloader(120 * 1024)
+            .with_uki("levitateos-live.efi", 50 * MIB)
+            .with_uki("levitateos-emergency.efi", 50 * MIB)
+            .with_uki("levitateos-debug.efi", 50 * MIB)
+            .check()
+            .unwrap();
+        assert!(usage.spare() > 0);
+        assert_eq!(usage.items.len(), 5);
+    }
+
+    #[test]
+    fn test_efiboot_overflow_is_reported() {
+        let err = EspBudget::efiboot_image()
+            .with_uki("a.efi", 80 * MIB)
+            .with_uki("b.efi", 80 * MIB)
+            .with_uki("c.efi", 80 * MIB)
+            .check()
+            .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.starts_with("efiboot.img is too small"), "{}", msg);
+        assert!(msg.contains("UKI a.efi x1"));
+    }
+
+    #[test]
+    fn test_installed_esp_counts_retained_kernels() {
+        let budget = EspBudget::installed()
+            .with_bootloader(120 * 1024)
+            .with_uki("levitateos.efi", 150 * MIB)
+            .with_uki("recovery-levitateos.efi", 150 * MIB)
+            .with_file("amd-ucode.img", 100 * 1024);
+        let usage = budget.clone().check().unwrap();
+        assert!(usage.used > 900 * MIB);
+
+        // A fourth kernel no longer fits with 10% headroom
+        assert!(budget.with_retained_kernels(4).check().is_err());
+    }
+}
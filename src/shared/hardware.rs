@@ -0,0 +1,666 @@
+//! Hardware probe against `SystemRequirements`.
+//!
+//! Reads procfs and sysfs to find out what the machine actually has, then
+//! grades it against a variant's requirements:
+//!
+//! - `/proc/meminfo` - total RAM
+//! - `/proc/cpuinfo` - CPU vendor and flags (x86-64 microarchitecture level,
+//!   graded only on x86_64)
+//! - `/sys/block/*/size` - disk sizes
+//! - `/sys/bus/pci/devices/*/{class,vendor}` - GPU vendors
+//! - `/sys/firmware/efi` - UEFI boot
+//!
+//! The filesystem root is injectable (`HardwareProbe::with_root`) so tests can
+//! point the probe at a fixture tree instead of the running system.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::requirements::{SystemRequirements, BYTES_PER_GB};
+use crate::arch::Arch;
+
+// =============================================================================
+// x86-64 Microarchitecture Levels
+// =============================================================================
+
+/// /proc/cpuinfo flags required for x86-64-v1 (baseline).
+pub const X86_64_V1_FLAGS: &[&str] = &[
+    "lm", "cmov", "cx8", "fpu", "fxsr", "mmx", "syscall", "sse", "sse2",
+];
+
+/// Additional flags required for x86-64-v2 (Nehalem+ / Bulldozer+).
+///
+/// `pni` is how /proc/cpuinfo spells SSE3.
+pub const X86_64_V2_FLAGS: &[&str] = &[
+    "cx16", "lahf_lm", "popcnt", "pni", "sse4_1", "sse4_2", "ssse3",
+];
+
+/// Additional flags required for x86-64-v3 (Haswell+ / Zen+).
+///
+/// `abm` is how /proc/cpuinfo spells LZCNT.
+pub const X86_64_V3_FLAGS: &[&str] = &[
+    "avx", "avx2", "bmi1", "bmi2", "f16c", "fma", "abm", "movbe", "xsave",
+];
+
+/// Additional flags required for x86-64-v4 (AVX-512).
+pub const X86_64_V4_FLAGS: &[&str] = &["avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl"];
+
+/// Highest x86-64 microarchitecture level (1-4) supported by a set of CPU flags.
+///
+/// Returns 0 if even the baseline is not met (not an x86-64 CPU).
+pub fn x86_64_level(flags: &[String]) -> u8 {
+    let has_all = |required: &[&str]| required.iter().all(|f| flags.iter().any(|x| x == f));
+    let levels = [
+        X86_64_V1_FLAGS,
+        X86_64_V2_FLAGS,
+        X86_64_V3_FLAGS,
+        X86_64_V4_FLAGS,
+    ];
+    levels.iter().take_while(|l| has_all(l)).count() as u8
+}
+
+/// Parse a microarchitecture name like "x86-64-v3" into its level.
+pub fn parse_microarch_level(name: &str) -> Option<u8> {
+    match name {
+        "x86-64" => Some(1),
+        _ => name.strip_prefix("x86-64-v")?.parse().ok(),
+    }
+}
+
+/// Flags missing for a given microarchitecture level.
+pub fn missing_flags(flags: &[String], level: u8) -> Vec<&'static str> {
+    [
+        X86_64_V1_FLAGS,
+        X86_64_V2_FLAGS,
+        X86_64_V3_FLAGS,
+        X86_64_V4_FLAGS,
+    ]
+    .iter()
+    .take(level as usize)
+    .flat_map(|l| l.iter())
+    .filter(|f| !flags.iter().any(|x| x == *f))
+    .copied()
+    .collect()
+}
+
+// =============================================================================
+// Vendor IDs
+// =============================================================================
+
+/// CPU vendor_id strings from /proc/cpuinfo → vendor name used in requirements.
+pub const CPU_VENDOR_IDS: &[(&str, &str)] = &[("AuthenticAMD", "AMD"), ("GenuineIntel", "Intel")];
+
+/// PCI vendor IDs → vendor name used in requirements.
+pub const GPU_VENDOR_IDS: &[(u16, &str)] = &[
+    (0x1002, "AMD"),
+    (0x10de, "NVIDIA"),
+    (0x8086, "Intel"),
+    (0x1af4, "virtio"),
+    (0x1234, "QEMU"),
+    (0x15ad, "VMware"),
+];
+
+/// PCI class code prefix for display controllers (VGA, 3D, other display).
+pub const PCI_CLASS_DISPLAY: u32 = 0x03;
+
+/// Block device name prefixes that are never install targets.
+pub const VIRTUAL_BLOCK_PREFIXES: &[&str] =
+    &["loop", "ram", "zram", "sr", "dm-", "md", "nbd", "fd"];
+
+// =============================================================================
+// Probe
+// =============================================================================
+
+/// A disk found under /sys/block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbedDisk {
+    /// Kernel name (e.g., "nvme0n1")
+    pub name: String,
+    /// Size in bytes
+    pub size_bytes: u64,
+    /// Whether the kernel reports the device as removable
+    pub removable: bool,
+}
+
+/// Raw hardware facts read from procfs/sysfs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HardwareInfo {
+    /// CPU architecture, `None` if it is not one the spec supports
+    pub arch: Option<Arch>,
+    /// MemTotal from /proc/meminfo in bytes
+    pub mem_total_bytes: Option<u64>,
+    /// CPU vendor name (e.g., "AMD"), or the raw vendor_id if unknown
+    pub cpu_vendor: Option<String>,
+    /// CPU model name
+    pub cpu_model: Option<String>,
+    /// CPU flags of the first processor
+    pub cpu_flags: Vec<String>,
+    /// Disks, excluding loop/ram/optical devices
+    pub disks: Vec<ProbedDisk>,
+    /// Display controller vendors (one entry per device)
+    pub gpu_vendors: Vec<String>,
+    /// Whether the system booted via UEFI
+    pub uefi: bool,
+}
+
+/// Reads hardware facts from a procfs/sysfs root.
+#[derive(Debug, Clone)]
+pub struct HardwareProbe {
+    root: PathBuf,
+    arch: Option<Arch>,
+}
+
+impl Default for HardwareProbe {
+    fn default() -> Self {
+        Self::with_root("/")
+    }
+}
+
+impl HardwareProbe {
+    /// Probe the running system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Probe a fixture tree containing `proc/` and `sys/`.
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            arch: Arch::from_name(std::env::consts::ARCH),
+        }
+    }
+
+    /// Report `arch` instead of the architecture this code runs on.
+    pub fn with_arch(mut self, arch: Arch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    fn path(&self, rel: &str) -> PathBuf {
+        self.root.join(rel)
+    }
+
+    /// Collect all hardware facts.
+    ///
+    /// Missing files are not errors - the corresponding field is left empty
+    /// and the report marks it as unknown.
+    pub fn probe(&self) -> HardwareInfo {
+        let (cpu_vendor, cpu_model, cpu_flags) = self.cpu().unwrap_or_default();
+        HardwareInfo {
+            arch: self.arch,
+            mem_total_bytes: self.mem_total().ok().flatten(),
+            cpu_vendor,
+            cpu_model,
+            cpu_flags,
+            disks: self.disks().unwrap_or_default(),
+            gpu_vendors: self.gpu_vendors().unwrap_or_default(),
+            uefi: self.path("sys/firmware/efi").is_dir(),
+        }
+    }
+
+    fn mem_total(&self) -> io::Result<Option<u64>> {
+        let meminfo = fs::read_to_string(self.path("proc/meminfo"))?;
+        Ok(meminfo.lines().find_map(|line| {
+            let rest = line.strip_prefix("MemTotal:")?;
+            let kb: u64 = rest.trim().trim_end_matches("kB").trim().parse().ok()?;
+            Some(kb * 1024)
+        }))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn cpu(&self) -> io::Result<(Option<String>, Option<String>, Vec<String>)> {
+        let cpuinfo = fs::read_to_string(self.path("proc/cpuinfo"))?;
+        // Only the first processor block; all cores report the same flags
+        let first = cpuinfo.split("\n\n").next().unwrap_or_default();
+        let field = |name: &str| {
+            first.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == name).then(|| value.trim().to_string())
+            })
+        };
+        let vendor = field("vendor_id").map(|id| {
+            CPU_VENDOR_IDS
+                .iter()
+                .find(|(raw, _)| *raw == id)
+                .map_or(id, |(_, name)| name.to_string())
+        });
+        let flags = field("flags")
+            .map(|f| f.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+        Ok((vendor, field("model name"), flags))
+    }
+
+    fn disks(&self) -> io::Result<Vec<ProbedDisk>> {
+        let mut disks = Vec::new();
+        for entry in fs::read_dir(self.path("sys/block"))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if VIRTUAL_BLOCK_PREFIXES.iter().any(|p| name.starts_with(p)) {
+                continue;
+            }
+            let dir = entry.path();
+            let Some(sectors) = read_trimmed(&dir.join("size")).and_then(|s| s.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let removable = read_trimmed(&dir.join("removable")).as_deref() == Some("1");
+            disks.push(ProbedDisk {
+                name,
+                // sysfs always reports size in 512-byte sectors
+                size_bytes: sectors * 512,
+                removable,
+            });
+        }
+        disks.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(disks)
+    }
+
+    fn gpu_vendors(&self) -> io::Result<Vec<String>> {
+        let mut vendors = Vec::new();
+        let mut devices: Vec<_> = fs::read_dir(self.path("sys/bus/pci/devices"))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect();
+        devices.sort();
+        for dev in devices {
+            let class = read_hex(&dev.join("class"));
+            if class.map(|c| c >> 16) != Some(PCI_CLASS_DISPLAY) {
+                continue;
+            }
+            let Some(vendor) = read_hex(&dev.join("vendor")) else {
+                continue;
+            };
+            let name = GPU_VENDOR_IDS
+                .iter()
+                .find(|(id, _)| u32::from(*id) == vendor)
+                .map_or_else(|| format!("0x{:04x}", vendor), |(_, n)| n.to_string());
+            vendors.push(name);
+        }
+        Ok(vendors)
+    }
+}
+
+/// Contents of a sysfs/procfs attribute without surrounding whitespace.
+pub(crate) fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_hex(path: &Path) -> Option<u32> {
+    let s = read_trimmed(path)?;
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+// =============================================================================
+// Report
+// =============================================================================
+
+/// Outcome of a single requirement check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    /// Meets the recommended value
+    Pass,
+    /// Meets the minimum but not the recommendation, or could not be determined
+    Warn,
+    /// Below the minimum - installation should not proceed
+    Fail,
+}
+
+/// One line of a hardware report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardwareCheck {
+    /// What was checked ("ram", "disk", "cpu", "cpu-vendor", "gpu", "uefi")
+    pub name: &'static str,
+    /// Result
+    pub status: CheckStatus,
+    /// Human-readable explanation
+    pub detail: String,
+}
+
+/// Hardware graded against a `SystemRequirements`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardwareReport {
+    pub checks: Vec<HardwareCheck>,
+}
+
+impl HardwareReport {
+    /// Worst status across all checks.
+    pub fn overall(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+
+    /// Find a check by name.
+    pub fn check(&self, name: &str) -> Option<&HardwareCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    /// Checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &HardwareCheck> {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Fail)
+    }
+}
+
+impl std::fmt::Display for HardwareReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Warn => "WARN",
+                CheckStatus::Fail => "FAIL",
+            };
+            writeln!(f, "[{}] {:<10} {}", status, check.name, check.detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl HardwareInfo {
+    /// RAM rounded to the nearest GiB.
+    ///
+    /// MemTotal excludes memory reserved by firmware and the kernel, so an
+    /// 8 GiB machine reports ~7.6 GiB. Rounding keeps it from failing an 8 GB floor.
+    pub fn ram_gb(&self) -> Option<u64> {
+        self.mem_total_bytes
+            .map(|b| (b + 512 * 1024 * 1024) / (1024 * 1024 * 1024))
+    }
+
+    /// Largest non-removable disk, falling back to the largest removable one.
+    pub fn largest_disk(&self) -> Option<&ProbedDisk> {
+        let fixed = self
+            .disks
+            .iter()
+            .filter(|d| !d.removable)
+            .max_by_key(|d| d.size_bytes);
+        fixed.or_else(|| self.disks.iter().max_by_key(|d| d.size_bytes))
+    }
+
+    /// Grade this hardware against a set of requirements.
+    pub fn check(&self, req: &SystemRequirements) -> HardwareReport {
+        let mut checks = Vec::new();
+        let mut push = |name, status, detail: String| {
+            checks.push(HardwareCheck {
+                name,
+                status,
+                detail,
+            })
+        };
+
+        // RAM
+        match self.ram_gb() {
+            None => push(
+                "ram",
+                CheckStatus::Warn,
+                "could not read MemTotal from /proc/meminfo".into(),
+            ),
+            Some(gb) => push(
+                "ram",
+                grade(gb, req.min_ram_gb, req.recommended_ram_gb),
+                format!(
+                    "{} GB (minimum {}, recommended {})",
+                    gb, req.min_ram_gb, req.recommended_ram_gb
+                ),
+            ),
+        }
+
+        // Disk
+        match self.largest_disk() {
+            None => push(
+                "disk",
+                CheckStatus::Fail,
+                "no disks found in /sys/block".into(),
+            ),
+            Some(disk) => {
+                let gb = disk.size_bytes / BYTES_PER_GB;
+                push(
+                    "disk",
+                    grade(gb, req.min_disk_gb, req.recommended_disk_gb),
+                    format!(
+                        "{} is {} GB (minimum {}, recommended {})",
+                        disk.name, gb, req.min_disk_gb, req.recommended_disk_gb
+                    ),
+                );
+            }
+        }
+
+        // CPU microarchitecture; x86-64 levels mean nothing elsewhere
+        let required = parse_microarch_level(req.cpu_microarch).unwrap_or(1);
+        if let Some(arch) = self.arch.filter(|&a| a != Arch::X86_64) {
+            push(
+                "cpu",
+                CheckStatus::Pass,
+                format!("{} does not apply to {}", req.cpu_microarch, arch),
+            );
+        } else if self.cpu_flags.is_empty() {
+            push(
+                "cpu",
+                CheckStatus::Warn,
+                "could not read CPU flags from /proc/cpuinfo".into(),
+            );
+        } else {
+            let level = x86_64_level(&self.cpu_flags);
+            if level >= required {
+                push(
+                    "cpu",
+                    CheckStatus::Pass,
+                    format!("x86-64-v{} (requires {})", level, req.cpu_microarch),
+                );
+            } else {
+                push(
+                    "cpu",
+                    CheckStatus::Fail,
+                    format!(
+                        "x86-64-v{} but {} is required (missing: {})",
+                        level,
+                        req.cpu_microarch,
+                        missing_flags(&self.cpu_flags, required).join(" ")
+                    ),
+                );
+            }
+        }
+
+        // CPU vendor
+        match self.cpu_vendor {
+            None => push(
+                "cpu-vendor",
+                CheckStatus::Warn,
+                "could not read vendor_id".into(),
+            ),
+            Some(ref v) if req.supported_vendors.contains(&v.as_str()) => {
+                push("cpu-vendor", CheckStatus::Pass, v.clone())
+            }
+            Some(ref v) => push(
+                "cpu-vendor",
+                CheckStatus::Warn,
+                format!(
+                    "{} is not a supported vendor ({})",
+                    v,
+                    req.supported_vendors.join(", ")
+                ),
+            ),
+        }
+
+        // GPU
+        let supported: Vec<&str> = self
+            .gpu_vendors
+            .iter()
+            .map(String::as_str)
+            .filter(|v| req.gpu_vendors.contains(v))
+            .collect();
+        if !supported.is_empty() {
+            push("gpu", CheckStatus::Pass, supported.join(", "));
+        } else if self.gpu_vendors.is_empty() {
+            push(
+                "gpu",
+                CheckStatus::Warn,
+                "no display controller found".into(),
+            );
+        } else {
+            push(
+                "gpu",
+                CheckStatus::Warn,
+                format!(
+                    "{} (supported: {})",
+                    self.gpu_vendors.join(", "),
+                    req.gpu_vendors.join(", ")
+                ),
+            );
+        }
+
+        // UEFI (both variants are UEFI-only)
+        if self.uefi {
+            push("uefi", CheckStatus::Pass, "booted via UEFI".into());
+        } else {
+            push(
+                "uefi",
+                CheckStatus::Fail,
+                "/sys/firmware/efi missing - legacy BIOS boot is not supported".into(),
+            );
+        }
+
+        HardwareReport { checks }
+    }
+}
+
+fn grade(value: u64, min: u32, recommended: u32) -> CheckStatus {
+    if value < u64::from(min) {
+        CheckStatus::Fail
+    } else if value < u64::from(recommended) {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::requirements::{ACORN_REQUIREMENTS, LEVITATE_REQUIREMENTS};
+    use crate::shared::test_util::TempDir;
+
+    const ZEN3_FLAGS: &str =
+        "fpu vme de pse tsc msr pae mce cx8 apic sep mtrr pge mca cmov pat pse36 \
+        clflush mmx fxsr sse sse2 ht syscall nx mmxext fxsr_opt pdpe1gb rdtscp lm constant_tsc \
+        rep_good nopl nonstop_tsc cpuid extd_apicid aperfmperf pni pclmulqdq monitor ssse3 fma \
+        cx16 sse4_1 sse4_2 movbe popcnt aes xsave avx f16c rdrand lahf_lm cmp_legacy svm \
+        extapic cr8_legacy abm sse4a misalignsse 3dnowprefetch osvw bmi1 avx2 smep bmi2 erms";
+
+    fn fixture(mem_kb: u64, flags: &str, uefi: bool) -> TempDir {
+        let dir = TempDir::new("hw");
+        dir.write(
+            "proc/meminfo",
+            format!("MemTotal:       {} kB\nMemFree:         1000 kB\n", mem_kb),
+        );
+        dir.write(
+            "proc/cpuinfo",
+            format!(
+                "processor\t: 0\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD Ryzen 7 5800X\nflags\t\t: {}\n\nprocessor\t: 1\nvendor_id\t: AuthenticAMD\n",
+                flags
+            ),
+        );
+        // 500 GB NVMe, 32 GB removable USB stick, loop device
+        dir.write("sys/block/nvme0n1/size", "976562500\n");
+        dir.write("sys/block/nvme0n1/removable", "0\n");
+        dir.write("sys/block/sda/size", "62500000\n");
+        dir.write("sys/block/sda/removable", "1\n");
+        dir.write("sys/block/loop0/size", "1000\n");
+        dir.write("sys/bus/pci/devices/0000:0a:00.0/class", "0x030000\n");
+        dir.write("sys/bus/pci/devices/0000:0a:00.0/vendor", "0x1002\n");
+        dir.write("sys/bus/pci/devices/0000:00:14.0/class", "0x0c0330\n");
+        dir.write("sys/bus/pci/devices/0000:00:14.0/vendor", "0x8086\n");
+        if uefi {
+            dir.mkdir("sys/firmware/efi/efivars");
+        }
+        dir
+    }
+
+    fn flags(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_microarch_levels() {
+        assert_eq!(x86_64_level(&flags(ZEN3_FLAGS)), 3);
+        let v2_only = ZEN3_FLAGS.replace(" avx2", "");
+        assert_eq!(x86_64_level(&flags(&v2_only)), 2);
+        assert_eq!(missing_flags(&flags(&v2_only), 3), ["avx2"]);
+        assert_eq!(x86_64_level(&flags("fpu vme")), 0);
+        assert_eq!(parse_microarch_level("x86-64-v3"), Some(3));
+        assert_eq!(parse_microarch_level("x86-64"), Some(1));
+        assert_eq!(parse_microarch_level("armv8"), None);
+    }
+
+    #[test]
+    fn test_probe_fixture() {
+        let dir = fixture(16_314_000, ZEN3_FLAGS, true);
+        let info = HardwareProbe::with_root(dir.path())
+            .with_arch(Arch::X86_64)
+            .probe();
+        assert_eq!(info.arch, Some(Arch::X86_64));
+        assert_eq!(info.ram_gb(), Some(16));
+        assert_eq!(info.cpu_vendor.as_deref(), Some("AMD"));
+        assert_eq!(info.cpu_model.as_deref(), Some("AMD Ryzen 7 5800X"));
+        assert_eq!(info.disks.len(), 2);
+        assert_eq!(info.largest_disk().unwrap().name, "nvme0n1");
+        assert_eq!(info.gpu_vendors, ["AMD"]);
+        assert!(info.uefi);
+
+        let report = info.check(&LEVITATE_REQUIREMENTS);
+        assert_eq!(report.overall(), CheckStatus::Pass, "{}", report);
+    }
+
+    #[test]
+    fn test_report_grades() {
+        // 6 GB RAM, no AVX2, legacy BIOS
+        let dir = fixture(6_000_000, &ZEN3_FLAGS.replace(" avx2", ""), false);
+        let info = HardwareProbe::with_root(dir.path())
+            .with_arch(Arch::X86_64)
+            .probe();
+
+        let report = info.check(&LEVITATE_REQUIREMENTS);
+        assert_eq!(report.check("ram").unwrap().status, CheckStatus::Fail);
+        assert_eq!(report.check("cpu").unwrap().status, CheckStatus::Fail);
+        assert!(report
+            .check("cpu")
+            .unwrap()
+            .detail
+            .contains("missing: avx2"));
+        assert_eq!(report.check("uefi").unwrap().status, CheckStatus::Fail);
+        assert_eq!(report.failures().count(), 3);
+
+        // Same RAM is above AcornOS's minimum but below its recommendation
+        let acorn = info.check(&ACORN_REQUIREMENTS);
+        assert_eq!(acorn.check("ram").unwrap().status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_microarch_only_on_x86_64() {
+        // aarch64 cpuinfo has "Features", not x86 flags
+        let dir = fixture(
+            16_314_000,
+            "fp asimd evtstrm aes pmull sha1 sha2 crc32",
+            true,
+        );
+        let info = HardwareProbe::with_root(dir.path())
+            .with_arch(Arch::Aarch64)
+            .probe();
+        let report = info.check(&LEVITATE_REQUIREMENTS);
+        let cpu = report.check("cpu").unwrap();
+        assert_eq!(cpu.status, CheckStatus::Pass);
+        assert_eq!(cpu.detail, "x86-64-v3 does not apply to aarch64");
+    }
+
+    #[test]
+    fn test_missing_procfs_is_warning() {
+        let dir = TempDir::new("hw-empty");
+        let info = HardwareProbe::with_root(dir.path())
+            .with_arch(Arch::X86_64)
+            .probe();
+        let report = info.check(&LEVITATE_REQUIREMENTS);
+        assert_eq!(report.check("ram").unwrap().status, CheckStatus::Warn);
+        assert_eq!(report.check("cpu").unwrap().status, CheckStatus::Warn);
+        assert_eq!(report.check("disk").unwrap().status, CheckStatus::Fail);
+    }
+}
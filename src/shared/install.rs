@@ -0,0 +1,1201 @@
+//! Installation plan - the ordered list of steps that turns a disk into an installed system.
+//!
+//! Installers used to stitch together `PartitionLayout::to_sfdisk_script`,
+//! `CHROOT_BIND_MOUNTS`, `bootctl_install_command`, `useradd_command` and the
+//! service enable commands themselves. `InstallPlan` composes them once, in one
+//! order, so every consumer installs the same way.
+//!
+//! # Step Order
+//!
+//! ```text
+//! partition → format → mount → extract → fstab → configure
+//!           → chroot-setup → users → services → bootloader
+//!           → boot-assessment → unmount
+//! ```
+//!
+//! Each step declares the steps it depends on (`StepKind::depends_on`), and the
+//! plan is always emitted in an order that satisfies them.
+//!
+//! # Rendering
+//!
+//! - `to_shell_script()` - dry-run shell script for review
+//! - `to_json()` - machine-readable plan for other tools
+//! - `steps()` - typed `StepAction`s for an executor
+
+use super::boot::{bootctl_install_command, LOADER_CONF_PATH};
+use super::boot_counting::{counted_default_pattern, DEFAULT_BOOT_TRIES};
+use super::chroot::{mounts_in_order, mounts_in_unmount_order};
+use super::command::{render_argv, shell_quote};
+use super::partitions::{PartitionLayout, LUKS_MAPPER_NAME};
+use super::profile::Profile;
+use super::users::{validate_username, UserSpec, ROOT_HOME};
+use crate::variant::{InitSystem, Variant};
+
+/// Default mount point for the installation target.
+pub const DEFAULT_INSTALL_TARGET: &str = "/mnt";
+
+/// Heredoc delimiter used in rendered shell scripts.
+const HEREDOC_DELIMITER: &str = "DISTRO_SPEC_EOF";
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// User choices that an install plan is built from.
+#[derive(Debug, Clone)]
+pub struct InstallConfig {
+    /// Distro variant being installed
+    pub variant: Variant,
+    /// Install profile, selecting the services enabled by default
+    pub profile: Profile,
+    /// Whole-disk device to install to (e.g., "/dev/vda")
+    pub disk: String,
+    /// Mount point for the target root filesystem
+    pub target: String,
+    /// Partition layout to create on `disk`
+    pub layout: PartitionLayout,
+    /// Regular users to create
+    pub users: Vec<UserSpec>,
+    /// SSH public keys to install, as (username, keys)
+    pub authorized_keys: Vec<(String, Vec<String>)>,
+    /// Hostname written to /etc/hostname
+    pub hostname: String,
+    /// Locale (e.g., "en_US.UTF-8"), left unset if `None`
+    pub locale: Option<String>,
+    /// Timezone name under /usr/share/zoneinfo (e.g., "Europe/Amsterdam")
+    pub timezone: Option<String>,
+    /// Console keymap (e.g., "us")
+    pub keymap: Option<String>,
+    /// LUKS encryption of the root partition
+    pub encryption: Option<RootEncryption>,
+    /// Services to enable in addition to the variant defaults
+    pub extra_services: Vec<String>,
+}
+
+/// LUKS2 encryption settings for the root partition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RootEncryption {
+    /// Key file on the live system (prompts for a passphrase if `None`)
+    pub key_file: Option<String>,
+    /// LUKS header UUID given to `luksFormat`, so crypttab and the kernel
+    /// command line can name the volume; random if `None`
+    pub uuid: Option<String>,
+}
+
+impl InstallConfig {
+    /// Create a config with the default layout and target for a variant.
+    pub fn new(variant: Variant, disk: impl Into<String>) -> Self {
+        Self {
+            variant,
+            profile: Profile::default(),
+            disk: disk.into(),
+            target: DEFAULT_INSTALL_TARGET.to_string(),
+            layout: PartitionLayout::default(),
+            users: Vec::new(),
+            authorized_keys: Vec::new(),
+            hostname: variant.default_hostname().to_string(),
+            locale: None,
+            timezone: None,
+            keymap: None,
+            encryption: None,
+            extra_services: Vec::new(),
+        }
+    }
+
+    /// Set the install profile.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Set the target mount point.
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Set the partition layout.
+    pub fn with_layout(mut self, layout: PartitionLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Add a user to create.
+    pub fn with_user(mut self, user: UserSpec) -> Self {
+        self.users.push(user);
+        self
+    }
+
+    /// Install SSH public keys for a user.
+    pub fn with_authorized_keys(mut self, username: impl Into<String>, keys: Vec<String>) -> Self {
+        self.authorized_keys.push((username.into(), keys));
+        self
+    }
+
+    /// Set the hostname.
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /// Set the locale.
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Set the timezone.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Set the console keymap.
+    pub fn with_keymap(mut self, keymap: impl Into<String>) -> Self {
+        self.keymap = Some(keymap.into());
+        self
+    }
+
+    /// Encrypt the root partition with LUKS2.
+    pub fn with_encryption(mut self, encryption: RootEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Enable an additional service.
+    pub fn with_service(mut self, name: impl Into<String>) -> Self {
+        self.extra_services.push(name.into());
+        self
+    }
+
+    /// Check the user-supplied account names before they reach a shell.
+    ///
+    /// Returns a human-readable reason on failure.
+    pub fn validate(&self) -> Result<(), String> {
+        for user in &self.users {
+            user.validate()
+                .map_err(|e| format!("user '{}': {}", user.username, e))?;
+        }
+        for (username, _) in &self.authorized_keys {
+            if username != "root" {
+                validate_username(username).map_err(|e| format!("authorized keys: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of `path` (absolute, on the installed system) under the target.
+    fn target_path(&self, path: &str) -> String {
+        format!("{}{}", self.target.trim_end_matches('/'), path)
+    }
+}
+
+// =============================================================================
+// Steps
+// =============================================================================
+
+/// Kind of installation step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepKind {
+    Partition,
+    Format,
+    Mount,
+    Extract,
+    Fstab,
+    Configure,
+    ChrootSetup,
+    Users,
+    Services,
+    Bootloader,
+    BootAssessment,
+    Unmount,
+}
+
+impl StepKind {
+    /// All step kinds in execution order.
+    pub const ORDER: &'static [StepKind] = &[
+        StepKind::Partition,
+        StepKind::Format,
+        StepKind::Mount,
+        StepKind::Extract,
+        StepKind::Fstab,
+        StepKind::Configure,
+        StepKind::ChrootSetup,
+        StepKind::Users,
+        StepKind::Services,
+        StepKind::Bootloader,
+        StepKind::BootAssessment,
+        StepKind::Unmount,
+    ];
+
+    /// Stable lowercase name (used in JSON and script comments).
+    pub fn name(&self) -> &'static str {
+        match self {
+            StepKind::Partition => "partition",
+            StepKind::Format => "format",
+            StepKind::Mount => "mount",
+            StepKind::Extract => "extract",
+            StepKind::Fstab => "fstab",
+            StepKind::Configure => "configure",
+            StepKind::ChrootSetup => "chroot-setup",
+            StepKind::Users => "users",
+            StepKind::Services => "services",
+            StepKind::Bootloader => "bootloader",
+            StepKind::BootAssessment => "boot-assessment",
+            StepKind::Unmount => "unmount",
+        }
+    }
+
+    /// Steps that must have completed before this one can run.
+    pub fn depends_on(&self) -> &'static [StepKind] {
+        match self {
+            StepKind::Partition => &[],
+            StepKind::Format => &[StepKind::Partition],
+            StepKind::Mount => &[StepKind::Format],
+            StepKind::Extract => &[StepKind::Mount],
+            StepKind::Fstab => &[StepKind::Extract],
+            StepKind::Configure => &[StepKind::Extract],
+            StepKind::ChrootSetup => &[StepKind::Extract],
+            StepKind::Users => &[StepKind::ChrootSetup],
+            StepKind::Services => &[StepKind::ChrootSetup],
+            StepKind::Bootloader => &[StepKind::ChrootSetup, StepKind::Fstab, StepKind::Configure],
+            StepKind::BootAssessment => &[StepKind::Bootloader],
+            // Everything that mounts or writes under the target must be done
+            StepKind::Unmount => &[
+                StepKind::Mount,
+                StepKind::Extract,
+                StepKind::Fstab,
+                StepKind::Configure,
+                StepKind::ChrootSetup,
+                StepKind::Users,
+                StepKind::Services,
+                StepKind::Bootloader,
+                StepKind::BootAssessment,
+            ],
+        }
+    }
+}
+
+/// A single action within a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepAction {
+    /// Run a program on the live system, optionally feeding it stdin.
+    Run {
+        argv: Vec<String>,
+        stdin: Option<String>,
+    },
+    /// Run a program on the live system and append its stdout to a file.
+    Capture { argv: Vec<String>, path: String },
+    /// Run a shell command inside the target root.
+    Chroot { root: String, command: String },
+    /// Write a file (path includes the target prefix).
+    WriteFile { path: String, contents: String },
+}
+
+impl StepAction {
+    fn run<S: Into<String>>(argv: impl IntoIterator<Item = S>) -> Self {
+        StepAction::Run {
+            argv: argv.into_iter().map(Into::into).collect(),
+            stdin: None,
+        }
+    }
+
+    /// argv that performs this action, for executors that don't special-case variants.
+    ///
+    /// `Capture` and `WriteFile` need redirection and return `None`.
+    pub fn argv(&self) -> Option<Vec<String>> {
+        match self {
+            StepAction::Run { argv, .. } => Some(argv.clone()),
+            StepAction::Chroot { root, command } => Some(vec![
+                "chroot".to_string(),
+                root.clone(),
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                command.clone(),
+            ]),
+            StepAction::Capture { .. } | StepAction::WriteFile { .. } => None,
+        }
+    }
+
+    /// Render this action as shell.
+    pub fn to_shell(&self) -> String {
+        match self {
+            StepAction::Run { argv, stdin: None } => render_argv(argv),
+            StepAction::Run {
+                argv,
+                stdin: Some(input),
+            } => format!(
+                "{} <<'{}'\n{}{}",
+                render_argv(argv),
+                HEREDOC_DELIMITER,
+                with_trailing_newline(input),
+                HEREDOC_DELIMITER
+            ),
+            StepAction::Capture { argv, path } => {
+                format!("{} >> {}", render_argv(argv), shell_quote(path))
+            }
+            StepAction::Chroot { .. } => render_argv(&self.argv().unwrap_or_default()),
+            StepAction::WriteFile { path, contents } => format!(
+                "cat > {} <<'{}'\n{}{}",
+                shell_quote(path),
+                HEREDOC_DELIMITER,
+                with_trailing_newline(contents),
+                HEREDOC_DELIMITER
+            ),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            StepAction::Run { argv, stdin } => format!(
+                "{{\"type\":\"run\",\"argv\":{},\"stdin\":{}}}",
+                json_array(argv),
+                stdin.as_deref().map_or("null".to_string(), json_string)
+            ),
+            StepAction::Capture { argv, path } => format!(
+                "{{\"type\":\"capture\",\"argv\":{},\"path\":{}}}",
+                json_array(argv),
+                json_string(path)
+            ),
+            StepAction::Chroot { root, command } => format!(
+                "{{\"type\":\"chroot\",\"root\":{},\"command\":{}}}",
+                json_string(root),
+                json_string(command)
+            ),
+            StepAction::WriteFile { path, contents } => format!(
+                "{{\"type\":\"write-file\",\"path\":{},\"contents\":{}}}",
+                json_string(path),
+                json_string(contents)
+            ),
+        }
+    }
+}
+
+/// One typed step of an installation.
+#[derive(Debug, Clone)]
+pub struct InstallStep {
+    /// What this step does
+    pub kind: StepKind,
+    /// Human-readable summary
+    pub description: String,
+    /// Steps in this plan that must run first
+    pub depends_on: Vec<StepKind>,
+    /// Actions to perform, in order
+    pub actions: Vec<StepAction>,
+}
+
+// =============================================================================
+// Plan
+// =============================================================================
+
+/// Ordered, dependency-aware installation plan.
+#[derive(Debug, Clone)]
+pub struct InstallPlan {
+    config: InstallConfig,
+    steps: Vec<InstallStep>,
+}
+
+impl InstallPlan {
+    /// Build the plan for a configuration that passed `InstallConfig::validate`.
+    ///
+    /// Use `try_new` for configurations from outside the program.
+    pub fn try_new(config: InstallConfig) -> Result<Self, String> {
+        config.validate()?;
+        Ok(Self::new(config))
+    }
+
+    /// Build the plan for the given configuration.
+    ///
+    /// Values are shell-quoted, but names are not checked; see `try_new`.
+    pub fn new(mut config: InstallConfig) -> Self {
+        if let Some(ref mut enc) = config.encryption {
+            enc.uuid.get_or_insert_with(random_uuid);
+        }
+        let mut plan = Self {
+            config,
+            steps: Vec::new(),
+        };
+        for kind in StepKind::ORDER {
+            let (description, actions) = plan.build_step(*kind);
+            if !actions.is_empty() {
+                plan.push(*kind, description, actions);
+            }
+        }
+        plan
+    }
+
+    /// The configuration this plan was built from.
+    pub fn config(&self) -> &InstallConfig {
+        &self.config
+    }
+
+    /// Steps in execution order.
+    pub fn steps(&self) -> &[InstallStep] {
+        &self.steps
+    }
+
+    /// Find a step by kind.
+    pub fn step(&self, kind: StepKind) -> Option<&InstallStep> {
+        self.steps.iter().find(|s| s.kind == kind)
+    }
+
+    fn push(&mut self, kind: StepKind, description: String, actions: Vec<StepAction>) {
+        let depends_on = kind
+            .depends_on()
+            .iter()
+            .copied()
+            .filter(|dep| self.steps.iter().any(|s| s.kind == *dep))
+            .collect();
+        self.steps.push(InstallStep {
+            kind,
+            description,
+            depends_on,
+            actions,
+        });
+    }
+
+    fn build_step(&self, kind: StepKind) -> (String, Vec<StepAction>) {
+        let cfg = &self.config;
+        let layout = &cfg.layout;
+        let efi_dev = layout.efi.device_on(&cfg.disk);
+        let root_part = layout.root.device_on(&cfg.disk);
+        // With encryption the filesystem lives on the opened LUKS mapping
+        let root_dev = match cfg.encryption {
+            Some(_) => format!("/dev/mapper/{}", LUKS_MAPPER_NAME),
+            None => root_part.clone(),
+        };
+        let efi_mount = cfg.target_path(layout.efi.mount_point);
+        let luks_uuid = cfg.encryption.as_ref().and_then(|e| e.uuid.as_deref());
+
+        match kind {
+            StepKind::Partition => (
+                format!("Create GPT partition table on {}", cfg.disk),
+                vec![StepAction::Run {
+                    argv: vec!["sfdisk".to_string(), cfg.disk.clone()],
+                    stdin: Some(layout.to_sfdisk_script()),
+                }],
+            ),
+            StepKind::Format => {
+                let mut actions = vec![StepAction::run(layout.efi.mkfs_command(&efi_dev))];
+                if let Some(ref enc) = cfg.encryption {
+                    let key_args: Vec<String> = match enc.key_file {
+                        Some(ref key) => vec!["--key-file".to_string(), key.clone()],
+                        None => Vec::new(),
+                    };
+                    let mut format = vec![
+                        "cryptsetup",
+                        "luksFormat",
+                        "--type",
+                        "luks2",
+                        "--batch-mode",
+                    ]
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                    format.extend(key_args.iter().cloned());
+                    format.push("--uuid".to_string());
+                    format.push(luks_uuid.unwrap_or_default().to_string());
+                    format.push(root_part.clone());
+                    actions.push(StepAction::run(format));
+
+                    let mut open = vec!["cryptsetup".to_string(), "open".to_string()];
+                    open.extend(key_args);
+                    open.push(root_part.clone());
+                    open.push(LUKS_MAPPER_NAME.to_string());
+                    actions.push(StepAction::run(open));
+                }
+                actions.push(StepAction::run(layout.root.mkfs_command(&root_dev)));
+                (
+                    format!(
+                        "Format {} ({}) and {} ({})",
+                        efi_dev, layout.efi.filesystem, root_dev, layout.root.filesystem
+                    ),
+                    actions,
+                )
+            }
+            StepKind::Mount => (
+                format!("Mount target filesystems at {}", cfg.target),
+                vec![
+                    StepAction::run(["mount".to_string(), root_dev.clone(), cfg.target.clone()]),
+                    StepAction::run(["mkdir".to_string(), "-p".to_string(), efi_mount.clone()]),
+                    StepAction::run(["mount".to_string(), efi_dev.clone(), efi_mount.clone()]),
+                ],
+            ),
+            StepKind::Extract => (
+                format!("Extract the {} rootfs", cfg.variant.os_name()),
+                vec![StepAction::run([
+                    "recstrap".to_string(),
+                    cfg.target.clone(),
+                ])],
+            ),
+            StepKind::Fstab => (
+                "Generate /etc/fstab".to_string(),
+                vec![StepAction::Capture {
+                    argv: vec!["recfstab".to_string(), cfg.target.clone()],
+                    path: cfg.target_path("/etc/fstab"),
+                }],
+            ),
+            StepKind::Configure => (
+                "Configure system identity and locale".to_string(),
+                self.configure_actions(),
+            ),
+            StepKind::ChrootSetup => (
+                "Bind-mount API filesystems for chroot".to_string(),
+                mounts_in_order()
+                    .map(|m| {
+                        StepAction::run([
+                            "mount",
+                            "--bind",
+                            m.source,
+                            &m.full_target(cfg.target.trim_end_matches('/')),
+                        ])
+                    })
+                    .collect(),
+            ),
+            StepKind::Users => {
+                let mut actions: Vec<StepAction> = cfg
+                    .users
+                    .iter()
+                    .map(|u| self.chroot(u.useradd_command()))
+                    .collect();
+                for (username, keys) in cfg.authorized_keys.iter().filter(|(_, k)| !k.is_empty()) {
+                    let home = if username == "root" {
+                        ROOT_HOME.to_string()
+                    } else {
+                        format!("/home/{}", username)
+                    };
+                    actions.push(StepAction::run([
+                        "mkdir".to_string(),
+                        "-p".to_string(),
+                        cfg.target_path(&format!("{}/.ssh", home)),
+                    ]));
+                    actions.push(StepAction::WriteFile {
+                        path: cfg.target_path(&format!("{}/.ssh/authorized_keys", home)),
+                        contents: format!("{}\n", keys.join("\n")),
+                    });
+                    let ssh_dir = shell_quote(&format!("{}/.ssh", home));
+                    actions.push(self.chroot(format!(
+                        "chmod 700 {dir} && chmod 600 {keys} && chown -R {owner} {dir}",
+                        dir = ssh_dir,
+                        keys = shell_quote(&format!("{}/.ssh/authorized_keys", home)),
+                        owner = shell_quote(&format!("{}:", username))
+                    )));
+                }
+                (
+                    format!("Create {} user account(s)", cfg.users.len()),
+                    actions,
+                )
+            }
+            StepKind::Services => {
+                let mut actions: Vec<StepAction> = cfg
+                    .variant
+                    .profile_services(cfg.profile)
+                    .iter()
+                    .map(|s| self.chroot(s.enable_command()))
+                    .collect();
+                actions.extend(
+                    cfg.extra_services
+                        .iter()
+                        .map(|name| self.chroot(cfg.variant.enable_service_command(name))),
+                );
+                ("Enable services".to_string(), actions)
+            }
+            StepKind::Bootloader => {
+                let root = match cfg.encryption {
+                    Some(_) => root_dev.clone(),
+                    None => format!("LABEL={}", layout.root.label),
+                };
+                let mut entry = cfg.variant.boot_entry_with_root(root);
+                if let Some(uuid) = luks_uuid {
+                    entry =
+                        entry.with_option(cfg.variant.luks_root_options(uuid, LUKS_MAPPER_NAME));
+                }
+                // Counted entry: a kernel that fails to boot falls back to the previous one
+                let loader = cfg
+                    .variant
+                    .default_loader_config()
+                    .with_default_entry(counted_default_pattern(cfg.variant.os_id()));
+                (
+                    "Install systemd-boot and write counted boot entry".to_string(),
+                    vec![
+                        self.chroot(bootctl_install_command()),
+                        StepAction::WriteFile {
+                            path: cfg.target_path(LOADER_CONF_PATH),
+                            contents: loader.to_loader_conf(),
+                        },
+                        StepAction::WriteFile {
+                            path: cfg.target_path(&entry.counted_entry_path(DEFAULT_BOOT_TRIES)),
+                            contents: entry.to_entry_file(),
+                        },
+                    ],
+                )
+            }
+            StepKind::BootAssessment => {
+                let mut actions = Vec::new();
+                if let Some((path, script)) = cfg.variant.bless_boot_script() {
+                    actions.push(StepAction::WriteFile {
+                        path: cfg.target_path(path),
+                        contents: script,
+                    });
+                    actions.push(self.chroot(format!("chmod 755 {}", path)));
+                }
+                actions.extend(
+                    cfg.variant
+                        .boot_assessment_commands()
+                        .into_iter()
+                        .map(|command| self.chroot(command)),
+                );
+                ("Enable boot assessment".to_string(), actions)
+            }
+            StepKind::Unmount => {
+                let mut actions: Vec<StepAction> = mounts_in_unmount_order()
+                    .map(|m| {
+                        StepAction::run([
+                            "umount",
+                            &m.full_target(cfg.target.trim_end_matches('/')),
+                        ])
+                    })
+                    .collect();
+                actions.push(StepAction::run(["umount".to_string(), efi_mount]));
+                actions.push(StepAction::run(["umount".to_string(), cfg.target.clone()]));
+                if cfg.encryption.is_some() {
+                    actions.push(StepAction::run(["cryptsetup", "close", LUKS_MAPPER_NAME]));
+                }
+                ("Unmount target filesystems".to_string(), actions)
+            }
+        }
+    }
+
+    fn configure_actions(&self) -> Vec<StepAction> {
+        let cfg = &self.config;
+        let openrc = cfg.variant.init_system() == InitSystem::OpenRc;
+        let mut actions = vec![StepAction::WriteFile {
+            path: cfg.target_path("/etc/hostname"),
+            contents: format!("{}\n", cfg.hostname),
+        }];
+        if let Some(ref locale) = cfg.locale {
+            // musl has no locale.conf; the profile snippet sets LANG for login shells
+            let (path, contents) = if openrc {
+                (
+                    "/etc/profile.d/locale.sh",
+                    format!("export LANG={}\n", locale),
+                )
+            } else {
+                ("/etc/locale.conf", format!("LANG={}\n", locale))
+            };
+            actions.push(StepAction::WriteFile {
+                path: cfg.target_path(path),
+                contents,
+            });
+        }
+        if let Some(ref keymap) = cfg.keymap {
+            let (path, contents) = if openrc {
+                ("/etc/conf.d/keymaps", format!("keymap=\"{}\"\n", keymap))
+            } else {
+                ("/etc/vconsole.conf", format!("KEYMAP={}\n", keymap))
+            };
+            actions.push(StepAction::WriteFile {
+                path: cfg.target_path(path),
+                contents,
+            });
+        }
+        if let Some(ref tz) = cfg.timezone {
+            actions.push(StepAction::run([
+                "ln".to_string(),
+                "-sf".to_string(),
+                format!("/usr/share/zoneinfo/{}", tz),
+                cfg.target_path("/etc/localtime"),
+            ]));
+        }
+        // mkinitfs unlocks the root from the kernel command line alone
+        let luks_uuid = cfg.encryption.as_ref().and_then(|e| e.uuid.as_ref());
+        if let Some(uuid) = luks_uuid.filter(|_| !openrc) {
+            actions.push(StepAction::WriteFile {
+                path: cfg.target_path("/etc/crypttab"),
+                contents: format!("{} UUID={} none luks\n", LUKS_MAPPER_NAME, uuid),
+            });
+        }
+        actions
+    }
+
+    fn chroot(&self, command: impl Into<String>) -> StepAction {
+        StepAction::Chroot {
+            root: self.config.target.clone(),
+            command: command.into(),
+        }
+    }
+
+    /// Render the plan as a dry-run shell script.
+    pub fn to_shell_script(&self) -> String {
+        let total = self.steps.len();
+        let mut script = format!(
+            "#!/bin/sh\n# {} installation plan\n# disk: {}\n# target: {}\nset -eu\n",
+            self.config.variant.os_name(),
+            self.config.disk,
+            self.config.target
+        );
+        for (i, step) in self.steps.iter().enumerate() {
+            script.push_str(&format!(
+                "\n# [{}/{}] {}: {}\n",
+                i + 1,
+                total,
+                step.kind.name(),
+                step.description
+            ));
+            for action in &step.actions {
+                script.push_str(&action.to_shell());
+                script.push('\n');
+            }
+        }
+        script
+    }
+
+    /// Render the plan as JSON.
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let deps: Vec<&str> = step.depends_on.iter().map(|k| k.name()).collect();
+                let actions: Vec<String> = step.actions.iter().map(|a| a.to_json()).collect();
+                format!(
+                    "{{\"kind\":{},\"description\":{},\"depends_on\":{},\"actions\":[{}]}}",
+                    json_string(step.kind.name()),
+                    json_string(&step.description),
+                    json_array(&deps),
+                    actions.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"variant\":{},\"disk\":{},\"target\":{},\"steps\":[{}]}}",
+            json_string(self.config.variant.os_id()),
+            json_string(&self.config.disk),
+            json_string(&self.config.target),
+            steps.join(",")
+        )
+    }
+}
+
+/// Random (version 4) UUID in the canonical 8-4-4-4-12 form.
+fn random_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    let mut filled = 0;
+    while filled < bytes.len() {
+        let rest = &mut bytes[filled..];
+        // SAFETY: the pointer and length describe the unfilled tail of `bytes`
+        let n = unsafe { libc::getrandom(rest.as_mut_ptr().cast(), rest.len(), 0) };
+        if n > 0 {
+            filled += n as usize;
+        }
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn with_trailing_newline(s: &str) -> String {
+    if s.ends_with('\n') {
+        s.to_string()
+    } else {
+        format!("{}\n", s)
+    }
+}
+
+/// Encode a string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Encode a list of strings as a JSON array.
+pub(crate) fn json_array<S: AsRef<str>>(items: &[S]) -> String {
+    let items: Vec<String> = items.iter().map(|s| json_string(s.as_ref())).collect();
+    format!("[{}]", items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> InstallPlan {
+        let config = InstallConfig::new(Variant::Levitate, "/dev/nvme0n1")
+            .with_user(Variant::Levitate.default_user("alice"));
+        InstallPlan::new(config)
+    }
+
+    #[test]
+    fn test_steps_in_order() {
+        let kinds: Vec<StepKind> = plan().steps().iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, StepKind::ORDER);
+    }
+
+    #[test]
+    fn test_dependencies_precede_dependents() {
+        let plan = plan();
+        for (i, step) in plan.steps().iter().enumerate() {
+            for dep in &step.depends_on {
+                let pos = plan.steps().iter().position(|s| s.kind == *dep).unwrap();
+                assert!(
+                    pos < i,
+                    "{} must run after {}",
+                    step.kind.name(),
+                    dep.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unmount_cannot_run_early() {
+        let satisfied = |order: &[StepKind]| {
+            order
+                .iter()
+                .enumerate()
+                .all(|(i, kind)| kind.depends_on().iter().all(|dep| order[..i].contains(dep)))
+        };
+        assert!(satisfied(StepKind::ORDER));
+        // Moving unmount in front of any step after mount breaks the plan
+        let mount = StepKind::ORDER
+            .iter()
+            .position(|k| *k == StepKind::Mount)
+            .unwrap();
+        let last = StepKind::ORDER.len() - 1;
+        for i in mount + 1..last {
+            let mut order = StepKind::ORDER.to_vec();
+            let unmount = order.remove(last);
+            order.insert(i, unmount);
+            assert!(
+                !satisfied(&order),
+                "unmount may run before {}",
+                order[i + 1].name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_users_skips_users_step() {
+        let plan = InstallPlan::new(InstallConfig::new(Variant::Acorn, "/dev/vda"));
+        assert!(plan.step(StepKind::Users).is_none());
+        assert!(plan.step(StepKind::Services).is_some());
+    }
+
+    #[test]
+    fn test_partition_and_format_use_layout() {
+        let plan = plan();
+        let partition = plan.step(StepKind::Partition).unwrap();
+        assert_eq!(
+            partition.actions[0],
+            StepAction::Run {
+                argv: vec!["sfdisk".into(), "/dev/nvme0n1".into()],
+                stdin: Some(PartitionLayout::default().to_sfdisk_script()),
+            }
+        );
+        let format = plan.step(StepKind::Format).unwrap();
+        assert_eq!(
+            format.actions[1].argv().unwrap(),
+            ["mkfs.ext4", "-F", "-L", "root", "/dev/nvme0n1p2"]
+        );
+    }
+
+    #[test]
+    fn test_services_follow_variant() {
+        let plan = InstallPlan::new(InstallConfig::new(Variant::Acorn, "/dev/vda"));
+        let services = plan.step(StepKind::Services).unwrap();
+        assert!(services.actions.iter().any(|a| matches!(
+            a,
+            StepAction::Chroot { command, .. } if command.starts_with("rc-update add")
+        )));
+    }
+
+    #[test]
+    fn test_configure_step() {
+        let plan = InstallPlan::new(
+            InstallConfig::new(Variant::Levitate, "/dev/vda")
+                .with_hostname("ws01")
+                .with_locale("en_US.UTF-8")
+                .with_timezone("Europe/Amsterdam"),
+        );
+        let configure = plan.step(StepKind::Configure).unwrap();
+        assert!(configure.actions.contains(&StepAction::WriteFile {
+            path: "/mnt/etc/hostname".into(),
+            contents: "ws01\n".into(),
+        }));
+        assert!(configure.actions.contains(&StepAction::WriteFile {
+            path: "/mnt/etc/locale.conf".into(),
+            contents: "LANG=en_US.UTF-8\n".into(),
+        }));
+    }
+
+    #[test]
+    fn test_encrypted_root() {
+        const UUID: &str = "0f3c7a9e-4b21-4d6e-9a58-2c1f7e8d3b40";
+        let plan = InstallPlan::new(
+            InstallConfig::new(Variant::Levitate, "/dev/vda").with_encryption(RootEncryption {
+                key_file: Some("/tmp/key".into()),
+                uuid: Some(UUID.into()),
+            }),
+        );
+        let format = plan.step(StepKind::Format).unwrap();
+        assert_eq!(
+            format.actions[1].argv().unwrap(),
+            [
+                "cryptsetup",
+                "luksFormat",
+                "--type",
+                "luks2",
+                "--batch-mode",
+                "--key-file",
+                "/tmp/key",
+                "--uuid",
+                UUID,
+                "/dev/vda2"
+            ]
+        );
+        assert_eq!(
+            format.actions[2].argv().unwrap(),
+            [
+                "cryptsetup",
+                "open",
+                "--key-file",
+                "/tmp/key",
+                "/dev/vda2",
+                "cryptroot"
+            ]
+        );
+        assert_eq!(
+            format.actions[3].argv().unwrap(),
+            ["mkfs.ext4", "-F", "-L", "root", "/dev/mapper/cryptroot"]
+        );
+        let unmount = plan.step(StepKind::Unmount).unwrap();
+        assert_eq!(
+            unmount.actions.last().unwrap().argv().unwrap(),
+            ["cryptsetup", "close", "cryptroot"]
+        );
+
+        let configure = plan.step(StepKind::Configure).unwrap();
+        assert!(configure.actions.contains(&StepAction::WriteFile {
+            path: "/mnt/etc/crypttab".into(),
+            contents: format!("cryptroot UUID={} none luks\n", UUID),
+        }));
+        let StepAction::WriteFile { contents, .. } =
+            &plan.step(StepKind::Bootloader).unwrap().actions[2]
+        else {
+            panic!("expected the boot entry");
+        };
+        let options = contents.lines().find(|l| l.starts_with("options")).unwrap();
+        assert!(options.contains("root=/dev/mapper/cryptroot"));
+        assert!(options.ends_with(&format!(" rd.luks.name={}=cryptroot", UUID)));
+
+        // AcornOS: mkinitfs options, no crypttab
+        let plan = InstallPlan::new(
+            InstallConfig::new(Variant::Acorn, "/dev/vda").with_encryption(RootEncryption {
+                key_file: None,
+                uuid: Some(UUID.into()),
+            }),
+        );
+        let configure = plan.step(StepKind::Configure).unwrap();
+        assert!(!configure.actions.iter().any(
+            |a| matches!(a, StepAction::WriteFile { path, .. } if path.ends_with("crypttab"))
+        ));
+        let StepAction::WriteFile { contents, .. } =
+            &plan.step(StepKind::Bootloader).unwrap().actions[2]
+        else {
+            panic!("expected the boot entry");
+        };
+        let options = contents.lines().find(|l| l.starts_with("options")).unwrap();
+        assert!(options.ends_with(&format!(" cryptroot=UUID={} cryptdm=cryptroot", UUID)));
+        assert!(!options.contains("rd.luks"));
+    }
+
+    #[test]
+    fn test_encryption_uuid_generated() {
+        let config = InstallConfig::new(Variant::Levitate, "/dev/vda")
+            .with_encryption(RootEncryption::default());
+        let uuid = InstallPlan::new(config.clone())
+            .config()
+            .encryption
+            .clone()
+            .unwrap()
+            .uuid
+            .unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.as_bytes()[14], b'4');
+        assert_ne!(
+            InstallPlan::new(config)
+                .config()
+                .encryption
+                .clone()
+                .unwrap()
+                .uuid,
+            Some(uuid)
+        );
+    }
+
+    #[test]
+    fn test_extra_services_and_keys() {
+        let plan = InstallPlan::new(
+            InstallConfig::new(Variant::Acorn, "/dev/vda")
+                .with_user(Variant::Acorn.default_user("bob"))
+                .with_authorized_keys("bob", vec!["ssh-ed25519 AAAA bob@host".into()])
+                .with_service("dhcpcd"),
+        );
+        let services = plan.step(StepKind::Services).unwrap();
+        assert!(services.actions.contains(&StepAction::Chroot {
+            root: "/mnt".into(),
+            command: "rc-update add dhcpcd default".into(),
+        }));
+        let users = plan.step(StepKind::Users).unwrap();
+        assert!(users.actions.contains(&StepAction::WriteFile {
+            path: "/mnt/home/bob/.ssh/authorized_keys".into(),
+            contents: "ssh-ed25519 AAAA bob@host\n".into(),
+        }));
+        assert!(users.actions.contains(&StepAction::Chroot {
+            root: "/mnt".into(),
+            command: "chmod 700 /home/bob/.ssh && chmod 600 /home/bob/.ssh/authorized_keys \
+                      && chown -R bob: /home/bob/.ssh"
+                .into(),
+        }));
+    }
+
+    #[test]
+    fn test_profile_services() {
+        let enables = |profile| {
+            let config = InstallConfig::new(Variant::Levitate, "/dev/vda").with_profile(profile);
+            InstallPlan::new(config)
+                .step(StepKind::Services)
+                .unwrap()
+                .actions
+                .clone()
+        };
+        let enable = |name: &str| StepAction::Chroot {
+            root: "/mnt".into(),
+            command: format!("systemctl enable {}", name),
+        };
+        let desktop = enables(Profile::Desktop);
+        assert!(desktop.contains(&enable("NetworkManager")));
+        assert!(!desktop.contains(&enable("systemd-networkd")));
+        let minimal = enables(Profile::Minimal);
+        assert!(!minimal.contains(&enable("NetworkManager")));
+        assert!(!minimal.contains(&enable("sshd")));
+        assert!(minimal.contains(&enable("systemd-networkd")));
+        assert!(minimal.contains(&enable("systemd-resolved")));
+    }
+
+    #[test]
+    fn test_try_new_validates_names() {
+        let config = InstallConfig::new(Variant::Acorn, "/dev/vda")
+            .with_authorized_keys("bob;reboot", vec!["ssh-ed25519 AAAA".into()]);
+        assert!(InstallPlan::try_new(config.clone())
+            .unwrap_err()
+            .starts_with("authorized keys: "));
+        // Unchecked plans still quote what they interpolate
+        let users = InstallPlan::new(config);
+        let users = users.step(StepKind::Users).unwrap();
+        assert!(users.actions.iter().any(|a| matches!(
+            a,
+            StepAction::Chroot { command, .. } if command.ends_with("chown -R 'bob;reboot:' '/home/bob;reboot/.ssh'")
+        )));
+
+        let config = InstallConfig::new(Variant::Acorn, "/dev/vda").with_user(UserSpec::new(
+            "bob",
+            "/bin/sh;reboot",
+            &[],
+        ));
+        assert!(InstallPlan::try_new(config).is_err());
+        let config = InstallConfig::new(Variant::Acorn, "/dev/vda")
+            .with_user(Variant::Acorn.default_user("bob"))
+            .with_authorized_keys("root", vec!["ssh-ed25519 AAAA".into()]);
+        assert!(InstallPlan::try_new(config).is_ok());
+    }
+
+    #[test]
+    fn test_shell_script_rendering() {
+        let script = plan().to_shell_script();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("sfdisk /dev/nvme0n1 <<'DISTRO_SPEC_EOF'\nlabel: gpt\n"));
+        assert!(script.contains("recfstab /mnt >> /mnt/etc/fstab"));
+        assert!(script.contains(
+            "chroot /mnt /bin/sh -c 'useradd -m -s /bin/bash -G wheel,audio,video,input alice'"
+        ));
+        assert!(script.contains("cat > /mnt/boot/loader/entries/levitateos+3.conf"));
+        assert!(script.trim_end().ends_with("umount /mnt"));
+    }
+
+    #[test]
+    fn test_boot_counting() {
+        let plan = plan();
+        let bootloader = plan.step(StepKind::Bootloader).unwrap();
+        assert!(bootloader.actions.iter().any(|a| matches!(
+            a,
+            StepAction::WriteFile { path, contents }
+                if path == "/mnt/boot/loader/loader.conf"
+                    && contents.starts_with("default levitateos*\n")
+        )));
+        let assessment = plan.step(StepKind::BootAssessment).unwrap();
+        assert_eq!(
+            assessment.actions,
+            [StepAction::Chroot {
+                root: "/mnt".into(),
+                command: "systemctl enable systemd-boot-check-no-failures.service".into(),
+            }]
+        );
+
+        let plan = InstallPlan::new(InstallConfig::new(Variant::Acorn, "/dev/vda"));
+        let assessment = plan.step(StepKind::BootAssessment).unwrap();
+        assert!(matches!(
+            &assessment.actions[0],
+            StepAction::WriteFile { path, contents }
+                if path == "/mnt/etc/init.d/bless-boot" && contents.starts_with("#!/sbin/openrc-run")
+        ));
+        assert_eq!(
+            assessment.actions.last().unwrap(),
+            &StepAction::Chroot {
+                root: "/mnt".into(),
+                command: "rc-update add bless-boot default".into(),
+            }
+        );
+        assert!(plan
+            .step(StepKind::Bootloader)
+            .unwrap()
+            .actions
+            .iter()
+            .any(|a| matches!(a, StepAction::WriteFile { path, .. } if path == "/mnt/boot/loader/entries/acornos+3.conf")));
+    }
+
+    #[test]
+    fn test_json_rendering() {
+        let json = plan().to_json();
+        assert!(json.starts_with("{\"variant\":\"levitateos\",\"disk\":\"/dev/nvme0n1\""));
+        assert!(json.contains("\"kind\":\"bootloader\""));
+        assert!(json.contains("\"depends_on\":[\"chroot-setup\",\"fstab\",\"configure\"]"));
+        assert!(json.contains("\"stdin\":\"label: gpt\\n"));
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+}
@@ -1,5 +1,7 @@
 //! ISO structure constants shared between LevitateOS and AcornOS.
 
+use crate::arch::Arch;
+
 // =============================================================================
 // ISO Directory Structure
 // =============================================================================
@@ -35,6 +37,10 @@ pub const INITRAMFS_LIVE_ISO_PATH: &str = "boot/initramfs.img";
 /// Path to live overlay inside ISO (relative to ISO root)
 pub const LIVE_OVERLAY_ISO_PATH: &str = "live/overlay";
 
+/// Directory on ISO containing pre-built UKIs for installed systems.
+/// Users copy these to /boot/EFI/Linux/ during installation.
+pub const UKI_INSTALLED_ISO_DIR: &str = "boot/uki";
+
 // =============================================================================
 // EFI Boot Files
 // =============================================================================
@@ -48,11 +54,11 @@ pub const EFIBOOT_FILENAME: &str = "efiboot.img";
 /// With 3 UKIs + systemd-boot + loader.conf, we need ~200MB.
 pub const EFIBOOT_SIZE_MB: u32 = 200;
 
-/// Primary EFI bootloader filename
-pub const EFI_BOOTLOADER: &str = "BOOTX64.EFI";
+/// Primary EFI bootloader filename (`Arch::efi_bootloader` for other architectures)
+pub const EFI_BOOTLOADER: &str = Arch::DEFAULT.efi_bootloader();
 
-/// GRUB EFI binary filename
-pub const EFI_GRUB: &str = "grubx64.efi";
+/// GRUB EFI binary filename (`Arch::efi_grub` for other architectures)
+pub const EFI_GRUB: &str = Arch::DEFAULT.efi_grub();
 
 // =============================================================================
 // Console Configuration
@@ -97,6 +103,12 @@ pub const SHA512_SEPARATOR: &str = "  ";
 /// MBR partition offset for hybrid ISO
 pub const XORRISO_PARTITION_OFFSET: u32 = 16;
 
+/// GPT partition type of the appended EFI system partition.
+pub const XORRISO_EFI_PARTITION_TYPE: &str = "C12A7328-F81F-11D2-BA4B-00A0C93EC93B";
+
+/// Maximum length of an ISO9660 volume ID (xorriso -V).
+pub const ISO_VOLUME_ID_MAX_LEN: usize = 32;
+
 /// ISO filesystem flags for xorriso
 pub const XORRISO_FS_FLAGS: &[&str] = &[
     "-full-iso9660-filenames",
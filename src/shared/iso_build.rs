@@ -0,0 +1,273 @@
+//! xorriso invocation for UEFI hybrid ISOs.
+//!
+//! `IsoBuildPlan` takes a staged ISO tree and produces the full `xorriso`
+//! argv, so builders don't each re-assemble it from `XORRISO_FS_FLAGS`,
+//! `XORRISO_PARTITION_OFFSET` and friends. The resulting image boots:
+//!
+//! - from optical media via an El Torito EFI boot image, and
+//! - from USB sticks via a GPT EFI system partition appended after the
+//!   ISO9660 filesystem.
+//!
+//! Both point at the same `efiboot.img`; El Torito references the appended
+//! partition, so the image is only stored once.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::iso::{
+    EFIBOOT_FILENAME, EFI_BOOTLOADER, INITRAMFS_LIVE_ISO_PATH, ISO_EFI_DIR, ISO_VOLUME_ID_MAX_LEN,
+    KERNEL_ISO_PATH, ROOTFS_ISO_PATH, UKI_INSTALLED_ISO_DIR, XORRISO_EFI_PARTITION_TYPE,
+    XORRISO_FS_FLAGS, XORRISO_PARTITION_OFFSET,
+};
+use super::uki::UKI_EFI_DIR;
+use crate::variant::Variant;
+
+/// Why an ISO cannot be built from the staged tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsoBuildError {
+    /// Required files are not staged (paths relative to the staging directory)
+    MissingFiles(Vec<String>),
+    /// The EFI boot image does not exist
+    MissingEfiBootImage(PathBuf),
+    /// Volume ID is too long or uses characters outside A-Z, 0-9, _
+    InvalidVolumeId(String),
+}
+
+impl fmt::Display for IsoBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsoBuildError::MissingFiles(files) => {
+                write!(f, "missing staged files: {}", files.join(", "))
+            }
+            IsoBuildError::MissingEfiBootImage(path) => {
+                write!(f, "EFI boot image not found: {}", path.display())
+            }
+            IsoBuildError::InvalidVolumeId(id) => write!(
+                f,
+                "invalid volume ID {:?}: must be 1-{} characters of A-Z, 0-9, _",
+                id, ISO_VOLUME_ID_MAX_LEN
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IsoBuildError {}
+
+/// Files every live ISO must contain, relative to the ISO root.
+pub fn base_required_files() -> Vec<String> {
+    vec![
+        KERNEL_ISO_PATH.to_string(),
+        INITRAMFS_LIVE_ISO_PATH.to_string(),
+        ROOTFS_ISO_PATH.to_string(),
+        format!("{}/{}", ISO_EFI_DIR, EFI_BOOTLOADER),
+    ]
+}
+
+/// Everything needed to run xorriso for one ISO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoBuildPlan {
+    /// Directory containing the staged ISO tree
+    pub staging_dir: PathBuf,
+    /// Output ISO path
+    pub output: PathBuf,
+    /// FAT image with systemd-boot and the live UKIs
+    pub efiboot_image: PathBuf,
+    /// ISO9660 volume ID (must match the `root=LABEL=` the initramfs looks for)
+    pub volume_id: String,
+    /// Files that must exist under `staging_dir`
+    pub required_files: Vec<String>,
+}
+
+impl IsoBuildPlan {
+    /// Plan with the base required files.
+    ///
+    /// The EFI boot image defaults to `EFIBOOT_FILENAME` next to `output`,
+    /// outside the staged tree so it isn't stored twice.
+    pub fn new(
+        staging_dir: impl Into<PathBuf>,
+        output: impl Into<PathBuf>,
+        volume_id: impl Into<String>,
+    ) -> Self {
+        let output = output.into();
+        let efiboot_image = output
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(EFIBOOT_FILENAME);
+        Self {
+            staging_dir: staging_dir.into(),
+            output,
+            efiboot_image,
+            volume_id: volume_id.into(),
+            required_files: base_required_files(),
+        }
+    }
+
+    /// Plan for a variant's live ISO: its volume label and all of its UKIs.
+    pub fn for_variant(
+        variant: Variant,
+        staging_dir: impl Into<PathBuf>,
+        output: impl Into<PathBuf>,
+    ) -> Self {
+        let mut plan = Self::new(staging_dir, output, variant.iso_label());
+        for uki in variant.live_uki_filenames() {
+            plan = plan.with_required_file(format!("{}/{}", UKI_EFI_DIR, uki));
+        }
+        for uki in variant.installed_uki_filenames() {
+            plan = plan.with_required_file(format!("{}/{}", UKI_INSTALLED_ISO_DIR, uki));
+        }
+        plan
+    }
+
+    /// Use a different EFI boot image.
+    pub fn with_efiboot_image(mut self, path: impl Into<PathBuf>) -> Self {
+        self.efiboot_image = path.into();
+        self
+    }
+
+    /// Require another file (relative to the ISO root) to be staged.
+    pub fn with_required_file(mut self, path: impl Into<String>) -> Self {
+        self.required_files.push(path.into());
+        self
+    }
+
+    /// Required files that are not present under `staging_dir`.
+    pub fn missing_files(&self) -> Vec<String> {
+        self.required_files
+            .iter()
+            .filter(|f| !self.staging_dir.join(f).is_file())
+            .cloned()
+            .collect()
+    }
+
+    /// Check the volume ID and that everything is staged.
+    pub fn validate(&self) -> Result<(), IsoBuildError> {
+        let id_ok = !self.volume_id.is_empty()
+            && self.volume_id.len() <= ISO_VOLUME_ID_MAX_LEN
+            && self
+                .volume_id
+                .bytes()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_');
+        if !id_ok {
+            return Err(IsoBuildError::InvalidVolumeId(self.volume_id.clone()));
+        }
+        let missing = self.missing_files();
+        if !missing.is_empty() {
+            return Err(IsoBuildError::MissingFiles(missing));
+        }
+        if !self.efiboot_image.is_file() {
+            return Err(IsoBuildError::MissingEfiBootImage(
+                self.efiboot_image.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The xorriso argv, without checking the staged tree.
+    pub fn argv(&self) -> Vec<String> {
+        let mut argv: Vec<String> = ["xorriso", "-as", "mkisofs", "-o"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        argv.push(self.output.display().to_string());
+        argv.extend(["-V".to_string(), self.volume_id.clone()]);
+        argv.extend(XORRISO_FS_FLAGS.iter().map(|s| s.to_string()));
+        argv.extend([
+            "-partition_offset".to_string(),
+            XORRISO_PARTITION_OFFSET.to_string(),
+            // GPT EFI system partition after the ISO9660 filesystem
+            "-append_partition".to_string(),
+            "2".to_string(),
+            XORRISO_EFI_PARTITION_TYPE.to_string(),
+            self.efiboot_image.display().to_string(),
+            "-appended_part_as_gpt".to_string(),
+            // El Torito EFI entry pointing at the appended partition
+            "-eltorito-alt-boot".to_string(),
+            "-e".to_string(),
+            "--interval:appended_partition_2:all::".to_string(),
+            "-no-emul-boot".to_string(),
+        ]);
+        argv.push(self.staging_dir.display().to_string());
+        argv
+    }
+
+    /// Validate, then return the xorriso argv.
+    pub fn checked_argv(&self) -> Result<Vec<String>, IsoBuildError> {
+        self.validate()?;
+        Ok(self.argv())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    fn stage(dir: &TempDir, variant: Variant) {
+        for file in base_required_files() {
+            dir.write(&format!("staging/{}", file), b"x");
+        }
+        for uki in variant.live_uki_filenames() {
+            dir.write(&format!("staging/EFI/Linux/{}", uki), b"x");
+        }
+        for uki in variant.installed_uki_filenames() {
+            dir.write(&format!("staging/boot/uki/{}", uki), b"x");
+        }
+        dir.write("out/efiboot.img", b"x");
+    }
+
+    #[test]
+    fn test_argv() {
+        let plan = IsoBuildPlan::for_variant(Variant::Levitate, "staging", "out/levitateos.iso");
+        let argv = plan.argv().join(" ");
+        assert!(argv.starts_with("xorriso -as mkisofs -o out/levitateos.iso -V LEVITATEOS "));
+        assert!(argv.contains("-full-iso9660-filenames -joliet -rational-rock"));
+        assert!(argv.contains("-partition_offset 16"));
+        assert!(argv
+            .contains("-append_partition 2 C12A7328-F81F-11D2-BA4B-00A0C93EC93B out/efiboot.img"));
+        assert!(argv.contains("-e --interval:appended_partition_2:all:: -no-emul-boot"));
+        assert!(argv.ends_with(" staging"));
+    }
+
+    #[test]
+    fn test_validate_staged_tree() {
+        let dir = TempDir::new("iso-build");
+        let plan = IsoBuildPlan::for_variant(
+            Variant::Acorn,
+            dir.path().join("staging"),
+            dir.path().join("out/acornos.iso"),
+        );
+        match plan.validate() {
+            Err(IsoBuildError::MissingFiles(files)) => {
+                assert!(files.contains(&KERNEL_ISO_PATH.to_string()));
+                assert!(files.contains(&"EFI/Linux/acornos-live.efi".to_string()));
+                assert!(files.contains(&"boot/uki/acornos.efi".to_string()));
+            }
+            other => panic!("expected MissingFiles, got {:?}", other),
+        }
+
+        stage(&dir, Variant::Acorn);
+        assert!(plan.checked_argv().is_ok());
+
+        std::fs::remove_file(dir.path().join("staging").join(ROOTFS_ISO_PATH)).unwrap();
+        assert_eq!(
+            plan.validate(),
+            Err(IsoBuildError::MissingFiles(vec![
+                ROOTFS_ISO_PATH.to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_volume_id_validation() {
+        let plan = IsoBuildPlan::new("s", "o.iso", "levitate os");
+        assert!(matches!(
+            plan.validate(),
+            Err(IsoBuildError::InvalidVolumeId(_))
+        ));
+        let long = IsoBuildPlan::new("s", "o.iso", "A".repeat(33));
+        assert!(matches!(
+            long.validate(),
+            Err(IsoBuildError::InvalidVolumeId(_))
+        ));
+    }
+}
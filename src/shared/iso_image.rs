@@ -0,0 +1,989 @@
+//! Read-only ISO9660 reader and layout verifier.
+//!
+//! Reads a built ISO image directly - no loop mount, no root - and answers the
+//! questions the build and test tooling care about:
+//!
+//! - what is the volume ID (must equal `ISO_LABEL`)?
+//! - which files are on the image, and how big are they?
+//! - does the El Torito catalog have an EFI entry?
+//!
+//! Names are taken from Rock Ridge (`NM`) when present, otherwise from the
+//! Joliet tree, otherwise from plain ISO9660 identifiers with the `;1`
+//! version suffix stripped.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::devices::{ISO9660_PVD_OFFSET, ISO9660_STANDARD_ID};
+use super::iso::{
+    EFIBOOT_SIZE_MB, EFI_BOOTLOADER, INITRAMFS_LIVE_ISO_PATH, ISO_EFI_DIR, KERNEL_ISO_PATH,
+    ROOTFS_ISO_PATH, UKI_INSTALLED_ISO_DIR,
+};
+use super::partitions::EFI_PARTITION_SIZE_MB;
+use super::rootfs::{invalid, le_u16, le_u32};
+use super::uki::UKI_EFI_DIR;
+use crate::variant::Variant;
+
+/// ISO9660 logical sector size.
+pub const ISO_SECTOR_SIZE: u64 = 2048;
+
+/// Largest file a single ISO9660 extent can describe.
+///
+/// Bigger files need multi-extent records, which some firmware and older
+/// readers cannot follow.
+pub const ISO9660_MAX_EXTENT_SIZE: u64 = u32::MAX as u64;
+
+/// Boot system identifier of an El Torito boot record.
+pub const EL_TORITO_SYSTEM_ID: &[u8] = b"EL TORITO SPECIFICATION";
+
+/// El Torito platform ID for UEFI.
+pub const EL_TORITO_PLATFORM_EFI: u8 = 0xef;
+
+/// Nested directories deeper than this are treated as corrupt.
+const MAX_DEPTH: usize = 64;
+
+/// Directory and continuation-area extents larger than this are treated as
+/// corrupt (a directory of 100k files is well under 16 MiB).
+const MAX_DIRECTORY_SIZE: u32 = 32 * 1024 * 1024;
+
+// =============================================================================
+// Directory Records
+// =============================================================================
+
+/// A file or directory on the image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoEntry {
+    /// Path relative to the ISO root, `/`-separated (e.g., "live/filesystem.erofs")
+    pub path: String,
+    /// Whether this is a directory
+    pub is_dir: bool,
+    /// Size in bytes (summed over all extents for multi-extent files)
+    pub size: u64,
+    /// First logical block of the data
+    pub extent: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    lba: u32,
+    len: u32,
+}
+
+struct Record {
+    extent: Extent,
+    is_dir: bool,
+    multi_extent: bool,
+    name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Naming {
+    RockRidge { skip: usize },
+    Joliet,
+    Iso9660,
+}
+
+/// El Torito boot platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootPlatform {
+    X86,
+    PowerPc,
+    Mac,
+    Efi,
+    Other(u8),
+}
+
+impl BootPlatform {
+    fn from_id(id: u8) -> Self {
+        match id {
+            0x00 => BootPlatform::X86,
+            0x01 => BootPlatform::PowerPc,
+            0x02 => BootPlatform::Mac,
+            EL_TORITO_PLATFORM_EFI => BootPlatform::Efi,
+            other => BootPlatform::Other(other),
+        }
+    }
+}
+
+/// One boot image in the El Torito catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElToritoEntry {
+    /// Platform of the section this entry belongs to
+    pub platform: BootPlatform,
+    /// Marked bootable (0x88)
+    pub bootable: bool,
+    /// Media emulation type (0 = no emulation)
+    pub media_type: u8,
+    /// Number of 512-byte sectors to load (0 on some hybrid images)
+    pub sector_count: u16,
+    /// Logical block of the boot image
+    pub load_rba: u32,
+}
+
+/// A read-only view of an ISO9660 image.
+pub struct IsoImage<R> {
+    reader: R,
+    image_size: u64,
+    volume_id: String,
+    volume_blocks: u32,
+    primary_root: Extent,
+    joliet_root: Option<Extent>,
+    boot_catalog: Option<u32>,
+    /// Result of the first directory walk
+    tree: Option<Vec<IsoEntry>>,
+}
+
+impl IsoImage<File> {
+    /// Open an ISO image file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> IsoImage<R> {
+    /// Read the volume descriptors from any seekable source.
+    pub fn from_reader(mut reader: R) -> io::Result<Self> {
+        let image_size = reader.seek(SeekFrom::End(0))?;
+        let mut primary = None;
+        let mut joliet_root = None;
+        let mut boot_catalog = None;
+
+        for sector in 0.. {
+            let mut vd = [0u8; ISO_SECTOR_SIZE as usize];
+            reader.seek(SeekFrom::Start(
+                ISO9660_PVD_OFFSET + sector * ISO_SECTOR_SIZE,
+            ))?;
+            reader.read_exact(&mut vd)?;
+            if &vd[1..6] != ISO9660_STANDARD_ID {
+                return Err(invalid("volume descriptor without CD001 identifier"));
+            }
+            match vd[0] {
+                0 if vd[7..7 + EL_TORITO_SYSTEM_ID.len()] == *EL_TORITO_SYSTEM_ID => {
+                    boot_catalog = Some(le_u32(&vd, 71));
+                }
+                1 => {
+                    let volume_id = String::from_utf8_lossy(&vd[40..72]).trim_end().to_string();
+                    primary = Some((volume_id, le_u32(&vd, 80), root_extent(&vd)));
+                }
+                // Joliet: supplementary descriptor with a UCS-2 escape sequence
+                2 if vd[88..90] == *b"%/" && matches!(vd[90], b'@' | b'C' | b'E') => {
+                    joliet_root = Some(root_extent(&vd));
+                }
+                255 => break,
+                _ => {}
+            }
+            if sector > 64 {
+                return Err(invalid("no volume descriptor set terminator"));
+            }
+        }
+
+        let (volume_id, volume_blocks, primary_root) =
+            primary.ok_or_else(|| invalid("no primary volume descriptor"))?;
+        Ok(Self {
+            reader,
+            image_size,
+            volume_id,
+            volume_blocks,
+            primary_root,
+            joliet_root,
+            boot_catalog,
+            tree: None,
+        })
+    }
+
+    /// ISO9660 volume ID (what xorriso -V set).
+    pub fn volume_id(&self) -> &str {
+        &self.volume_id
+    }
+
+    /// Size of the ISO9660 filesystem in bytes (excludes appended partitions).
+    pub fn filesystem_size(&self) -> u64 {
+        u64::from(self.volume_blocks) * ISO_SECTOR_SIZE
+    }
+
+    /// Whether the image has a Joliet tree.
+    pub fn has_joliet(&self) -> bool {
+        self.joliet_root.is_some()
+    }
+
+    /// Whether the primary tree carries Rock Ridge extensions.
+    pub fn has_rock_ridge(&mut self) -> io::Result<bool> {
+        Ok(matches!(self.naming()?.0, Naming::RockRidge { .. }))
+    }
+
+    fn naming(&mut self) -> io::Result<(Naming, Extent)> {
+        // Rock Ridge is announced by an SUSP "SP" entry in the root's "." record
+        let data = self.read_extent(self.primary_root)?;
+        let len = data.first().copied().unwrap_or(0) as usize;
+        if len >= 34 && len <= data.len() {
+            let su = &data[34..len];
+            if su.len() >= 7 && &su[0..2] == b"SP" && su[4] == 0xbe && su[5] == 0xef {
+                return Ok((
+                    Naming::RockRidge {
+                        skip: su[6] as usize,
+                    },
+                    self.primary_root,
+                ));
+            }
+        }
+        Ok(match self.joliet_root {
+            Some(root) => (Naming::Joliet, root),
+            None => (Naming::Iso9660, self.primary_root),
+        })
+    }
+
+    /// Read a metadata extent (directory, continuation area, boot catalog).
+    ///
+    /// Extents past the end of the image or over `MAX_DIRECTORY_SIZE` are
+    /// rejected before anything is allocated.
+    fn read_extent(&mut self, extent: Extent) -> io::Result<Vec<u8>> {
+        let start = u64::from(extent.lba) * ISO_SECTOR_SIZE;
+        if extent.len > MAX_DIRECTORY_SIZE || start + u64::from(extent.len) > self.image_size {
+            return Err(invalid(format!(
+                "extent of {} bytes at block {} is out of range",
+                extent.len, extent.lba
+            )));
+        }
+        let mut buf = vec![0u8; extent.len as usize];
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Every file and directory on the image, in directory order.
+    ///
+    /// The tree is walked once; later calls (and `find`) reuse the result.
+    pub fn entries(&mut self) -> io::Result<&[IsoEntry]> {
+        if self.tree.is_none() {
+            let (naming, root) = self.naming()?;
+            let mut out = Vec::new();
+            let mut visited = HashSet::new();
+            self.walk(root, "", naming, 0, &mut visited, &mut out)?;
+            self.tree = Some(out);
+        }
+        Ok(self.tree.as_deref().unwrap_or_default())
+    }
+
+    /// Look up a single entry by path (e.g., "boot/uki/levitateos.efi").
+    pub fn find(&mut self, path: &str) -> io::Result<Option<IsoEntry>> {
+        let path = path.trim_matches('/');
+        Ok(self.entries()?.iter().find(|e| e.path == path).cloned())
+    }
+
+    fn walk(
+        &mut self,
+        dir: Extent,
+        prefix: &str,
+        naming: Naming,
+        depth: usize,
+        visited: &mut HashSet<u32>,
+        out: &mut Vec<IsoEntry>,
+    ) -> io::Result<()> {
+        if depth > MAX_DEPTH || !visited.insert(dir.lba) {
+            return Err(invalid("directory loop or excessive nesting"));
+        }
+        let data = self.read_extent(dir)?;
+        let mut records = Vec::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let len = data[pos] as usize;
+            if len == 0 {
+                // Records never span sectors; zero padding up to the next one
+                pos = (pos / ISO_SECTOR_SIZE as usize + 1) * ISO_SECTOR_SIZE as usize;
+                continue;
+            }
+            if len < 34 || pos + len > data.len() {
+                return Err(invalid(format!("bad directory record at {}", pos)));
+            }
+            if let Some(record) = self.parse_record(&data[pos..pos + len], naming)? {
+                records.push(record);
+            }
+            pos += len;
+        }
+
+        let mut i = 0;
+        while i < records.len() {
+            let record = &records[i];
+            let mut size = u64::from(record.extent.len);
+            // Multi-extent files: following records with the same name continue it
+            let mut j = i;
+            while records[j].multi_extent
+                && j + 1 < records.len()
+                && records[j + 1].name == record.name
+            {
+                j += 1;
+                size += u64::from(records[j].extent.len);
+            }
+            let path = if prefix.is_empty() {
+                record.name.clone()
+            } else {
+                format!("{}/{}", prefix, record.name)
+            };
+            out.push(IsoEntry {
+                path: path.clone(),
+                is_dir: record.is_dir,
+                size,
+                extent: record.extent.lba,
+            });
+            if record.is_dir {
+                let extent = record.extent;
+                self.walk(extent, &path, naming, depth + 1, visited, out)?;
+            }
+            i = j + 1;
+        }
+        Ok(())
+    }
+
+    /// Parse one directory record; `None` for the "." and ".." entries.
+    fn parse_record(&mut self, rec: &[u8], naming: Naming) -> io::Result<Option<Record>> {
+        let name_len = rec[32] as usize;
+        if 33 + name_len > rec.len() {
+            return Err(invalid("directory record name overruns record"));
+        }
+        let raw_name = &rec[33..33 + name_len];
+        if name_len == 1 && (raw_name[0] == 0 || raw_name[0] == 1) {
+            return Ok(None);
+        }
+        let flags = rec[25];
+        let extent = Extent {
+            lba: le_u32(rec, 2),
+            len: le_u32(rec, 10),
+        };
+
+        let name = match naming {
+            Naming::RockRidge { skip } => {
+                let su_start = 33 + name_len + (1 - name_len % 2) + skip;
+                let su = rec.get(su_start..).unwrap_or_default().to_vec();
+                match self.rock_ridge_name(&su, 0)? {
+                    Some(name) => name,
+                    None => iso9660_name(raw_name),
+                }
+            }
+            Naming::Joliet => {
+                let units: Vec<u16> = raw_name
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                strip_version(&String::from_utf16_lossy(&units)).to_string()
+            }
+            Naming::Iso9660 => iso9660_name(raw_name),
+        };
+
+        Ok(Some(Record {
+            extent,
+            is_dir: flags & 0x02 != 0,
+            multi_extent: flags & 0x80 != 0,
+            name,
+        }))
+    }
+
+    /// Collect a Rock Ridge `NM` name, following `CE` continuation areas.
+    fn rock_ridge_name(&mut self, su: &[u8], depth: usize) -> io::Result<Option<String>> {
+        let mut name: Option<Vec<u8>> = None;
+        let mut continuation = None;
+        let mut pos = 0;
+        while pos + 4 <= su.len() {
+            let sig = &su[pos..pos + 2];
+            let len = su[pos + 2] as usize;
+            if len < 4 || pos + len > su.len() {
+                break;
+            }
+            let entry = &su[pos..pos + len];
+            match sig {
+                // Flags: 0x02 = ".", 0x04 = ".." (never used for real names)
+                b"NM" if len >= 5 && entry[4] & 0x06 == 0 => {
+                    name.get_or_insert_with(Vec::new)
+                        .extend_from_slice(&entry[5..]);
+                }
+                // Continuation area: block, offset and length (both-endian fields)
+                b"CE" if len >= 28 => {
+                    let extent = Extent {
+                        lba: le_u32(entry, 4),
+                        len: le_u32(entry, 20),
+                    };
+                    continuation = Some((extent, le_u32(entry, 12)));
+                }
+                b"ST" => break,
+                _ => {}
+            }
+            pos += len;
+        }
+        if let Some((extent, offset)) = continuation {
+            if depth < 8 {
+                let len = offset
+                    .checked_add(extent.len)
+                    .ok_or_else(|| invalid("Rock Ridge continuation area out of range"))?;
+                let area = self.read_extent(Extent {
+                    lba: extent.lba,
+                    len,
+                })?;
+                if let Some(rest) = self.rock_ridge_name(&area[offset as usize..], depth + 1)? {
+                    name.get_or_insert_with(Vec::new)
+                        .extend_from_slice(rest.as_bytes());
+                }
+            }
+        }
+        Ok(name.map(|n| String::from_utf8_lossy(&n).into_owned()))
+    }
+
+    /// Parse the El Torito boot catalog, if the image has one.
+    pub fn boot_catalog(&mut self) -> io::Result<Vec<ElToritoEntry>> {
+        let Some(lba) = self.boot_catalog else {
+            return Ok(Vec::new());
+        };
+        let catalog = self.read_extent(Extent {
+            lba,
+            len: ISO_SECTOR_SIZE as u32,
+        })?;
+
+        // Validation entry: header 0x01, key 0x55 0xAA, words sum to zero
+        let validation = &catalog[0..32];
+        let sum = validation.chunks_exact(2).fold(0u16, |acc, w| {
+            acc.wrapping_add(u16::from_le_bytes([w[0], w[1]]))
+        });
+        if validation[0] != 0x01 || validation[30..32] != [0x55, 0xaa] || sum != 0 {
+            return Err(invalid("invalid El Torito validation entry"));
+        }
+
+        let parse = |e: &[u8], platform| ElToritoEntry {
+            platform,
+            bootable: e[0] == 0x88,
+            media_type: e[1] & 0x0f,
+            sector_count: le_u16(e, 6),
+            load_rba: le_u32(e, 8),
+        };
+
+        let mut entries = vec![parse(
+            &catalog[32..64],
+            BootPlatform::from_id(validation[1]),
+        )];
+        let mut pos = 64;
+        while pos + 32 <= catalog.len() {
+            let header = &catalog[pos..pos + 32];
+            if header[0] != 0x90 && header[0] != 0x91 {
+                break;
+            }
+            let platform = BootPlatform::from_id(header[1]);
+            let count = le_u16(header, 2) as usize;
+            pos += 32;
+            for _ in 0..count {
+                if pos + 32 > catalog.len() {
+                    break;
+                }
+                entries.push(parse(&catalog[pos..pos + 32], platform));
+                pos += 32;
+            }
+            if header[0] == 0x91 {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn root_extent(vd: &[u8]) -> Extent {
+    // Root directory record is embedded at offset 156
+    Extent {
+        lba: le_u32(vd, 156 + 2),
+        len: le_u32(vd, 156 + 10),
+    }
+}
+
+fn strip_version(name: &str) -> &str {
+    let name = name.split(';').next().unwrap_or(name);
+    name.strip_suffix('.').unwrap_or(name)
+}
+
+fn iso9660_name(raw: &[u8]) -> String {
+    strip_version(&String::from_utf8_lossy(raw)).to_string()
+}
+
+// =============================================================================
+// Layout Verification
+// =============================================================================
+
+/// A file the image must contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedIsoEntry {
+    /// Path relative to the ISO root
+    pub path: String,
+    /// Largest acceptable size in bytes
+    pub max_size: u64,
+}
+
+/// Expected layout of a variant's live ISO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoLayout {
+    /// Expected volume ID
+    pub volume_id: &'static str,
+    /// Files that must be present
+    pub entries: Vec<ExpectedIsoEntry>,
+}
+
+impl IsoLayout {
+    /// Layout produced by `IsoBuildPlan::for_variant`.
+    ///
+    /// Live UKIs must fit in `efiboot.img` and installed UKIs in the installed
+    /// ESP; everything else is limited to a single ISO9660 extent.
+    pub fn for_variant(variant: Variant) -> Self {
+        let mb = |n: u32| u64::from(n) * 1024 * 1024;
+        let mut entries: Vec<ExpectedIsoEntry> = [
+            KERNEL_ISO_PATH.to_string(),
+            INITRAMFS_LIVE_ISO_PATH.to_string(),
+            ROOTFS_ISO_PATH.to_string(),
+            format!("{}/{}", ISO_EFI_DIR, EFI_BOOTLOADER),
+        ]
+        .into_iter()
+        .map(|path| ExpectedIsoEntry {
+            path,
+            max_size: ISO9660_MAX_EXTENT_SIZE,
+        })
+        .collect();
+        for uki in variant.live_uki_filenames() {
+            entries.push(ExpectedIsoEntry {
+                path: format!("{}/{}", UKI_EFI_DIR, uki),
+                max_size: mb(EFIBOOT_SIZE_MB),
+            });
+        }
+        for uki in variant.installed_uki_filenames() {
+            entries.push(ExpectedIsoEntry {
+                path: format!("{}/{}", UKI_INSTALLED_ISO_DIR, uki),
+                max_size: mb(EFI_PARTITION_SIZE_MB),
+            });
+        }
+        Self {
+            volume_id: variant.iso_label(),
+            entries,
+        }
+    }
+}
+
+/// A difference between an image and its expected layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsoIssue {
+    /// Volume ID does not match `ISO_LABEL`
+    WrongVolumeId { expected: String, found: String },
+    /// Required file is absent
+    Missing { path: String },
+    /// Required path exists but is a directory
+    NotAFile { path: String },
+    /// File is larger than allowed
+    Oversized { path: String, size: u64, max: u64 },
+    /// El Torito catalog has no bootable EFI entry
+    NoEfiBootEntry,
+}
+
+impl fmt::Display for IsoIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsoIssue::WrongVolumeId { expected, found } => {
+                write!(f, "volume ID is {:?}, expected {:?}", found, expected)
+            }
+            IsoIssue::Missing { path } => write!(f, "missing: {}", path),
+            IsoIssue::NotAFile { path } => write!(f, "not a file: {}", path),
+            IsoIssue::Oversized { path, size, max } => {
+                write!(f, "oversized: {} is {} bytes (max {})", path, size, max)
+            }
+            IsoIssue::NoEfiBootEntry => write!(f, "no bootable EFI El Torito entry"),
+        }
+    }
+}
+
+/// Compare an open image with a layout. An empty result means it matches.
+pub fn verify_layout<R: Read + Seek>(
+    image: &mut IsoImage<R>,
+    layout: &IsoLayout,
+) -> io::Result<Vec<IsoIssue>> {
+    let mut issues = Vec::new();
+    if image.volume_id() != layout.volume_id {
+        issues.push(IsoIssue::WrongVolumeId {
+            expected: layout.volume_id.to_string(),
+            found: image.volume_id().to_string(),
+        });
+    }
+
+    let entries = image.entries()?;
+    for expected in &layout.entries {
+        match entries.iter().find(|e| e.path == expected.path) {
+            None => issues.push(IsoIssue::Missing {
+                path: expected.path.clone(),
+            }),
+            Some(e) if e.is_dir => issues.push(IsoIssue::NotAFile {
+                path: expected.path.clone(),
+            }),
+            Some(e) if e.size > expected.max_size => issues.push(IsoIssue::Oversized {
+                path: expected.path.clone(),
+                size: e.size,
+                max: expected.max_size,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    let efi = image
+        .boot_catalog()?
+        .iter()
+        .any(|e| e.bootable && e.platform == BootPlatform::Efi);
+    if !efi {
+        issues.push(IsoIssue::NoEfiBootEntry);
+    }
+    Ok(issues)
+}
+
+/// Open `path` and verify it against a variant's expected layout.
+pub fn verify_iso(path: impl AsRef<Path>, variant: Variant) -> io::Result<Vec<IsoIssue>> {
+    let mut image = IsoImage::open(path)?;
+    verify_layout(&mut image, &IsoLayout::for_variant(variant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Minimal ISO writer for fixtures. File contents are never read by the
+    /// reader, so data extents all point at one empty sector and sizes are free.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Names {
+        Plain,
+        RockRidge,
+        Joliet,
+    }
+
+    struct Node {
+        name: String,
+        size: u64,
+        children: Option<Vec<Node>>,
+    }
+
+    fn insert(root: &mut Vec<Node>, path: &str, size: u64) {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        let file = parts.pop().unwrap();
+        let mut dir = root;
+        for part in parts {
+            let idx = match dir.iter().position(|n| n.name == part) {
+                Some(i) => i,
+                None => {
+                    dir.push(Node {
+                        name: part.to_string(),
+                        size: 0,
+                        children: Some(Vec::new()),
+                    });
+                    dir.len() - 1
+                }
+            };
+            dir = dir[idx].children.as_mut().unwrap();
+        }
+        dir.push(Node {
+            name: file.to_string(),
+            size,
+            children: None,
+        });
+    }
+
+    fn record(lba: u32, len: u32, dir: bool, multi: bool, name: &[u8], su: &[u8]) -> Vec<u8> {
+        let pad = 1 - name.len() % 2;
+        let total = 33 + name.len() + pad + su.len();
+        let mut r = vec![0u8; total];
+        r[0] = total as u8;
+        r[2..6].copy_from_slice(&lba.to_le_bytes());
+        r[6..10].copy_from_slice(&lba.to_be_bytes());
+        r[10..14].copy_from_slice(&len.to_le_bytes());
+        r[14..18].copy_from_slice(&len.to_be_bytes());
+        r[25] = (if dir { 0x02 } else { 0 }) | (if multi { 0x80 } else { 0 });
+        r[32] = name.len() as u8;
+        r[33..33 + name.len()].copy_from_slice(name);
+        r[33 + name.len() + pad..].copy_from_slice(su);
+        r
+    }
+
+    fn nm(name: &str) -> Vec<u8> {
+        let mut e = vec![b'N', b'M', (5 + name.len()) as u8, 1, 0];
+        e.extend_from_slice(name.as_bytes());
+        e
+    }
+
+    struct Writer {
+        image: Vec<u8>,
+        names: Names,
+    }
+
+    const DATA_LBA: u32 = 30;
+
+    impl Writer {
+        fn alloc(&mut self) -> u32 {
+            let lba = (self.image.len() / 2048) as u32;
+            self.image.resize(self.image.len() + 2048, 0);
+            lba
+        }
+
+        fn encode(&self, name: &str, dir: bool) -> (Vec<u8>, Vec<u8>) {
+            match self.names {
+                Names::Joliet => (
+                    name.encode_utf16().flat_map(|u| u.to_be_bytes()).collect(),
+                    Vec::new(),
+                ),
+                Names::RockRidge => {
+                    let iso = name.to_ascii_uppercase().replace('-', "_");
+                    let iso = if dir { iso } else { format!("{};1", iso) };
+                    (iso.into_bytes(), nm(name))
+                }
+                Names::Plain => {
+                    let iso = if dir {
+                        name.to_string()
+                    } else {
+                        format!("{};1", name)
+                    };
+                    (iso.into_bytes(), Vec::new())
+                }
+            }
+        }
+
+        fn write_dir(&mut self, nodes: &[Node], root: bool) -> u32 {
+            let lba = self.alloc();
+            let mut body = Vec::new();
+            let dot_su = if root && self.names == Names::RockRidge {
+                vec![b'S', b'P', 7, 1, 0xbe, 0xef, 0]
+            } else {
+                Vec::new()
+            };
+            body.extend(record(lba, 2048, true, false, &[0], &dot_su));
+            body.extend(record(lba, 2048, true, false, &[1], &[]));
+            for node in nodes {
+                let (name, su) = self.encode(&node.name, node.children.is_some());
+                match &node.children {
+                    Some(children) => {
+                        let child = self.write_dir(children, false);
+                        body.extend(record(child, 2048, true, false, &name, &su));
+                    }
+                    None if node.size > ISO9660_MAX_EXTENT_SIZE => {
+                        body.extend(record(DATA_LBA, u32::MAX - 2047, false, true, &name, &su));
+                        let rest = (node.size - u64::from(u32::MAX - 2047)) as u32;
+                        body.extend(record(DATA_LBA, rest, false, false, &name, &su));
+                    }
+                    None => {
+                        body.extend(record(DATA_LBA, node.size as u32, false, false, &name, &su))
+                    }
+                }
+            }
+            let start = lba as usize * 2048;
+            self.image[start..start + body.len()].copy_from_slice(&body);
+            lba
+        }
+    }
+
+    fn build(label: &str, names: Names, files: &[(&str, u64)], efi_boot: bool) -> Vec<u8> {
+        let mut tree = Vec::new();
+        for (path, size) in files {
+            insert(&mut tree, path, *size);
+        }
+        let mut w = Writer {
+            image: vec![0u8; (DATA_LBA as usize + 1) * 2048],
+            names,
+        };
+        let root = w.write_dir(&tree, true);
+
+        let vd = |w: &mut Writer, sector: usize, kind: u8| -> usize {
+            let off = sector * 2048;
+            w.image[off] = kind;
+            w.image[off + 1..off + 6].copy_from_slice(ISO9660_STANDARD_ID);
+            w.image[off + 6] = 1;
+            off
+        };
+        let blocks = (w.image.len() / 2048) as u32;
+        let root_rec = record(root, 2048, true, false, &[0], &[]);
+
+        let pvd = vd(&mut w, 16, 1);
+        w.image[pvd + 40..pvd + 72].fill(b' ');
+        w.image[pvd + 40..pvd + 40 + label.len()].copy_from_slice(label.as_bytes());
+        w.image[pvd + 80..pvd + 84].copy_from_slice(&blocks.to_le_bytes());
+        w.image[pvd + 156..pvd + 156 + 34].copy_from_slice(&root_rec);
+
+        let br = vd(&mut w, 17, 0);
+        w.image[br + 7..br + 7 + EL_TORITO_SYSTEM_ID.len()].copy_from_slice(EL_TORITO_SYSTEM_ID);
+        w.image[br + 71..br + 75].copy_from_slice(&20u32.to_le_bytes());
+
+        let mut next = 18;
+        if names == Names::Joliet {
+            let svd = vd(&mut w, next, 2);
+            w.image[svd + 88..svd + 91].copy_from_slice(b"%/E");
+            w.image[svd + 156..svd + 156 + 34].copy_from_slice(&root_rec);
+            next += 1;
+        }
+        vd(&mut w, next, 255);
+
+        // Boot catalog at sector 20: validation entry (x86) + default entry,
+        // then an EFI section
+        let cat = 20 * 2048;
+        let mut validation = [0u8; 32];
+        validation[0] = 1;
+        validation[30] = 0x55;
+        validation[31] = 0xaa;
+        let sum = validation.chunks_exact(2).fold(0u16, |a, c| {
+            a.wrapping_add(u16::from_le_bytes([c[0], c[1]]))
+        });
+        validation[28..30].copy_from_slice(&0u16.wrapping_sub(sum).to_le_bytes());
+        w.image[cat..cat + 32].copy_from_slice(&validation);
+        if efi_boot {
+            w.image[cat + 64] = 0x91;
+            w.image[cat + 65] = EL_TORITO_PLATFORM_EFI;
+            w.image[cat + 66] = 1;
+            w.image[cat + 96] = 0x88;
+            w.image[cat + 104..cat + 108].copy_from_slice(&12345u32.to_le_bytes());
+        }
+        w.image
+    }
+
+    fn levitate_files() -> Vec<(String, u64)> {
+        IsoLayout::for_variant(Variant::Levitate)
+            .entries
+            .into_iter()
+            .map(|e| (e.path, 1000))
+            .collect()
+    }
+
+    fn open(image: Vec<u8>) -> IsoImage<Cursor<Vec<u8>>> {
+        IsoImage::from_reader(Cursor::new(image)).unwrap()
+    }
+
+    #[test]
+    fn test_reads_rock_ridge_names() {
+        let mut iso = open(build(
+            "LEVITATEOS",
+            Names::RockRidge,
+            &[("live/filesystem.erofs", 5000), ("boot/vmlinuz", 100)],
+            true,
+        ));
+        assert_eq!(iso.volume_id(), "LEVITATEOS");
+        assert!(iso.has_rock_ridge().unwrap());
+        let rootfs = iso.find("live/filesystem.erofs").unwrap().unwrap();
+        assert_eq!(rootfs.size, 5000);
+        assert!(iso.find("live").unwrap().unwrap().is_dir);
+        assert!(iso.find("LIVE/FILESYSTEM_EROFS").unwrap().is_none());
+
+        // Lookups after the first walk don't touch the image again
+        iso.reader = Cursor::new(Vec::new());
+        assert!(iso.find("boot/vmlinuz").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_directory() {
+        let image = build("X", Names::Plain, &[("a", 1)], false);
+        // Root directory record in the primary volume descriptor
+        let root = 16 * 2048 + 156;
+        let with_root = |lba: u32, len: u32| {
+            let mut image = image.clone();
+            image[root + 2..root + 6].copy_from_slice(&lba.to_le_bytes());
+            image[root + 10..root + 14].copy_from_slice(&len.to_le_bytes());
+            open(image)
+        };
+
+        // Over the directory cap, and past the end of the image
+        for (lba, len) in [(21, u32::MAX), (21, 1 << 30), (10_000, 2048)] {
+            let err = with_root(lba, len).entries().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{} {}", lba, len);
+        }
+    }
+
+    #[test]
+    fn test_rejects_overflowing_continuation_area() {
+        let mut iso = open(build("X", Names::RockRidge, &[("a", 1)], false));
+        let ce = |lba: u32, offset: u32, len: u32| {
+            let mut e = vec![0u8; 28];
+            e[..4].copy_from_slice(&[b'C', b'E', 28, 1]);
+            for (at, v) in [(4, lba), (12, offset), (20, len)] {
+                e[at..at + 4].copy_from_slice(&v.to_le_bytes());
+                e[at + 4..at + 8].copy_from_slice(&v.to_be_bytes());
+            }
+            e
+        };
+
+        let err = iso.rock_ridge_name(&ce(21, u32::MAX, 16), 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // A well-formed area that holds no name is fine
+        assert_eq!(iso.rock_ridge_name(&ce(21, 100, 16), 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reads_joliet_and_plain_names() {
+        let files = [("EFI/BOOT/BOOTX64.EFI", 10)];
+        let mut joliet = open(build("X", Names::Joliet, &files, false));
+        assert!(joliet.has_joliet());
+        assert!(joliet.find("EFI/BOOT/BOOTX64.EFI").unwrap().is_some());
+
+        let mut plain = open(build("X", Names::Plain, &files, false));
+        assert!(!plain.has_rock_ridge().unwrap());
+        assert!(plain.find("EFI/BOOT/BOOTX64.EFI").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_multi_extent_size() {
+        let big = ISO9660_MAX_EXTENT_SIZE + 10;
+        let mut iso = open(build("X", Names::RockRidge, &[("big.img", big)], false));
+        let entries = iso.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].size, big);
+    }
+
+    #[test]
+    fn test_boot_catalog() {
+        let mut iso = open(build("X", Names::Plain, &[("a", 1)], true));
+        let catalog = iso.boot_catalog().unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog[0].platform, BootPlatform::X86);
+        assert!(!catalog[0].bootable);
+        assert_eq!(catalog[1].platform, BootPlatform::Efi);
+        assert!(catalog[1].bootable);
+        assert_eq!(catalog[1].load_rba, 12345);
+    }
+
+    #[test]
+    fn test_verify_layout() {
+        let layout = IsoLayout::for_variant(Variant::Levitate);
+        let files = levitate_files();
+        let refs: Vec<(&str, u64)> = files.iter().map(|(p, s)| (p.as_str(), *s)).collect();
+        let mut good = open(build("LEVITATEOS", Names::RockRidge, &refs, true));
+        assert_eq!(verify_layout(&mut good, &layout).unwrap(), []);
+
+        // Wrong label, missing rootfs, oversized UKI, no EFI boot entry
+        let mut bad_files: Vec<(&str, u64)> = refs
+            .iter()
+            .filter(|(p, _)| *p != ROOTFS_ISO_PATH)
+            .copied()
+            .collect();
+        let uki = bad_files
+            .iter_mut()
+            .find(|(p, _)| p.starts_with("EFI/Linux/"))
+            .unwrap();
+        uki.1 = 300 * 1024 * 1024;
+        let uki_path = uki.0.to_string();
+        let mut bad = open(build("ACORNOS", Names::RockRidge, &bad_files, false));
+        let issues = verify_layout(&mut bad, &layout).unwrap();
+        assert_eq!(
+            issues,
+            [
+                IsoIssue::WrongVolumeId {
+                    expected: "LEVITATEOS".into(),
+                    found: "ACORNOS".into()
+                },
+                IsoIssue::Missing {
+                    path: ROOTFS_ISO_PATH.into()
+                },
+                IsoIssue::Oversized {
+                    path: uki_path,
+                    size: 300 * 1024 * 1024,
+                    max: 200 * 1024 * 1024
+                },
+                IsoIssue::NoEfiBootEntry,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_iso() {
+        assert!(IsoImage::from_reader(Cursor::new(vec![0u8; 64 * 1024])).is_err());
+    }
+}
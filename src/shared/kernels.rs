@@ -0,0 +1,521 @@
+//! Versioned kernel, initramfs and UKI naming, and kernel retention.
+//!
+//! `KERNEL_FILENAME` and `UKI_INSTALLED_FILENAME` are fixed names, which leaves
+//! room for exactly one kernel. For updates that keep a working fallback,
+//! installed systems name boot files after the kernel version, following the
+//! Boot Loader Specification:
+//!
+//! | File      | Name                           | Location (ESP)      |
+//! |-----------|--------------------------------|---------------------|
+//! | Kernel    | `vmlinuz-<kver>`               | `/`                 |
+//! | Initramfs | `initramfs-<kver>.img`         | `/`                 |
+//! | UKI       | `<os_id>-<kver>.efi`           | `EFI/Linux/`        |
+//! | Recovery  | `recovery-<os_id>-<kver>.efi`  | `EFI/Linux/`        |
+//! | Entry     | `<os_id>-<kver>.conf`          | `loader/entries/`   |
+//!
+//! systemd-boot sorts entries with the same prefix by version, newest first.
+//! Recovery images are prefixed rather than suffixed so the loader.conf
+//! `default` glob (`<os_id>*`) never picks one.
+//! `RetentionPolicy` decides which versions stay on the ESP.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::boot::BootEntry;
+use super::esp::RETAINED_KERNELS;
+use super::paths::{INITRAMFS_FILENAME, KERNEL_FILENAME};
+use super::uki::UKI_EFI_DIR;
+
+/// Prefix that marks a recovery UKI.
+pub const RECOVERY_PREFIX: &str = "recovery-";
+
+// =============================================================================
+// Kernel Version
+// =============================================================================
+
+/// Kernel release string (`uname -r`), ordered like systemd-boot orders entries.
+///
+/// Digit runs compare numerically and everything else lexically, so
+/// `6.12.10` > `6.12.9`, and `6.6.30-lts` > `6.6.30`. Releases that only
+/// differ in spelling (`6.06` and `6.6`) are ordered by the raw string, so
+/// the order agrees with `Eq`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KernelVersion(String);
+
+impl KernelVersion {
+    /// Parse a kernel release. Must start with a digit and contain no `/` or whitespace.
+    pub fn parse(s: &str) -> Option<Self> {
+        let valid = s.starts_with(|c: char| c.is_ascii_digit())
+            && !s.contains('/')
+            && !s.chars().any(char::is_whitespace);
+        valid.then(|| KernelVersion(s.to_string()))
+    }
+
+    /// The release string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Ord for KernelVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_versions(&self.0, &other.0).then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for KernelVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare two version strings segment by segment.
+///
+/// `.`, `-`, `_` and `+` only separate segments. A `~` segment sorts before
+/// anything, including the end of the string (`6.13~rc1` < `6.13`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn is_sep(c: u8) -> bool {
+        matches!(c, b'.' | b'-' | b'_' | b'+')
+    }
+
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    loop {
+        while i < a.len() && is_sep(a[i]) {
+            i += 1;
+        }
+        while j < b.len() && is_sep(b[j]) {
+            j += 1;
+        }
+
+        let a_tilde = a.get(i) == Some(&b'~');
+        let b_tilde = b.get(j) == Some(&b'~');
+        match (a_tilde, b_tilde) {
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (true, true) => {
+                i += 1;
+                j += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        match (i < a.len(), j < b.len()) {
+            (false, false) => return Ordering::Equal,
+            (false, true) => return Ordering::Less,
+            (true, false) => return Ordering::Greater,
+            _ => {}
+        }
+
+        let a_digit = a[i].is_ascii_digit();
+        let b_digit = b[j].is_ascii_digit();
+        if a_digit != b_digit {
+            // Numbers are newer than words ("6.6.1" > "6.6.rc")
+            return if a_digit {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let start_a = i;
+        let start_b = j;
+        if a_digit {
+            while i < a.len() && a[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+            let na = trim_zeros(&a[start_a..i]);
+            let nb = trim_zeros(&b[start_b..j]);
+            let ord = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        } else {
+            let word = |c: u8| !c.is_ascii_digit() && !is_sep(c) && c != b'~';
+            while i < a.len() && word(a[i]) {
+                i += 1;
+            }
+            while j < b.len() && word(b[j]) {
+                j += 1;
+            }
+            let ord = a[start_a..i].cmp(&b[start_b..j]);
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+    }
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let first = digits
+        .iter()
+        .position(|&d| d != b'0')
+        .unwrap_or(digits.len());
+    &digits[first..]
+}
+
+// =============================================================================
+// Versioned Boot Files
+// =============================================================================
+
+/// Boot file names for one kernel version of one OS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedBootFiles {
+    /// OS identifier used as the BLS entry token (e.g., "levitateos")
+    pub os_id: &'static str,
+    /// Kernel release
+    pub version: KernelVersion,
+}
+
+impl VersionedBootFiles {
+    /// Names for `version` of `os_id`.
+    pub fn new(os_id: &'static str, version: KernelVersion) -> Self {
+        Self { os_id, version }
+    }
+
+    /// Kernel filename: `vmlinuz-<kver>`.
+    pub fn kernel_filename(&self) -> String {
+        format!("{}-{}", KERNEL_FILENAME, self.version)
+    }
+
+    /// Initramfs filename: `initramfs-<kver>.img`.
+    pub fn initramfs_filename(&self) -> String {
+        let stem = INITRAMFS_FILENAME.trim_end_matches(".img");
+        format!("{}-{}.img", stem, self.version)
+    }
+
+    /// BLS entry ID and entry filename stem: `<os_id>-<kver>`.
+    pub fn entry_id(&self) -> String {
+        format!("{}-{}", self.os_id, self.version)
+    }
+
+    /// UKI filename: `<os_id>-<kver>.efi`.
+    pub fn uki_filename(&self) -> String {
+        format!("{}.efi", self.entry_id())
+    }
+
+    /// Recovery UKI filename: `recovery-<os_id>-<kver>.efi`.
+    pub fn recovery_uki_filename(&self) -> String {
+        format!("{}{}", RECOVERY_PREFIX, self.uki_filename())
+    }
+
+    /// Every file on the ESP (relative to it) that belongs to this version.
+    pub fn esp_files(&self) -> Vec<String> {
+        vec![
+            self.kernel_filename(),
+            self.initramfs_filename(),
+            format!("{}/{}", UKI_EFI_DIR, self.uki_filename()),
+            format!("{}/{}", UKI_EFI_DIR, self.recovery_uki_filename()),
+            format!("loader/entries/{}.conf", self.entry_id()),
+        ]
+    }
+
+    /// Type #1 boot entry for this version.
+    pub fn boot_entry(&self, os_name: &str, root_device: impl Into<String>) -> BootEntry {
+        let mut entry = BootEntry::with_root(
+            self.os_id,
+            os_name,
+            &self.kernel_filename(),
+            &self.initramfs_filename(),
+            root_device,
+        );
+        entry.filename = self.entry_id().into();
+        entry.title = format!("{} ({})", os_name, self.version).into();
+        entry
+    }
+
+    /// Parse a UKI filename back into its version and whether it is the recovery image.
+    ///
+    /// Returns `None` for files of another OS or the unversioned legacy name.
+    pub fn parse_uki_filename(os_id: &str, filename: &str) -> Option<(KernelVersion, bool)> {
+        let (stem, recovery) = match filename.strip_prefix(RECOVERY_PREFIX) {
+            Some(stem) => (stem, true),
+            None => (filename, false),
+        };
+        let kver = stem
+            .strip_suffix(".efi")?
+            .strip_prefix(os_id)?
+            .strip_prefix('-')?;
+        Some((KernelVersion::parse(kver)?, recovery))
+    }
+}
+
+// =============================================================================
+// Retention Policy
+// =============================================================================
+
+/// Which installed kernel versions to keep on the ESP.
+///
+/// At most `keep` versions stay, chosen in this order: the newest version
+/// (the new default), the running kernel (known to boot), pinned versions,
+/// then the other versions, newest first. The newest and running kernels
+/// are kept even when `keep` is smaller than two. The default keeps
+/// `RETAINED_KERNELS`, which is what the ESP is sized for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Maximum number of versions kept, pinned and running ones included
+    pub keep: usize,
+    /// Versions kept ahead of other older ones (e.g., an LTS kernel)
+    pub pinned: Vec<KernelVersion>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep: RETAINED_KERNELS,
+            pinned: Vec::new(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Keep at most `keep` versions.
+    pub fn new(keep: usize) -> Self {
+        Self {
+            keep,
+            pinned: Vec::new(),
+        }
+    }
+
+    /// Prefer `version` over other older versions.
+    pub fn with_pinned(mut self, version: KernelVersion) -> Self {
+        self.pinned.push(version);
+        self
+    }
+
+    /// Decide what to keep among `installed` while `running` is booted.
+    ///
+    /// `installed` should include a freshly installed kernel.
+    pub fn plan(
+        &self,
+        installed: &[KernelVersion],
+        running: Option<&KernelVersion>,
+    ) -> RetentionPlan {
+        let mut all: Vec<KernelVersion> = installed.to_vec();
+        all.sort_by(|a, b| b.cmp(a));
+        all.dedup();
+
+        let mut keep: Vec<KernelVersion> = Vec::new();
+        let required = all
+            .first()
+            .into_iter()
+            .chain(all.iter().filter(|v| Some(*v) == running));
+        for v in required {
+            if !keep.contains(v) {
+                keep.push(v.clone());
+            }
+        }
+        let pinned = all.iter().filter(|v| self.pinned.contains(v));
+        for v in pinned.chain(&all) {
+            if keep.len() >= self.keep {
+                break;
+            }
+            if !keep.contains(v) {
+                keep.push(v.clone());
+            }
+        }
+        keep.sort_by(|a, b| b.cmp(a));
+
+        let remove = all.iter().filter(|v| !keep.contains(v)).cloned().collect();
+        let default = keep.first().cloned();
+        let fallback = match running {
+            Some(r) if Some(r) != default.as_ref() && keep.contains(r) => Some(r.clone()),
+            _ => keep.get(1).cloned(),
+        };
+        RetentionPlan {
+            keep,
+            remove,
+            default,
+            fallback,
+        }
+    }
+}
+
+/// Outcome of a retention decision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPlan {
+    /// Versions to keep, newest first
+    pub keep: Vec<KernelVersion>,
+    /// Versions whose files should be deleted
+    pub remove: Vec<KernelVersion>,
+    /// Version to boot by default (the newest)
+    pub default: Option<KernelVersion>,
+    /// Version to fall back to (the running kernel if it isn't the default)
+    pub fallback: Option<KernelVersion>,
+}
+
+impl RetentionPlan {
+    /// ESP-relative files to delete for `os_id`.
+    pub fn files_to_remove(&self, os_id: &'static str) -> Vec<String> {
+        self.remove
+            .iter()
+            .flat_map(|v| VersionedBootFiles::new(os_id, v.clone()).esp_files())
+            .collect()
+    }
+
+    /// loader.conf `default` value selecting the default version's UKI.
+    pub fn default_entry(&self, os_id: &'static str) -> Option<String> {
+        self.default
+            .as_ref()
+            .map(|v| VersionedBootFiles::new(os_id, v.clone()).uki_filename())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::esp::EspBudget;
+
+    fn v(s: &str) -> KernelVersion {
+        KernelVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(v("6.12.10") > v("6.12.9"));
+        assert!(v("6.12.1") > v("6.6.30"));
+        assert!(v("6.6.30-lts") > v("6.6.30"));
+        assert!(v("6.13~rc1") < v("6.13"));
+        assert!(v("6.6.30-1-lts") < v("6.6.30-2-lts"));
+        assert_eq!(compare_versions("6.06", "6.6"), Ordering::Equal);
+        // Total order consistent with Eq
+        assert_eq!(v("6.06").cmp(&v("6.6")), Ordering::Less);
+        assert_eq!(v("6.6").cmp(&v("6.06")), Ordering::Greater);
+        assert_eq!(v("6.6").cmp(&v("6.6")), Ordering::Equal);
+        let mut versions = vec![v("6.6"), v("6.06"), v("6.6.0"), v("6.06")];
+        versions.sort();
+        versions.dedup();
+        assert_eq!(versions, [v("6.06"), v("6.6"), v("6.6.0")]);
+        assert!(KernelVersion::parse("vmlinuz").is_none());
+        assert!(KernelVersion::parse("6.1/x").is_none());
+    }
+
+    #[test]
+    fn test_versioned_names() {
+        let files = VersionedBootFiles::new("levitateos", v("6.12.5-levitate"));
+        assert_eq!(files.kernel_filename(), "vmlinuz-6.12.5-levitate");
+        assert_eq!(files.initramfs_filename(), "initramfs-6.12.5-levitate.img");
+        assert_eq!(files.uki_filename(), "levitateos-6.12.5-levitate.efi");
+        assert_eq!(
+            files.recovery_uki_filename(),
+            "recovery-levitateos-6.12.5-levitate.efi"
+        );
+
+        let entry = files.boot_entry("LevitateOS", "LABEL=root");
+        assert_eq!(
+            entry.entry_path(),
+            "/boot/loader/entries/levitateos-6.12.5-levitate.conf"
+        );
+        assert!(entry
+            .to_entry_file()
+            .contains("linux   /vmlinuz-6.12.5-levitate\n"));
+
+        assert_eq!(
+            VersionedBootFiles::parse_uki_filename("acornos", "recovery-acornos-6.6.30-0-lts.efi"),
+            Some((v("6.6.30-0-lts"), true))
+        );
+        assert_eq!(
+            VersionedBootFiles::parse_uki_filename("acornos", "acornos-6.6.30-0-lts.efi"),
+            Some((v("6.6.30-0-lts"), false))
+        );
+        assert_eq!(
+            VersionedBootFiles::parse_uki_filename("levitateos", "levitateos.efi"),
+            None
+        );
+        assert_eq!(
+            VersionedBootFiles::parse_uki_filename("levitateos", "acornos-6.1.efi"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_retention_keeps_running_kernel() {
+        let installed = [
+            v("6.12.1"),
+            v("6.12.3"),
+            v("6.11.9"),
+            v("6.12.2"),
+            v("6.10.1"),
+        ];
+        let plan = RetentionPolicy::default().plan(&installed, Some(&v("6.10.1")));
+        // The running kernel takes one of the three slots
+        assert_eq!(plan.keep, vec![v("6.12.3"), v("6.12.2"), v("6.10.1")]);
+        assert_eq!(plan.remove, vec![v("6.12.1"), v("6.11.9")]);
+        assert_eq!(plan.default, Some(v("6.12.3")));
+        assert_eq!(plan.fallback, Some(v("6.10.1")));
+        assert_eq!(
+            plan.default_entry("levitateos").as_deref(),
+            Some("levitateos-6.12.3.efi")
+        );
+        assert!(plan
+            .files_to_remove("levitateos")
+            .contains(&"EFI/Linux/levitateos-6.11.9.efi".to_string()));
+
+        // Running the newest kernel leaves the other slot to an older one
+        let plan = RetentionPolicy::new(2).plan(&installed, Some(&v("6.12.3")));
+        assert_eq!(plan.keep, vec![v("6.12.3"), v("6.12.2")]);
+    }
+
+    #[test]
+    fn test_retention_prefers_pinned() {
+        let installed = [v("6.12.3"), v("6.12.2"), v("6.12.1"), v("6.1.100-lts")];
+        let plan = RetentionPolicy::new(3)
+            .with_pinned(v("6.1.100-lts"))
+            .plan(&installed, None);
+        assert_eq!(plan.keep, vec![v("6.12.3"), v("6.12.2"), v("6.1.100-lts")]);
+        assert_eq!(plan.remove, vec![v("6.12.1")]);
+        assert_eq!(plan.fallback, Some(v("6.12.2")));
+    }
+
+    #[test]
+    fn test_retention_pinned_and_minimum() {
+        let installed = [v("6.12.3"), v("6.6.30-lts"), v("6.12.2")];
+        let plan = RetentionPolicy::new(2)
+            .with_pinned(v("6.6.30-lts"))
+            .plan(&installed, None);
+        assert_eq!(plan.keep, vec![v("6.12.3"), v("6.6.30-lts")]);
+        assert_eq!(plan.remove, vec![v("6.12.2")]);
+        assert_eq!(plan.fallback, Some(v("6.6.30-lts")));
+
+        // keep = 0 still keeps the default
+        let plan = RetentionPolicy::new(0).plan(&installed, None);
+        assert_eq!(plan.keep, vec![v("6.12.3")]);
+        assert_eq!(plan.fallback, None);
+    }
+
+    #[test]
+    fn test_retention_fits_esp_budget() {
+        // Worst case: an old running kernel and more pins than slots
+        let installed: Vec<_> = [
+            "6.12.5",
+            "6.12.4",
+            "6.12.3",
+            "6.6.30-lts",
+            "6.1.100-lts",
+            "6.0.1",
+        ]
+        .into_iter()
+        .map(v)
+        .collect();
+        let policy = RetentionPolicy::default()
+            .with_pinned(v("6.6.30-lts"))
+            .with_pinned(v("6.1.100-lts"));
+        for running in &installed {
+            let plan = policy.plan(&installed, Some(running));
+            assert_eq!(plan.keep.len(), RETAINED_KERNELS);
+            assert!(plan.keep.contains(running));
+            assert_eq!(plan.default, Some(v("6.12.5")));
+        }
+        assert_eq!(EspBudget::installed().retained_kernels, RETAINED_KERNELS);
+    }
+}
@@ -0,0 +1,532 @@
+//! License bundle for a finished rootfs.
+//!
+//! `licenses.rs` maps binaries and libraries to source packages; this module
+//! does the copying. `LicenseCollector` classifies every file of a rootfs,
+//! looks up its package, copies `usr/share/licenses/<package>/` from the
+//! source sysroot and writes a `LICENSES` index listing which shipped files
+//! each package covers. Symlinks are resolved inside the rootfs and mapped
+//! by what they point to, so `usr/bin/sh -> bash` is covered by bash.
+//!
+//! Collection fails when a binary or library has no package mapping or the
+//! package ships no license files, so an image cannot be built with a
+//! silently incomplete bundle. Files that are neither (configuration, data,
+//! plugins such as `pam_unix.so`) are not checked.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use super::licenses::{package_for_binary, package_for_library};
+use super::rootfs_manifest::{resolve_traced, Resolved};
+
+/// Directory holding per-package license directories (rootfs-relative).
+pub const LICENSE_DIR: &str = "usr/share/licenses";
+
+/// Consolidated index written by `LicenseCollector::bundle` (rootfs-relative).
+pub const LICENSE_INDEX: &str = "usr/share/licenses/LICENSES";
+
+/// Directories whose direct children are binaries, if executable or ELF.
+pub const BINARY_DIRS: &[&str] = &[
+    "usr/bin",
+    "usr/sbin",
+    "bin",
+    "sbin",
+    "usr/lib/systemd",
+    "usr/libexec",
+];
+
+/// How a shipped file is mapped to a package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// Looked up with `package_for_binary`
+    Binary,
+    /// Looked up with `package_for_library`
+    Library,
+}
+
+impl FileKind {
+    /// Lowercase name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::Binary => "binary",
+            FileKind::Library => "library",
+        }
+    }
+}
+
+/// Why a shipped file is not covered by a license.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseProblem {
+    /// No entry in `BINARY_TO_PACKAGE`/`LIB_TO_PACKAGE`
+    Unmapped { path: String, kind: FileKind },
+    /// The package's license directory is missing or empty in the source
+    MissingLicense {
+        package: &'static str,
+        needed_by: Vec<String>,
+    },
+}
+
+impl fmt::Display for LicenseProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseProblem::Unmapped { path, kind } => {
+                write!(f, "{}: {} has no package mapping", path, kind.name())
+            }
+            LicenseProblem::MissingLicense { package, needed_by } => write!(
+                f,
+                "{}: no files in {}/{} (needed by {})",
+                package,
+                LICENSE_DIR,
+                package,
+                needed_by.join(", ")
+            ),
+        }
+    }
+}
+
+/// Why a license bundle could not be produced.
+#[derive(Debug)]
+pub enum LicenseError {
+    /// Reading the source or writing the rootfs failed
+    Io { path: PathBuf, source: io::Error },
+    /// Shipped files without a license
+    Incomplete(Vec<LicenseProblem>),
+}
+
+impl fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            LicenseError::Incomplete(problems) => {
+                write!(f, "{} files without a license:", problems.len())?;
+                for p in problems {
+                    write!(f, "\n  {}", p)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LicenseError {}
+
+fn io_err(path: &Path) -> impl FnOnce(io::Error) -> LicenseError + '_ {
+    move |source| LicenseError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// License files of one package and the shipped files they cover.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageLicense {
+    /// License files in the source, relative to its license directory
+    pub files: Vec<String>,
+    /// Shipped files mapped to the package (rootfs-relative)
+    pub covers: BTreeSet<String>,
+}
+
+/// Licenses needed by a file set, keyed by package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicensePlan {
+    /// Package → license files and covered files
+    pub packages: BTreeMap<&'static str, PackageLicense>,
+}
+
+impl LicensePlan {
+    /// Render the `LICENSES` index: one stanza per package.
+    pub fn index(&self) -> String {
+        let mut out = String::from("# Licenses of the packages redistributed in this image\n");
+        for (package, license) in &self.packages {
+            out.push_str(&format!("\nPackage: {}\nLicense-Files:\n", package));
+            for file in &license.files {
+                out.push_str(&format!(" {}/{}/{}\n", LICENSE_DIR, package, file));
+            }
+            out.push_str("Files:\n");
+            for file in &license.covers {
+                out.push_str(&format!(" {}\n", file));
+            }
+        }
+        out
+    }
+}
+
+/// Gathers license directories for a rootfs from a source sysroot.
+#[derive(Debug, Clone)]
+pub struct LicenseCollector {
+    source: PathBuf,
+    binary_dirs: Vec<String>,
+}
+
+impl LicenseCollector {
+    /// Collector copying licenses from the sysroot at `source`.
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        Self {
+            source: source.into(),
+            binary_dirs: BINARY_DIRS.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    /// Treat direct children of these directories as binaries instead of
+    /// `BINARY_DIRS`.
+    pub fn with_binary_dirs(mut self, dirs: &[&str]) -> Self {
+        self.binary_dirs = dirs
+            .iter()
+            .map(|d| d.trim_matches('/').to_string())
+            .collect();
+        self
+    }
+
+    /// How `path` (rootfs-relative) is mapped, if it is mapped at all.
+    /// Judged by the path alone; `bundle` also requires binaries to be
+    /// executable or ELF.
+    pub fn classify(&self, path: &str) -> Option<FileKind> {
+        let path = path.trim_start_matches('/');
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        if (name.starts_with("lib") || name.starts_with("ld-")) && name.contains(".so") {
+            Some(FileKind::Library)
+        } else if self.binary_dirs.iter().any(|d| d == dir) {
+            Some(FileKind::Binary)
+        } else {
+            None
+        }
+    }
+
+    /// Licenses needed by `files` (rootfs-relative paths, taken as they
+    /// are). Reads the source license directories but writes nothing.
+    pub fn plan<I, S>(&self, files: I) -> Result<LicensePlan, LicenseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let shipped = files.into_iter().filter_map(|file| {
+            let path = file.as_ref().trim_start_matches('/');
+            self.classify(path)
+                .map(|kind| (path.to_string(), kind, path.to_string()))
+        });
+        self.plan_shipped(shipped)
+    }
+
+    /// `plan` over `(shipped path, kind, resolved path)`: the package is
+    /// looked up by the resolved file, the shipped path is what it covers.
+    fn plan_shipped(
+        &self,
+        shipped: impl IntoIterator<Item = (String, FileKind, String)>,
+    ) -> Result<LicensePlan, LicenseError> {
+        let mut problems = Vec::new();
+        let mut plan = LicensePlan::default();
+        for (path, kind, target) in shipped {
+            let name = target.rsplit('/').next().unwrap_or(&target);
+            let package = match kind {
+                FileKind::Binary => package_for_binary(name),
+                FileKind::Library => package_for_library(name),
+            };
+            match package {
+                Some(package) => {
+                    plan.packages
+                        .entry(package)
+                        .or_default()
+                        .covers
+                        .insert(path);
+                }
+                None => problems.push(LicenseProblem::Unmapped { path, kind }),
+            }
+        }
+
+        for (package, license) in &mut plan.packages {
+            license.files = self.license_files(package)?;
+            if license.files.is_empty() {
+                problems.push(LicenseProblem::MissingLicense {
+                    package,
+                    needed_by: license.covers.iter().cloned().collect(),
+                });
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(plan)
+        } else {
+            Err(LicenseError::Incomplete(problems))
+        }
+    }
+
+    /// Plan the licenses for every file in the rootfs at `rootfs`, copy the
+    /// license directories into it and write `LICENSE_INDEX`.
+    pub fn bundle(&self, rootfs: impl AsRef<Path>) -> Result<LicensePlan, LicenseError> {
+        let rootfs = rootfs.as_ref();
+        let mut files = Vec::new();
+        walk(rootfs, "", &mut files)?;
+        let mut shipped = Vec::new();
+        for file in files {
+            if let Some((kind, target)) = self.inspect(rootfs, &file)? {
+                shipped.push((file, kind, target));
+            }
+        }
+        let plan = self.plan_shipped(shipped)?;
+
+        for (package, license) in &plan.packages {
+            for file in &license.files {
+                let rel = format!("{}/{}/{}", LICENSE_DIR, package, file);
+                let from = self.locate(&rel)?.ok_or_else(|| LicenseError::Io {
+                    path: self.source.join(&rel),
+                    source: io::ErrorKind::NotFound.into(),
+                })?;
+                let to = rootfs.join(&rel);
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent).map_err(io_err(parent))?;
+                }
+                fs::copy(&from, &to).map_err(io_err(&to))?;
+            }
+        }
+        let index = rootfs.join(LICENSE_INDEX);
+        if let Some(parent) = index.parent() {
+            fs::create_dir_all(parent).map_err(io_err(parent))?;
+        }
+        fs::write(&index, plan.index()).map_err(io_err(&index))?;
+        Ok(plan)
+    }
+
+    /// Kind and resolved rootfs-relative path of the file at `rel` in
+    /// `rootfs`. Symlinks are followed inside the rootfs; dangling links,
+    /// directories and binaries that are neither executable nor ELF (such
+    /// as `usr/lib/systemd/resolv.conf`) are not mapped.
+    fn inspect(
+        &self,
+        rootfs: &Path,
+        rel: &str,
+    ) -> Result<Option<(FileKind, String)>, LicenseError> {
+        let host = match resolve_traced(rootfs, rel, &mut Vec::new()) {
+            Ok(Resolved::Found(host)) => host,
+            Ok(_) => return Ok(None),
+            Err(source) => {
+                return Err(LicenseError::Io {
+                    path: rootfs.join(rel),
+                    source,
+                })
+            }
+        };
+        let meta = fs::metadata(&host).map_err(io_err(&host))?;
+        if !meta.is_file() {
+            return Ok(None);
+        }
+        let target = host
+            .strip_prefix(rootfs)
+            .unwrap_or(&host)
+            .to_string_lossy()
+            .into_owned();
+        let kind = match self.classify(&target) {
+            Some(FileKind::Binary) if meta.permissions().mode() & 0o111 == 0 && !is_elf(&host)? => {
+                None
+            }
+            kind => kind,
+        };
+        Ok(kind.map(|kind| (kind, target)))
+    }
+
+    /// Regular files under the package's license directory in the source,
+    /// sorted; empty if the directory does not exist.
+    fn license_files(&self, package: &str) -> Result<Vec<String>, LicenseError> {
+        let rel = format!("{}/{}", LICENSE_DIR, package);
+        let Some(dir) = self.locate(&rel)? else {
+            return Ok(Vec::new());
+        };
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        walk(&dir, "", &mut entries)?;
+        let mut files = Vec::new();
+        for entry in entries {
+            // License files may be symlinks (into another package's directory)
+            if let Some(path) = self.locate(&format!("{}/{}", rel, entry))? {
+                if path.is_file() {
+                    files.push(entry);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Host path of `rel` in the source, symlinks resolved inside it.
+    fn locate(&self, rel: &str) -> Result<Option<PathBuf>, LicenseError> {
+        match resolve_traced(&self.source, rel, &mut Vec::new()) {
+            Ok(Resolved::Found(path)) => Ok(Some(path)),
+            Ok(_) => Ok(None),
+            Err(e) => Err(LicenseError::Io {
+                path: self.source.join(rel),
+                source: e,
+            }),
+        }
+    }
+}
+
+/// Whether the file at `path` starts with the ELF magic.
+fn is_elf(path: &Path) -> Result<bool, LicenseError> {
+    let mut magic = [0u8; 4];
+    let mut file = fs::File::open(path).map_err(io_err(path))?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == b"\x7fELF"),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(io_err(path)(e)),
+    }
+}
+
+/// Every non-directory entry under `dir`, relative to the walk start.
+/// Symlinks are listed, not followed.
+fn walk(dir: &Path, prefix: &str, out: &mut Vec<String>) -> Result<(), LicenseError> {
+    for entry in fs::read_dir(dir).map_err(io_err(dir))? {
+        let entry = entry.map_err(io_err(dir))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let rel = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let file_type = entry.file_type().map_err(io_err(&entry.path()))?;
+        if file_type.is_dir() {
+            walk(&entry.path(), &rel, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    fn source() -> TempDir {
+        let dir = TempDir::new("license-source");
+        dir.write("usr/share/licenses/glibc/COPYING", "GPL");
+        dir.write("usr/share/licenses/glibc/COPYING.LIB", "LGPL");
+        dir.write("usr/share/licenses/bash/COPYING", "GPL");
+        dir.write("usr/share/licenses/coreutils-common/COPYING", "GPL");
+        dir.mkdir("usr/share/licenses/systemd");
+        dir
+    }
+
+    fn executable(dir: &TempDir, rel: &str) {
+        let path = dir.write(rel, "#!/bin/true\n");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_classify() {
+        let c = LicenseCollector::new("/");
+        assert_eq!(c.classify("usr/bin/ls"), Some(FileKind::Binary));
+        assert_eq!(
+            c.classify("/usr/lib/systemd/systemd-journald"),
+            Some(FileKind::Binary)
+        );
+        assert_eq!(c.classify("usr/lib64/libc.so.6"), Some(FileKind::Library));
+        assert_eq!(
+            c.classify("usr/lib64/ld-linux-x86-64.so.2"),
+            Some(FileKind::Library)
+        );
+        assert_eq!(c.classify("usr/lib64/security/pam_unix.so"), None);
+        assert_eq!(c.classify("etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_plan_fails_on_gaps() {
+        let src = source();
+        let err = LicenseCollector::new(src.path())
+            .plan([
+                "usr/bin/bash",
+                "usr/bin/frobnicate",
+                "usr/bin/systemctl",
+                "etc/hostname",
+            ])
+            .unwrap_err();
+        let LicenseError::Incomplete(problems) = err else {
+            panic!("expected Incomplete");
+        };
+        assert_eq!(
+            problems,
+            vec![
+                LicenseProblem::Unmapped {
+                    path: "usr/bin/frobnicate".into(),
+                    kind: FileKind::Binary
+                },
+                LicenseProblem::MissingLicense {
+                    package: "systemd",
+                    needed_by: vec!["usr/bin/systemctl".into()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bundle() {
+        let src = source();
+        src.symlink(
+            "usr/share/licenses/bash/LICENSE",
+            "/usr/share/licenses/glibc/COPYING",
+        );
+        let rootfs = TempDir::new("license-rootfs");
+        executable(&rootfs, "usr/bin/bash");
+        executable(&rootfs, "usr/bin/ls");
+        rootfs.write("usr/lib64/libc.so.6", "");
+        rootfs.symlink("usr/lib64/libc.so", "libc.so.6");
+        rootfs.write("etc/hostname", "host\n");
+
+        let plan = LicenseCollector::new(src.path())
+            .bundle(rootfs.path())
+            .unwrap();
+        assert_eq!(
+            plan.packages.keys().copied().collect::<Vec<_>>(),
+            vec!["bash", "coreutils-common", "glibc"]
+        );
+        assert_eq!(plan.packages["glibc"].files, vec!["COPYING", "COPYING.LIB"]);
+        assert_eq!(plan.packages["glibc"].covers.len(), 2);
+        assert_eq!(plan.packages["bash"].files, vec!["COPYING", "LICENSE"]);
+
+        let licenses = rootfs.path().join(LICENSE_DIR);
+        assert_eq!(
+            fs::read_to_string(licenses.join("bash/LICENSE")).unwrap(),
+            "GPL"
+        );
+        assert!(licenses.join("glibc/COPYING.LIB").is_file());
+        let index = fs::read_to_string(rootfs.path().join(LICENSE_INDEX)).unwrap();
+        assert_eq!(index, plan.index());
+        assert!(
+            index.contains("Package: glibc\nLicense-Files:\n usr/share/licenses/glibc/COPYING\n")
+        );
+        assert!(index.contains(" usr/lib64/libc.so\n usr/lib64/libc.so.6\n"));
+    }
+
+    #[test]
+    fn test_bundle_resolves_symlinks() {
+        let src = source();
+        let rootfs = TempDir::new("license-rootfs-links");
+        executable(&rootfs, "usr/bin/bash");
+        rootfs.symlink("usr/bin/sh", "bash");
+        rootfs.symlink("bin", "usr/bin");
+        rootfs.symlink("usr/bin/gone", "/usr/bin/missing");
+        rootfs.write("usr/lib/systemd/resolv.conf", "nameserver 127.0.0.53\n");
+        rootfs.write("usr/lib64/libc.so.6", b"\x7fELF\x02\x01");
+        rootfs.symlink("usr/lib64/libc.so", "/usr/lib64/libc.so.6");
+
+        let plan = LicenseCollector::new(src.path())
+            .bundle(rootfs.path())
+            .unwrap();
+        assert_eq!(
+            plan.packages.keys().copied().collect::<Vec<_>>(),
+            vec!["bash", "glibc"]
+        );
+        assert_eq!(
+            plan.packages["bash"].covers.iter().collect::<Vec<_>>(),
+            ["usr/bin/bash", "usr/bin/sh"]
+        );
+        assert_eq!(
+            plan.packages["glibc"].covers.iter().collect::<Vec<_>>(),
+            ["usr/lib64/libc.so", "usr/lib64/libc.so.6"]
+        );
+    }
+}
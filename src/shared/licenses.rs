@@ -3,11 +3,26 @@
 //! Maps binaries and libraries to their source package names for license compliance.
 //! When we copy a binary or library, we need to also copy the corresponding
 //! license files from `/usr/share/licenses/<package>/`.
+//!
+//! Mappings for registered components live with the component in `registry.rs`;
+//! `license_bundle.rs` copies the license directories into a rootfs.
+
+use super::const_check::static_check;
+use super::lookup::StaticMap;
+use super::registry::pairs_view;
 
 /// Binary name → package name mapping.
 ///
 /// Used to determine which license directory to copy when a binary is included.
-pub const BINARY_TO_PACKAGE: &[(&str, &str)] = &[
+/// Core entries followed by the `binary_packages` of every registered component.
+pub const BINARY_TO_PACKAGE: &[(&str, &str)] = pairs_view!(
+    Part::List(CORE_BINARY_TO_PACKAGE),
+    Part::Field(PairField::BinaryPackages),
+);
+static_check!(package_map BINARY_TO_PACKAGE);
+
+/// Binary mappings that are not part of a registered component.
+const CORE_BINARY_TO_PACKAGE: &[(&str, &str)] = &[
     // === COREUTILS ===
     ("ls", "coreutils-common"),
     ("cat", "coreutils-common"),
@@ -123,6 +138,13 @@ pub const BINARY_TO_PACKAGE: &[(&str, &str)] = &[
     ("systemd-resolved", "systemd"),
     ("systemd-udevd", "systemd"),
     ("systemd-fsck", "systemd"),
+    ("systemd-makefs", "systemd"),
+    ("systemd-bless-boot", "systemd"),
+    // Power commands (symlinks to systemctl)
+    ("reboot", "systemd"),
+    ("shutdown", "systemd"),
+    ("poweroff", "systemd"),
+    ("halt", "systemd"),
     // Udev helpers (part of systemd)
     ("ata_id", "systemd"),
     ("scsi_id", "systemd"),
@@ -273,28 +295,6 @@ pub const BINARY_TO_PACKAGE: &[(&str, &str)] = &[
     ("sudoedit", "sudo"),
     ("sudoreplay", "sudo"),
     ("visudo", "sudo"),
-    // === OPENSSH ===
-    ("ssh", "openssh"),
-    ("scp", "openssh"),
-    ("sftp", "openssh"),
-    ("ssh-keygen", "openssh"),
-    ("ssh-add", "openssh"),
-    ("ssh-agent", "openssh"),
-    ("sshd", "openssh-server"),
-    // === NETWORKMANAGER ===
-    ("nmcli", "NetworkManager"),
-    ("nm-online", "NetworkManager"),
-    ("nmtui", "NetworkManager"),
-    ("NetworkManager", "NetworkManager"),
-    // === WPA_SUPPLICANT ===
-    ("wpa_supplicant", "wpa_supplicant"),
-    ("wpa_cli", "wpa_supplicant"),
-    ("wpa_passphrase", "wpa_supplicant"),
-    // === DBUS-BROKER ===
-    ("dbus-broker", "dbus-broker"),
-    ("dbus-broker-launch", "dbus-broker"),
-    // === CHRONY ===
-    ("chronyd", "chrony"),
     // === GZIP ===
     ("gzip", "gzip"),
     ("gunzip", "gzip"),
@@ -411,48 +411,23 @@ pub const BINARY_TO_PACKAGE: &[(&str, &str)] = &[
     ("7zr", "p7zip"),
     // === TREE ===
     ("tree", "tree"),
-    // === BLUEZ ===
-    ("bluetoothctl", "bluez"),
-    ("bluetoothd", "bluez"),
-    // === PIPEWIRE ===
-    ("pw-cli", "pipewire"),
-    ("pw-dump", "pipewire"),
-    ("pw-cat", "pipewire"),
-    ("pw-play", "pipewire"),
-    ("pw-record", "pipewire"),
-    ("pw-top", "pipewire"),
-    ("pw-metadata", "pipewire"),
-    ("pw-mon", "pipewire"),
-    ("pw-link", "pipewire"),
-    ("pipewire", "pipewire"),
-    ("pipewire-pulse", "pipewire-pulseaudio"),
-    // === WIREPLUMBER ===
-    ("wpctl", "wireplumber"),
-    ("wireplumber", "wireplumber"),
-    // === PULSEAUDIO (compatibility tools from pipewire-pulseaudio) ===
-    ("pactl", "pipewire-pulseaudio"),
-    ("pacmd", "pipewire-pulseaudio"),
-    ("paplay", "pipewire-pulseaudio"),
-    ("parecord", "pipewire-pulseaudio"),
-    // === POLKIT ===
-    ("pkexec", "polkit"),
-    ("pkaction", "polkit"),
-    ("pkcheck", "polkit"),
-    ("polkitd", "polkit"),
-    // === UDISKS2 ===
-    ("udisksctl", "udisks2"),
-    ("udisksd", "udisks2"),
-    // === UPOWER ===
-    ("upower", "upower"),
-    ("upowerd", "upower"),
     // === LINUX-PAM ===
     ("unix_chkpwd", "pam"),
 ];
 
 /// Library prefix → package name mapping.
 ///
-/// Libraries are matched by prefix (e.g., "libc.so" matches "libc.so.6").
-pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
+/// Libraries are matched by longest prefix (e.g., "libc.so" matches "libc.so.6"
+/// but not "libcap.so.2"), so the order of entries does not matter.
+/// Core entries followed by the `library_packages` of every registered component.
+pub const LIB_TO_PACKAGE: &[(&str, &str)] = pairs_view!(
+    Part::List(CORE_LIB_TO_PACKAGE),
+    Part::Field(PairField::LibraryPackages),
+);
+static_check!(package_map LIB_TO_PACKAGE);
+
+/// Library mappings that are not part of a registered component.
+const CORE_LIB_TO_PACKAGE: &[(&str, &str)] = &[
     // === GLIBC ===
     ("libc.so", "glibc"),
     ("libpthread.so", "glibc"),
@@ -464,6 +439,7 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libnss_dns.so", "glibc"),
     ("libnss_compat.so", "glibc"),
     ("ld-linux-x86-64.so", "glibc"),
+    ("ld-linux-aarch64.so", "glibc"),
     ("libcrypt.so", "libxcrypt"),
     // === SYSTEMD ===
     ("libsystemd.so", "systemd-libs"),
@@ -568,9 +544,6 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libgobject-2.0.so", "glib2"),
     ("libgmodule-2.0.so", "glib2"),
     ("libgthread-2.0.so", "glib2"),
-    // === POLKIT ===
-    ("libpolkit-gobject-1.so", "polkit-libs"),
-    ("libpolkit-agent-1.so", "polkit-libs"),
     // === FFI ===
     ("libffi.so", "libffi"),
     // === GCRYPT ===
@@ -593,12 +566,6 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libevent_core.so", "libevent"),
     // === ALSA ===
     ("libasound.so", "alsa-lib"),
-    // === PIPEWIRE ===
-    ("libpipewire-0.3.so", "pipewire-libs"),
-    // === SPA (part of pipewire) ===
-    ("libspa-0.2.so", "pipewire-libs"),
-    // === BLUETOOTH ===
-    ("libbluetooth.so", "bluez-libs"),
     // === PYTHON ===
     ("libpython3", "python3-libs"),
     // === PERL ===
@@ -615,8 +582,6 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libnl-route-3.so", "libnl3"),
     // === MM-GLIB ===
     ("libmm-glib.so", "ModemManager-glib"),
-    // === LIBNM ===
-    ("libnm.so", "NetworkManager-libnm"),
     // === LIBNDP ===
     ("libndp.so", "libndp"),
     // === NEWT ===
@@ -626,8 +591,6 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libpwquality.so", "libpwquality"),
     // === CRACKLIB ===
     ("libcrack.so", "cracklib"),
-    // === UDISKS2 ===
-    ("libudisks2.so", "udisks2-libs"),
     // === LIBATASMART ===
     ("libatasmart.so", "libatasmart"),
     // === LIBBYTESIZE ===
@@ -638,33 +601,32 @@ pub const LIB_TO_PACKAGE: &[(&str, &str)] = &[
     ("libparted.so", "parted-libs"),
     // === LIBGUDEV ===
     ("libgudev-1.0.so", "libgudev"),
-    // === UPOWER ===
-    ("libupower-glib.so", "upower"),
     // === LIBIMOBILEDEVICE ===
     ("libimobiledevice-1.0.so", "libimobiledevice"),
     ("libplist-2.0.so", "libplist"),
     ("libusbmuxd-2.0.so", "libusbmuxd"),
 ];
 
+/// `BINARY_TO_PACKAGE` hashed for lookup.
+static BINARY_INDEX: StaticMap<{ BINARY_TO_PACKAGE.len() }> = StaticMap::new(BINARY_TO_PACKAGE);
+
+/// `LIB_TO_PACKAGE` hashed for lookup.
+static LIB_INDEX: StaticMap<{ LIB_TO_PACKAGE.len() }> = StaticMap::new(LIB_TO_PACKAGE);
+
 /// Get the package name for a binary.
 ///
 /// Returns `None` if the binary is not in the mapping (can be added later).
 pub fn package_for_binary(binary: &str) -> Option<&'static str> {
-    BINARY_TO_PACKAGE
-        .iter()
-        .find(|(b, _)| *b == binary)
-        .map(|(_, pkg)| *pkg)
+    BINARY_INDEX.get(binary)
 }
 
 /// Get the package name for a library.
 ///
-/// Libraries are matched by prefix (e.g., "libc.so.6" matches "libc.so").
+/// Libraries are matched by longest prefix (e.g., "libc.so.6" matches
+/// "libc.so", "libcap.so.2" matches "libcap.so").
 /// Returns `None` if the library is not in the mapping (can be added later).
 pub fn package_for_library(lib: &str) -> Option<&'static str> {
-    LIB_TO_PACKAGE
-        .iter()
-        .find(|(prefix, _)| lib.starts_with(prefix))
-        .map(|(_, pkg)| *pkg)
+    LIB_INDEX.longest_prefix(lib)
 }
 
 #[cfg(test)]
@@ -685,6 +647,9 @@ mod tests {
         assert_eq!(package_for_library("libsystemd.so.0"), Some("systemd-libs"));
         assert_eq!(package_for_library("libpam.so.0"), Some("pam"));
         assert_eq!(package_for_library("libunknown.so.1"), None);
+        // Longest prefix wins regardless of table order
+        assert_eq!(package_for_library("libcap.so.2"), Some("libcap"));
+        assert_eq!(package_for_library("libcap-ng.so.0"), Some("libcap-ng"));
     }
 
     #[test]
@@ -0,0 +1,227 @@
+//! Hash tables built at compile time.
+//!
+//! The name → value tables (`BINARY_TO_PACKAGE`, `LIB_TO_PACKAGE`,
+//! `MODULE_PATHS`) are written in whatever order reads best. Lookups run
+//! once per file while a rootfs is assembled, so each table also gets a
+//! `StaticMap`: the same pairs bucketed by hash during const evaluation, so
+//! a lookup hashes the key once and compares against about one entry instead
+//! of scanning the table.
+//!
+//! ```rust
+//! use distro_spec::shared::lookup::StaticMap;
+//!
+//! static MAP: StaticMap<3> =
+//!     StaticMap::new(&[("libc.so", "glibc"), ("libcap.so", "libcap"), ("b", "x")]);
+//! assert_eq!(MAP.get("b"), Some("x"));
+//! assert_eq!(MAP.longest_prefix("libcap.so.2"), Some("libcap"));
+//! assert_eq!(MAP.longest_prefix("libc.so.6"), Some("glibc"));
+//! ```
+
+use smallvec::SmallVec;
+
+/// Keys must be shorter than this (one bit per length in `StaticMap::lengths`).
+pub const MAX_KEY_LEN: usize = 128;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// `&str` → `&str` map laid out at compile time.
+///
+/// `N` must equal the length of the source table; build it with
+/// `StaticMap::<{ TABLE.len() }>::new(TABLE)`. If a key is repeated, the
+/// first pair wins (the tables in this crate reject repeats at compile time).
+#[derive(Debug)]
+pub struct StaticMap<const N: usize> {
+    /// Pairs grouped by bucket, table order within a bucket
+    entries: [(&'static str, &'static str); N],
+    /// Index in `entries` of the first pair of each bucket
+    starts: [u32; N],
+    /// Bit `n` set if some key is `n` bytes long
+    lengths: u128,
+}
+
+impl<const N: usize> StaticMap<N> {
+    /// Bucket `pairs` by key hash.
+    ///
+    /// Panics (a compile error in a `static`) if `pairs.len() != N` or a key
+    /// is `MAX_KEY_LEN` bytes or longer.
+    pub const fn new(pairs: &[(&'static str, &'static str)]) -> Self {
+        assert!(pairs.len() == N, "StaticMap size does not match its table");
+        let mut counts = [0u32; N];
+        let mut lengths = 0u128;
+        let mut i = 0;
+        while i < N {
+            let key = pairs[i].0.as_bytes();
+            assert!(key.len() < MAX_KEY_LEN, "StaticMap key too long");
+            lengths |= 1 << key.len();
+            counts[bucket(hash(key), N)] += 1;
+            i += 1;
+        }
+
+        // Counting sort by bucket; stable, so the first of equal keys stays first
+        let mut starts = [0u32; N];
+        let mut next = [0u32; N];
+        let mut total = 0;
+        let mut b = 0;
+        while b < N {
+            starts[b] = total;
+            next[b] = total;
+            total += counts[b];
+            b += 1;
+        }
+        let mut entries = [("", ""); N];
+        let mut i = 0;
+        while i < N {
+            let b = bucket(hash(pairs[i].0.as_bytes()), N);
+            entries[next[b] as usize] = pairs[i];
+            next[b] += 1;
+            i += 1;
+        }
+        Self {
+            entries,
+            starts,
+            lengths,
+        }
+    }
+
+    /// Number of pairs.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Whether the map is empty.
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Value for exactly `key`.
+    pub fn get(&self, key: &str) -> Option<&'static str> {
+        let key = key.as_bytes();
+        if key.len() >= MAX_KEY_LEN || self.lengths & (1 << key.len()) == 0 {
+            return None;
+        }
+        self.find(key, hash(key))
+    }
+
+    /// Value of the longest key that is a prefix of `s`.
+    ///
+    /// Hashes `s` once, then probes the lengths some key has, longest first.
+    pub fn longest_prefix(&self, s: &str) -> Option<&'static str> {
+        let s = s.as_bytes();
+        let mut candidates: SmallVec<[(usize, u64); 32]> = SmallVec::new();
+        if self.lengths & 1 != 0 {
+            candidates.push((0, FNV_OFFSET));
+        }
+        let mut h = FNV_OFFSET;
+        for (i, &byte) in s.iter().enumerate().take(MAX_KEY_LEN - 1) {
+            h = step(h, byte);
+            if self.lengths & (1 << (i + 1)) != 0 {
+                candidates.push((i + 1, h));
+            }
+        }
+        candidates
+            .iter()
+            .rev()
+            .find_map(|&(len, h)| self.find(&s[..len], h))
+    }
+
+    fn find(&self, key: &[u8], h: u64) -> Option<&'static str> {
+        if N == 0 {
+            return None;
+        }
+        let b = bucket(h, N);
+        let start = self.starts[b] as usize;
+        let end = self.starts.get(b + 1).map_or(N, |&e| e as usize);
+        self.entries[start..end]
+            .iter()
+            .find(|(k, _)| k.as_bytes() == key)
+            .map(|(_, v)| *v)
+    }
+}
+
+/// FNV-1a, usable in const fns.
+const fn hash(bytes: &[u8]) -> u64 {
+    let mut h = FNV_OFFSET;
+    let mut i = 0;
+    while i < bytes.len() {
+        h = step(h, bytes[i]);
+        i += 1;
+    }
+    h
+}
+
+const fn step(h: u64, byte: u8) -> u64 {
+    (h ^ byte as u64).wrapping_mul(FNV_PRIME)
+}
+
+const fn bucket(h: u64, n: usize) -> usize {
+    (h % n as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIRS: &[(&str, &str)] = &[
+        ("libc.so", "glibc"),
+        ("libcap.so", "libcap"),
+        ("libcap-ng.so", "libcap-ng"),
+        ("libcrypt.so", "libxcrypt"),
+        ("libcrypto.so", "openssl-libs"),
+        ("zz", "last"),
+        ("libc.so", "duplicate"),
+    ];
+    static MAP: StaticMap<{ PAIRS.len() }> = StaticMap::new(PAIRS);
+
+    #[test]
+    fn test_exact() {
+        for (k, _) in PAIRS {
+            assert!(MAP.get(k).is_some(), "{}", k);
+        }
+        assert_eq!(MAP.get("zz"), Some("last"));
+        assert_eq!(MAP.get("libc.so"), Some("glibc"));
+        assert_eq!(MAP.get("libc"), None);
+        assert_eq!(MAP.get(""), None);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        assert_eq!(MAP.longest_prefix("libc.so.6"), Some("glibc"));
+        assert_eq!(MAP.longest_prefix("libcap.so.2.69"), Some("libcap"));
+        assert_eq!(MAP.longest_prefix("libcap-ng.so.0"), Some("libcap-ng"));
+        assert_eq!(MAP.longest_prefix("libcrypto.so.3"), Some("openssl-libs"));
+        assert_eq!(MAP.longest_prefix("libcrypt.so.2"), Some("libxcrypt"));
+        assert_eq!(MAP.longest_prefix("libcurl.so.4"), None);
+        assert_eq!(MAP.longest_prefix(""), None);
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        // Reference: first longest matching prefix by brute force
+        for probe in [
+            "libc.so.6",
+            "libcap.so",
+            "libcrypto.so.3",
+            "libcz",
+            "zzz",
+            "l",
+        ] {
+            let mut expected: Option<(&str, &str)> = None;
+            for (k, v) in PAIRS {
+                if probe.starts_with(k) && expected.is_none_or(|(best, _)| k.len() > best.len()) {
+                    expected = Some((k, v));
+                }
+            }
+            let expected = expected.map(|(_, v)| v);
+            assert_eq!(MAP.longest_prefix(probe), expected, "{}", probe);
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        static EMPTY: StaticMap<0> = StaticMap::new(&[]);
+        assert!(EMPTY.is_empty());
+        assert_eq!(EMPTY.get("x"), None);
+        assert_eq!(EMPTY.longest_prefix("x"), None);
+    }
+}
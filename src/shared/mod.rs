@@ -2,47 +2,98 @@
 //!
 //! These modules contain specifications that are common across all distro variants.
 
+pub mod answer_file;
 pub mod auth;
 pub mod boot;
+pub mod boot_counting;
 pub mod boot_modules;
+pub mod checksum;
 pub mod chroot;
+pub mod command;
 pub mod components;
+pub(crate) mod const_check;
 pub mod devices;
+pub mod elf_deps;
 pub mod error;
+pub mod esp;
+pub mod hardware;
 pub mod initramfs;
+pub mod install;
 pub mod iso;
+pub mod iso_build;
+pub mod iso_image;
+pub mod kernels;
+pub mod license_bundle;
 pub mod licenses;
+pub mod lookup;
 pub mod modules;
 pub mod partitions;
 pub mod paths;
+pub mod profile;
 pub mod qemu;
+pub mod registry;
 pub mod requirements;
+pub mod secure_boot;
+pub mod serial_log;
 pub mod services;
 pub mod rootfs;
+pub mod rootfs_build;
+pub mod rootfs_manifest;
 pub mod system;
+pub mod test_protocol;
+#[cfg(test)]
+pub(crate) mod test_util;
 pub mod udev;
 pub mod uki;
 pub mod users;
+pub mod validate;
 
 pub use boot::{
-    bootctl_install_command, BootEntry, LoaderConfig, DEFAULT_TIMEOUT, ENTRIES_DIR,
-    ESP_MOUNT_POINT, LOADER_CONF_PATH,
+    bootctl_install_command, BootEntry, LoaderConfig, SecureBootEnroll, DEFAULT_TIMEOUT,
+    ENTRIES_DIR, ESP_MOUNT_POINT, LOADER_CONF_PATH,
+};
+pub use checksum::{
+    hash_file, verify_checksums, verify_sidecar, write_checksums, write_sidecar, ChecksumError,
+    HashAlgorithm,
 };
 pub use chroot::{BindMount, CHROOT_BIND_MOUNTS};
-pub use devices::BOOT_DEVICE_PROBE_ORDER;
+pub use elf_deps::{
+    resolve_components, DependencyIssue, DependencyResolver, ElfError, ElfFile, Library,
+    LibraryClosure,
+};
+pub use devices::{
+    BlockDevice, BlockDevices, BlockPartition, LiveMedium, LiveMediumMatch, Transport, BOOT_DEVICE_PROBE_ORDER,
+};
 pub use error::{ToolError, ToolErrorCode};
+pub use license_bundle::{
+    FileKind, LicenseCollector, LicenseError, LicensePlan, LicenseProblem, PackageLicense,
+    LICENSE_INDEX,
+};
+pub use kernels::{
+    KernelVersion, RetentionPlan, RetentionPolicy, VersionedBootFiles,
+};
+pub use esp::{EspBudget, EspBudgetError, EspTarget, EspUsage, RETAINED_KERNELS};
+pub use hardware::{CheckStatus, HardwareCheck, HardwareInfo, HardwareProbe, HardwareReport};
 pub use initramfs::{
     CPIO_GZIP_LEVEL, INITRAMFS_DIRS, MOUNT_LIVE_OVERLAY, MOUNT_NEWROOT, MOUNT_OVERLAY,
     MOUNT_ROOTFS,
 };
+pub use answer_file::{AnswerFileError, AnswerFileErrors, InstallRequest};
+pub use install::{
+    InstallConfig, InstallPlan, InstallStep, RootEncryption, StepAction, StepKind,
+    DEFAULT_INSTALL_TARGET,
+};
 pub use iso::{
     EFI_DEBUG, EFIBOOT_FILENAME, EFIBOOT_SIZE_MB, EFI_BOOTLOADER, EFI_GRUB, INITRAMFS_LIVE_ISO_PATH, ISO_BOOT_DIR,
     ISO_CHECKSUM_SUFFIX, ISO_EFI_DIR, ISO_LIVE_DIR, KERNEL_ISO_PATH, LIVE_OVERLAY_ISO_PATH,
     ROOTFS_ISO_PATH, SELINUX_DISABLE, SERIAL_BAUD_RATE, SERIAL_CONSOLE, SHA512_SEPARATOR,
-    SQUASHFS_ISO_PATH, VGA_CONSOLE, XORRISO_FS_FLAGS, XORRISO_PARTITION_OFFSET,
+    SQUASHFS_ISO_PATH, UKI_INSTALLED_ISO_DIR, VGA_CONSOLE, XORRISO_EFI_PARTITION_TYPE,
+    XORRISO_FS_FLAGS, XORRISO_PARTITION_OFFSET,
 };
+pub use iso_build::{IsoBuildError, IsoBuildPlan};
+pub use iso_image::{verify_iso, IsoEntry, IsoImage, IsoIssue, IsoLayout};
 pub use partitions::{PartitionLayout, PartitionSpec, EFI_PARTITION_SIZE_MB};
-pub use qemu::{QEMU_CPU_MODE, QEMU_DISK_FILENAME, QEMU_DISK_GB, QEMU_MEMORY_GB, QEMU_SERIAL_LOG, QCOW2_IMAGE_FILENAME, RAW_DISK_FILENAME};
+pub use qemu::{BootMode, QemuCommand, QemuError, QEMU_CPU_MODE, QEMU_DISK_FILENAME, QEMU_DISK_GB, QEMU_MEMORY_GB, QEMU_OVMF_VARS, QEMU_SERIAL_LOG};
 pub use rootfs::{
     // EROFS (primary)
     EROFS_CDROM_PATH, EROFS_CHUNK_SIZE, EROFS_COMPRESSION, EROFS_COMPRESSION_LEVEL,
@@ -51,13 +102,27 @@ pub use rootfs::{
     SQUASHFS_BLOCK_SIZE, SQUASHFS_CDROM_PATH, SQUASHFS_COMPRESSION, SQUASHFS_MAGIC, SQUASHFS_NAME,
     // Installer constants
     ESSENTIAL_DIRS, MIN_REQUIRED_BYTES, ROOTFS_SEARCH_PATHS,
+    // Format detection
+    detect_rootfs_format, find_rootfs, read_superblock, RootfsFormat, RootfsSuperblock,
 };
+pub use profile::Profile;
+pub use registry::{component, Component, COMPONENTS};
+pub use rootfs_manifest::{
+    verify_rootfs, EntryKind, IssueKind, Manifest, ManifestEntry, ManifestIssue, ManifestReport,
+    Severity,
+};
+pub use rootfs_build::{BuildParamError, ErofsBuildParams, SquashfsBuildParams, SOURCE_DATE_EPOCH};
 pub use uki::{
     LOADER_ENTRIES_DIR, SYSTEMD_BOOT_EFI, SYSTEMD_BOOT_STUB, UKI_DEBUG_FILENAME,
     UKI_EFI_DIR, UKI_EMERGENCY_FILENAME, UKI_LIVE_FILENAME,
     // Installed UKI constants
     UKI_INSTALLED_FILENAME, UKI_INSTALLED_RECOVERY_FILENAME,
 };
+pub use boot_counting::{
+    counted_default_pattern, counted_filename,
+    loader_boot_count_path, BootAssessment, BootCounter, BootCounting,
+    CountedBootFile, CountedName, DEFAULT_BOOT_TRIES,
+};
 pub use boot_modules::{CORE_BOOT_MODULES, INSTALL_BOOT_MODULES, USB_BOOT_MODULES};
 pub use modules::{
     module_path, INSTALL_MODULES, INSTALL_MODULES_BUILTIN, LIVE_MODULES, LIVE_MODULES_BUILTIN,
@@ -68,10 +133,22 @@ pub use paths::{
     INITRAMFS_FILENAME, INITRAMFS_LIVE_OUTPUT, INTEL_UCODE_FILENAME, KERNEL_FILENAME,
     LOADER_CONF_FILENAME, OS_VERSION, PROTECTED_PATHS,
 };
-pub use requirements::{SystemRequirements, ACORN_REQUIREMENTS, LEVITATE_REQUIREMENTS};
+pub use requirements::{SystemRequirements, ACORN_REQUIREMENTS, BYTES_PER_GB, LEVITATE_REQUIREMENTS};
+pub use secure_boot::{
+    auto_enroll_files, signing_targets, signing_targets_live, KeyRole, Sbat, SbatEntry,
+    SecureBootError, SecureBootKeys, Signer, SigningTarget, SECURE_BOOT_KEY_DIR,
+};
+pub use serial_log::{
+    parse_serial_log, BootFailure, BootReport, BootStage, SerialLogParser, SHELL_READY_MARKER,
+};
 pub use services::ServiceManager;
 pub use system::{is_mount_point, is_root};
+pub use test_protocol::{
+    profile_snippet, CommandEncoder, CommandOutput, ProtocolDecoder, ProtocolEvent,
+    CMD_END_MARKER, CMD_START_MARKER, PROMPT_MARKER, TEST_PROFILE_PATH,
+};
 pub use users::{UserSpec, MIN_GID, MIN_UID, SUDOERS_WHEEL_LINE};
+pub use validate::{validate_spec, Diagnostic, DiagnosticKind, SpecReport};
 pub use auth::{
     // All PAM configuration files (SINGLE SOURCE OF TRUTH)
     PAM_SYSTEM_AUTH, PAM_POSTLOGIN, PAM_LOGIN, PAM_SSHD, PAM_REMOTE,
@@ -104,7 +181,7 @@ pub use components::{
     // /etc files
     ETC_FILES,
     // Libraries
-    CRITICAL_LIBS,
+    CRITICAL_LIBS, critical_libs,
     // Users/groups
     SYSTEM_USERS, SYSTEM_GROUPS,
 };
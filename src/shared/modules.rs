@@ -6,6 +6,9 @@
 //! - `recinit` - builds initramfs, constructs full paths from these names
 //! - `fsdbg` - verifies initramfs contents against these lists
 
+use super::const_check::static_check;
+use super::lookup::StaticMap;
+
 // =============================================================================
 // LIVE INITRAMFS MODULES
 // =============================================================================
@@ -42,6 +45,7 @@ pub const LIVE_MODULES: &[&str] = &[
     "loop",
     "overlay",
 ];
+static_check!(names LIVE_MODULES);
 
 /// Modules typically built-in to LevitateOS kernel (won't exist as .ko files).
 ///
@@ -72,6 +76,7 @@ pub const LIVE_MODULES_BUILTIN: &[&str] = &[
     "libahci",
     "ahci",
 ];
+static_check!(unique LIVE_MODULES_BUILTIN);
 
 // =============================================================================
 // INSTALL INITRAMFS MODULES
@@ -86,11 +91,10 @@ pub const INSTALL_MODULES: &[&str] = &[
     "virtio",
     "virtio_ring",
     "virtio_pci",
-    "virtio_scsi",
-    "virtio_blk",
-    // === SCSI core ===
+    // === SCSI core (before virtio_scsi, which needs it) ===
     "scsi_mod",
     "sd_mod",
+    "virtio_scsi",
     // === NVMe (modern SSDs) ===
     "nvme-core",
     "nvme",
@@ -99,6 +103,8 @@ pub const INSTALL_MODULES: &[&str] = &[
     "libahci",
     "ahci",
     "ata_piix",
+    // === Virtio block (QEMU virtual disks) ===
+    "virtio_blk",
     // === USB Storage ===
     "usb-common",
     "usbcore",
@@ -124,6 +130,7 @@ pub const INSTALL_MODULES: &[&str] = &[
     "dm-mod",
     "dm-crypt",
 ];
+static_check!(names INSTALL_MODULES);
 
 /// Modules typically built-in to LevitateOS kernel (won't exist as .ko files).
 ///
@@ -173,6 +180,7 @@ pub const INSTALL_MODULES_BUILTIN: &[&str] = &[
     "dm-mod",
     "dm-crypt",
 ];
+static_check!(names INSTALL_MODULES_BUILTIN);
 
 // =============================================================================
 // MODULE PATH CONSTRUCTION
@@ -232,17 +240,18 @@ pub const MODULE_PATHS: &[(&str, &str)] = &[
     ("dm-mod", "kernel/drivers/md/dm-mod"),
     ("dm-crypt", "kernel/drivers/md/dm-crypt"),
 ];
+static_check!(module_paths MODULE_PATHS);
 
 /// Get the kernel path for a module name.
 ///
 /// Returns the path relative to `/lib/modules/<version>/` without extension.
 pub fn module_path(name: &str) -> Option<&'static str> {
-    MODULE_PATHS
-        .iter()
-        .find(|(n, _)| *n == name)
-        .map(|(_, p)| *p)
+    MODULE_INDEX.get(name)
 }
 
+/// `MODULE_PATHS` hashed for lookup.
+static MODULE_INDEX: StaticMap<{ MODULE_PATHS.len() }> = StaticMap::new(MODULE_PATHS);
+
 /// Get full paths for a list of module names.
 ///
 /// Returns paths suitable for copying from kernel modules directory.
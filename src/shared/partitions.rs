@@ -2,6 +2,8 @@
 //!
 //! Defines the standard partition scheme for LevitateOS installations.
 
+use crate::arch::Arch;
+
 /// Size of the EFI System Partition in megabytes.
 /// 1GB allows room for multiple kernels (current, fallback, LTS).
 pub const EFI_PARTITION_SIZE_MB: u32 = 1024;
@@ -18,6 +20,12 @@ pub const EFI_FILESYSTEM: &str = "vfat";
 /// Root partition filesystem type.
 pub const ROOT_FILESYSTEM: &str = "ext4";
 
+/// Filesystems supported for the root partition.
+pub const SUPPORTED_ROOT_FILESYSTEMS: &[&str] = &["ext4", "xfs", "btrfs"];
+
+/// Device-mapper name for an encrypted root partition (`/dev/mapper/<name>`).
+pub const LUKS_MAPPER_NAME: &str = "cryptroot";
+
 /// Standard partition layout for UEFI installations.
 #[derive(Debug, Clone)]
 pub struct PartitionLayout {
@@ -68,6 +76,27 @@ impl Default for PartitionLayout {
 }
 
 impl PartitionLayout {
+    /// Named layout presets.
+    pub const PRESETS: &'static [&'static str] = &["default"];
+
+    /// Default layout with the root partition typed for `arch`.
+    ///
+    /// Uses the Discoverable Partitions root type instead of the generic
+    /// Linux filesystem type, so the root can be found without `root=`.
+    pub fn for_arch(arch: Arch) -> Self {
+        let mut layout = Self::default();
+        layout.root.gpt_type = arch.gpt_root_type();
+        layout
+    }
+
+    /// Look up a named layout preset.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            _ => None,
+        }
+    }
+
     /// Generate sfdisk script for this layout.
     pub fn to_sfdisk_script(&self) -> String {
         format!(
@@ -76,3 +105,68 @@ impl PartitionLayout {
         )
     }
 }
+
+impl PartitionSpec {
+    /// Device path for this partition on the given disk.
+    ///
+    /// Follows the kernel naming scheme: `sda` → `sda1`, `nvme0n1` → `nvme0n1p1`.
+    pub fn device_on(&self, disk: &str) -> String {
+        partition_device(disk, self.number)
+    }
+
+    /// mkfs argv for formatting this partition on the given device.
+    pub fn mkfs_command(&self, device: &str) -> Vec<String> {
+        let mut argv: Vec<String> = match self.filesystem {
+            "vfat" => vec!["mkfs.fat".into(), "-F".into(), "32".into(), "-n".into()],
+            "ext4" => vec!["mkfs.ext4".into(), "-F".into(), "-L".into()],
+            fs => vec![format!("mkfs.{}", fs), "-f".into(), "-L".into()],
+        };
+        argv.push(self.label.to_string());
+        argv.push(device.to_string());
+        argv
+    }
+}
+
+/// Device path for partition `number` on `disk`.
+///
+/// Disks whose name ends in a digit (nvme, mmcblk, loop) get a `p` separator.
+pub fn partition_device(disk: &str, number: u8) -> String {
+    if disk.ends_with(|c: char| c.is_ascii_digit()) {
+        format!("{}p{}", disk, number)
+    } else {
+        format!("{}{}", disk, number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_for_arch() {
+        let script = PartitionLayout::for_arch(Arch::Aarch64).to_sfdisk_script();
+        assert!(script.ends_with(",,B921B045-1DF0-41C3-AF44-4C6F280D3FAE\n"));
+        assert!(PartitionLayout::default().to_sfdisk_script().ends_with(",,L\n"));
+    }
+
+    #[test]
+    fn test_partition_device_naming() {
+        assert_eq!(partition_device("/dev/sda", 1), "/dev/sda1");
+        assert_eq!(partition_device("/dev/vda", 2), "/dev/vda2");
+        assert_eq!(partition_device("/dev/nvme0n1", 1), "/dev/nvme0n1p1");
+        assert_eq!(partition_device("/dev/mmcblk0", 2), "/dev/mmcblk0p2");
+    }
+
+    #[test]
+    fn test_mkfs_commands() {
+        let layout = PartitionLayout::default();
+        assert_eq!(
+            layout.efi.mkfs_command("/dev/vda1"),
+            ["mkfs.fat", "-F", "32", "-n", "EFI", "/dev/vda1"]
+        );
+        assert_eq!(
+            layout.root.mkfs_command("/dev/vda2"),
+            ["mkfs.ext4", "-F", "-L", "root", "/dev/vda2"]
+        );
+    }
+}
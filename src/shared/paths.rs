@@ -11,6 +11,8 @@
 /// Kernel filename in /boot after installation.
 ///
 /// Both distros use the standard "vmlinuz" name for the compressed kernel.
+/// Systems keeping several kernels use `vmlinuz-<kver>` instead
+/// (see `kernels::VersionedBootFiles`).
 pub const KERNEL_FILENAME: &str = "vmlinuz";
 
 /// Initramfs filename in /boot after installation.
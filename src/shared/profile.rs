@@ -0,0 +1,249 @@
+//! Install profiles - which registered components an image ships.
+//!
+//! The flat lists in `components.rs` describe the full (desktop) system. A
+//! `Profile` selects a subset of the component registry and filters those
+//! lists, the rootfs manifest and the enabled services to match, so a lean
+//! server image needs no hand-edited lists.
+
+use std::fmt;
+
+use super::auth::{SSH_BIN, SSH_SBIN};
+use super::components::{
+    ALL_SYSTEMD_UNITS, BIN_UTILS, ETC_FILES, NM_BIN, NM_SBIN, NM_UNITS, SBIN_UTILS, SSH_UNITS,
+    SYSTEM_GROUPS, SYSTEM_USERS,
+};
+use super::registry::{Component, Field, CHRONY, COMPONENTS, DBUS, NETWORKMANAGER, SSH};
+
+/// Components of the minimal profile.
+const MINIMAL_COMPONENTS: &[&Component] = &[&DBUS];
+
+/// Components of the server profile.
+const SERVER_COMPONENTS: &[&Component] = &[&DBUS, &SSH, &CHRONY, &NETWORKMANAGER];
+
+/// A named set of components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Profile {
+    /// Base system only: no audio, Bluetooth or NetworkManager
+    Minimal,
+    /// Headless: SSH, time sync and networking, no desktop daemons
+    Server,
+    /// Every registered component (the full list set)
+    #[default]
+    Desktop,
+}
+
+impl Profile {
+    /// All profiles, smallest first.
+    pub const ALL: &'static [Profile] = &[Profile::Minimal, Profile::Server, Profile::Desktop];
+
+    /// Parse a profile name (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Lowercase name.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Profile::Minimal => "minimal",
+            Profile::Server => "server",
+            Profile::Desktop => "desktop",
+        }
+    }
+
+    /// One-line description.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Profile::Minimal => "Base system without audio, Bluetooth or NetworkManager",
+            Profile::Server => "Headless server with SSH and time synchronization",
+            Profile::Desktop => "Full daily-driver system",
+        }
+    }
+
+    /// Registered components included in this profile.
+    pub const fn components(&self) -> &'static [&'static Component] {
+        match self {
+            Profile::Minimal => MINIMAL_COMPONENTS,
+            Profile::Server => SERVER_COMPONENTS,
+            Profile::Desktop => COMPONENTS,
+        }
+    }
+
+    /// Whether `component` is part of this profile.
+    pub fn includes(&self, component: &Component) -> bool {
+        self.components().iter().any(|c| c.name == component.name)
+    }
+
+    /// Whether a service (with or without `.service`) is part of this profile.
+    ///
+    /// Services that belong to no registered component are always included.
+    pub fn includes_service(&self, name: &str) -> bool {
+        let unit = format!("{}.service", name.trim_end_matches(".service"));
+        self.keeps(&unit, &[Field::Units, Field::UserUnits])
+    }
+
+    /// Entries of a flat list that this profile keeps.
+    ///
+    /// An entry is dropped only if every component that contributes it via
+    /// one of `fields` is excluded from the profile.
+    pub fn select(&self, list: &[&'static str], fields: &[Field]) -> Vec<&'static str> {
+        list.iter()
+            .copied()
+            .filter(|name| self.keeps(name, fields))
+            .collect()
+    }
+
+    fn keeps(&self, name: &str, fields: &[Field]) -> bool {
+        let mut owners = COMPONENTS
+            .iter()
+            .filter(|c| fields.iter().any(|f| f.of(c).contains(&name)))
+            .peekable();
+        owners.peek().is_none() || owners.any(|c| self.includes(c))
+    }
+
+    /// `BIN_UTILS` for this profile.
+    pub fn bin_utils(&self) -> Vec<&'static str> {
+        self.select(BIN_UTILS, &[Field::Bin])
+    }
+
+    /// `SBIN_UTILS` for this profile.
+    pub fn sbin_utils(&self) -> Vec<&'static str> {
+        self.select(SBIN_UTILS, &[Field::Sbin])
+    }
+
+    /// `ALL_SYSTEMD_UNITS` for this profile.
+    pub fn systemd_units(&self) -> Vec<&'static str> {
+        self.select(ALL_SYSTEMD_UNITS, &[Field::Units, Field::UserUnits])
+    }
+
+    /// `ETC_FILES` for this profile.
+    pub fn etc_files(&self) -> Vec<&'static str> {
+        self.select(ETC_FILES, &[Field::Config])
+    }
+
+    /// `SYSTEM_USERS` for this profile.
+    pub fn system_users(&self) -> Vec<&'static str> {
+        self.select(SYSTEM_USERS, &[Field::Users])
+    }
+
+    /// `SYSTEM_GROUPS` for this profile.
+    pub fn system_groups(&self) -> Vec<&'static str> {
+        self.select(SYSTEM_GROUPS, &[Field::Groups])
+    }
+
+    /// `NM_BIN` for this profile (empty without NetworkManager).
+    pub fn nm_bin(&self) -> &'static [&'static str] {
+        self.component_list(&NETWORKMANAGER, NM_BIN)
+    }
+
+    /// `NM_SBIN` for this profile (empty without NetworkManager).
+    pub fn nm_sbin(&self) -> &'static [&'static str] {
+        self.component_list(&NETWORKMANAGER, NM_SBIN)
+    }
+
+    /// `NM_UNITS` for this profile (empty without NetworkManager).
+    pub fn nm_units(&self) -> &'static [&'static str] {
+        self.component_list(&NETWORKMANAGER, NM_UNITS)
+    }
+
+    /// `auth::SSH_BIN` for this profile (empty without SSH).
+    pub fn ssh_bin(&self) -> &'static [&'static str] {
+        self.component_list(&SSH, SSH_BIN)
+    }
+
+    /// `auth::SSH_SBIN` for this profile (empty without SSH).
+    pub fn ssh_sbin(&self) -> &'static [&'static str] {
+        self.component_list(&SSH, SSH_SBIN)
+    }
+
+    /// `SSH_UNITS` for this profile (empty without SSH).
+    pub fn ssh_units(&self) -> &'static [&'static str] {
+        self.component_list(&SSH, SSH_UNITS)
+    }
+
+    /// A list builders copy on its own, kept only with its component.
+    fn component_list(
+        &self,
+        component: &Component,
+        list: &'static [&'static str],
+    ) -> &'static [&'static str] {
+        if self.includes(component) {
+            list
+        } else {
+            &[]
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::registry::{BLUETOOTH, PIPEWIRE};
+
+    #[test]
+    fn test_desktop_is_full_set() {
+        let p = Profile::default();
+        assert_eq!(p, Profile::Desktop);
+        assert_eq!(p.bin_utils(), BIN_UTILS);
+        assert_eq!(p.systemd_units(), ALL_SYSTEMD_UNITS);
+        assert_eq!(p.system_groups(), SYSTEM_GROUPS);
+    }
+
+    #[test]
+    fn test_profiles_nest() {
+        for pair in Profile::ALL.windows(2) {
+            for c in pair[0].components() {
+                assert!(pair[1].includes(c), "{} drops {}", pair[1], c.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_server_profile() {
+        let p = Profile::from_name("Server").unwrap();
+        assert!(p.includes(&SSH) && !p.includes(&PIPEWIRE) && !p.includes(&BLUETOOTH));
+
+        let bin = p.bin_utils();
+        assert!(bin.contains(&"ls") && bin.contains(&"systemctl"));
+        assert!(!bin.contains(&"bluetoothctl") && !bin.contains(&"pw-cli"));
+        assert!(!p.systemd_units().contains(&"pipewire.service"));
+        assert!(p.systemd_units().contains(&"systemd-journald.service"));
+        assert!(!p.system_users().contains(&"pipewire"));
+        assert!(p.etc_files().contains(&"etc/ssh/sshd_config"));
+    }
+
+    #[test]
+    fn test_minimal_services() {
+        let p = Profile::Minimal;
+        assert!(!p.includes_service("NetworkManager"));
+        assert!(!p.includes_service("sshd.service"));
+        // Not owned by any component
+        assert!(p.includes_service("networking"));
+        assert!(!p.sbin_utils().contains(&"NetworkManager"));
+        assert_eq!(p.system_users(), vec!["root", "dbus"]);
+    }
+
+    #[test]
+    fn test_feature_lists() {
+        assert!(Profile::Minimal.nm_bin().is_empty());
+        assert!(Profile::Minimal.nm_sbin().is_empty());
+        assert!(Profile::Minimal.nm_units().is_empty());
+        assert!(Profile::Minimal.ssh_bin().is_empty());
+        assert!(Profile::Minimal.ssh_sbin().is_empty());
+        assert!(Profile::Minimal.ssh_units().is_empty());
+        for p in [Profile::Server, Profile::Desktop] {
+            assert_eq!(p.nm_sbin(), NM_SBIN);
+            assert_eq!(p.nm_units(), NM_UNITS);
+            assert_eq!(p.ssh_bin(), SSH_BIN);
+            assert_eq!(p.ssh_units(), SSH_UNITS);
+        }
+    }
+}
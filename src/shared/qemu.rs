@@ -1,4 +1,14 @@
 //! QEMU testing defaults shared between LevitateOS and AcornOS.
+//!
+//! `QemuCommand` turns these defaults into a complete qemu-system argv, so
+//! test harnesses don't each assemble their own.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::arch::Arch;
+use crate::variant::Variant;
 
 // =============================================================================
 // VM Resources
@@ -26,6 +36,9 @@ pub const QEMU_DISK_FILENAME: &str = "virtual-disk.qcow2";
 /// Serial log path
 pub const QEMU_SERIAL_LOG: &str = "/tmp/levitateos-serial.log";
 
+/// Writable UEFI variable store, copied from the template on every run
+pub const QEMU_OVMF_VARS: &str = "/tmp/levitateos-ovmf-vars.fd";
+
 // =============================================================================
 // CPU Configuration
 // =============================================================================
@@ -33,14 +46,540 @@ pub const QEMU_SERIAL_LOG: &str = "/tmp/levitateos-serial.log";
 /// CPU emulation mode for TCG fallback (when KVM unavailable).
 ///
 /// Uses qemu64 to avoid TCG warnings about unsupported features.
-pub const QEMU_CPU_MODE: &str = "qemu64";
+/// See `Arch::qemu_tcg_cpu` for other architectures.
+pub const QEMU_CPU_MODE: &str = Arch::DEFAULT.qemu_tcg_cpu();
+
+// =============================================================================
+// Firmware
+// =============================================================================
+
+/// OVMF code images for x86_64, in search order.
+///
+/// Covers Fedora/Rocky, Debian/Ubuntu, Arch, openSUSE and QEMU's own bundle.
+pub const OVMF_CODE_PATHS_X86_64: &[&str] = &[
+    "/usr/share/edk2/ovmf/OVMF_CODE.fd",
+    "/usr/share/OVMF/OVMF_CODE_4M.fd",
+    "/usr/share/OVMF/OVMF_CODE.fd",
+    "/usr/share/edk2/x64/OVMF_CODE.4m.fd",
+    "/usr/share/edk2-ovmf/x64/OVMF_CODE.fd",
+    "/usr/share/qemu/ovmf-x86_64-code.bin",
+    "/usr/share/qemu/edk2-x86_64-code.fd",
+];
+
+/// AAVMF (OVMF for ARM) code images for aarch64, in search order.
+pub const OVMF_CODE_PATHS_AARCH64: &[&str] = &[
+    "/usr/share/edk2/aarch64/QEMU_EFI-pflash.raw",
+    "/usr/share/AAVMF/AAVMF_CODE.fd",
+    "/usr/share/edk2/aarch64/QEMU_CODE.fd",
+    "/usr/share/qemu/aavmf-aarch64-code.bin",
+    "/usr/share/qemu/edk2-aarch64-code.fd",
+];
+
+/// OVMF variable store templates for x86_64.
+///
+/// Entry `i` is the store matching `OVMF_CODE_PATHS_X86_64[i]` (2M and 4M
+/// images don't mix).
+pub const OVMF_VARS_PATHS_X86_64: &[&str] = &[
+    "/usr/share/edk2/ovmf/OVMF_VARS.fd",
+    "/usr/share/OVMF/OVMF_VARS_4M.fd",
+    "/usr/share/OVMF/OVMF_VARS.fd",
+    "/usr/share/edk2/x64/OVMF_VARS.4m.fd",
+    "/usr/share/edk2-ovmf/x64/OVMF_VARS.fd",
+    "/usr/share/qemu/ovmf-x86_64-vars.bin",
+    "/usr/share/qemu/edk2-i386-vars.fd",
+];
+
+/// AAVMF variable store templates for aarch64, parallel to
+/// `OVMF_CODE_PATHS_AARCH64`.
+pub const OVMF_VARS_PATHS_AARCH64: &[&str] = &[
+    "/usr/share/edk2/aarch64/vars-template-pflash.raw",
+    "/usr/share/AAVMF/AAVMF_VARS.fd",
+    "/usr/share/edk2/aarch64/QEMU_VARS.fd",
+    "/usr/share/qemu/aavmf-aarch64-vars.bin",
+    "/usr/share/qemu/edk2-arm-vars.fd",
+];
+
+/// OVMF code search paths for `arch`.
+pub fn ovmf_code_paths(arch: Arch) -> &'static [&'static str] {
+    match arch {
+        Arch::X86_64 => OVMF_CODE_PATHS_X86_64,
+        Arch::Aarch64 => OVMF_CODE_PATHS_AARCH64,
+    }
+}
+
+/// OVMF variable store templates for `arch`.
+pub fn ovmf_vars_paths(arch: Arch) -> &'static [&'static str] {
+    match arch {
+        Arch::X86_64 => OVMF_VARS_PATHS_X86_64,
+        Arch::Aarch64 => OVMF_VARS_PATHS_AARCH64,
+    }
+}
+
+/// KVM device node.
+pub const KVM_DEVICE: &str = "/dev/kvm";
+
+/// Host port forwarded to the guest's SSH port by default.
+pub const QEMU_SSH_HOST_PORT: u16 = 2222;
+
+/// Virtual CPUs given to the guest.
+pub const QEMU_CPUS: u32 = 4;
 
 // =============================================================================
-// qcow2 VM Image Constants
+// Command Builder
 // =============================================================================
 
-/// Output filename for qcow2 VM images (x86_64 architecture).
-pub const QCOW2_IMAGE_FILENAME: &str = "levitateos-x86_64.qcow2";
+/// What the VM boots from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootMode {
+    /// Live ISO attached as a CD-ROM
+    LiveIso(PathBuf),
+    /// Installed system on a disk image
+    InstalledDisk(PathBuf),
+    /// UKI loaded directly by the firmware (`-kernel`), no bootloader
+    Uki(PathBuf),
+}
+
+/// QEMU argv cannot be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QemuError {
+    /// No UEFI firmware image found (paths searched)
+    FirmwareNotFound(Vec<PathBuf>),
+    /// No UEFI variable store template found (paths searched)
+    VarsNotFound(Vec<PathBuf>),
+    /// The per-run variable store could not be written (path, reason)
+    VarsCopy(PathBuf, String),
+}
+
+impl fmt::Display for QemuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QemuError::FirmwareNotFound(searched) => {
+                write!(f, "no OVMF firmware found (install edk2-ovmf); searched:")?;
+                for path in searched {
+                    write!(f, " {}", path.display())?;
+                }
+                Ok(())
+            }
+            QemuError::VarsNotFound(searched) => {
+                write!(
+                    f,
+                    "no OVMF variable store found (install edk2-ovmf); searched:"
+                )?;
+                for path in searched {
+                    write!(f, " {}", path.display())?;
+                }
+                Ok(())
+            }
+            QemuError::VarsCopy(path, reason) => {
+                write!(f, "cannot write {}: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QemuError {}
+
+/// qemu-system argv for booting a variant under UEFI.
+///
+/// ```no_run
+/// use distro_spec::shared::qemu::{BootMode, QemuCommand};
+/// use distro_spec::Variant;
+///
+/// let argv = QemuCommand::new(Variant::Levitate, BootMode::LiveIso("levitateos-x86_64.iso".into()))
+///     .with_disk("virtual-disk.qcow2")
+///     .with_ssh_forward(2222)
+///     .argv()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct QemuCommand {
+    /// Variant being booted (names the VM)
+    pub variant: Variant,
+    /// Boot source
+    pub mode: BootMode,
+    /// Guest architecture
+    pub arch: Arch,
+    /// Memory in GB
+    pub memory_gb: u32,
+    /// Virtual CPUs
+    pub cpus: u32,
+    /// Extra virtio disk (install target for live/UKI boots)
+    pub disk: Option<PathBuf>,
+    /// Serial console output file
+    pub serial_log: PathBuf,
+    /// Host port forwarded to guest port 22 (None = no network)
+    pub ssh_port: Option<u16>,
+    /// Firmware image (None = search `ovmf_code_paths`)
+    pub firmware: Option<PathBuf>,
+    /// Variable store template (None = search `ovmf_vars_paths`)
+    pub vars_template: Option<PathBuf>,
+    /// Writable copy of the variable store the guest boots with
+    pub vars: PathBuf,
+    /// Force KVM on or off (None = use it if `/dev/kvm` is usable)
+    pub kvm: Option<bool>,
+    /// Filesystem root for firmware and `/dev/kvm` lookup
+    pub root: PathBuf,
+}
+
+impl QemuCommand {
+    /// Command with the default resources for `variant` booting from `mode`.
+    pub fn new(variant: Variant, mode: BootMode) -> Self {
+        Self {
+            variant,
+            mode,
+            arch: Arch::DEFAULT,
+            memory_gb: QEMU_MEMORY_GB,
+            cpus: QEMU_CPUS,
+            disk: None,
+            serial_log: PathBuf::from(QEMU_SERIAL_LOG),
+            ssh_port: None,
+            firmware: None,
+            vars_template: None,
+            vars: PathBuf::from(QEMU_OVMF_VARS),
+            kvm: None,
+            root: PathBuf::from("/"),
+        }
+    }
+
+    /// Emulate a different architecture.
+    pub fn with_arch(mut self, arch: Arch) -> Self {
+        self.arch = arch;
+        self
+    }
+
+    /// Set guest memory.
+    pub fn with_memory_gb(mut self, gb: u32) -> Self {
+        self.memory_gb = gb;
+        self
+    }
+
+    /// Set the number of virtual CPUs.
+    pub fn with_cpus(mut self, cpus: u32) -> Self {
+        self.cpus = cpus;
+        self
+    }
+
+    /// Attach a virtio disk image (qcow2 if the name ends in `.qcow2`, else raw).
+    pub fn with_disk(mut self, path: impl Into<PathBuf>) -> Self {
+        self.disk = Some(path.into());
+        self
+    }
+
+    /// Write the serial console to a different file.
+    pub fn with_serial_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.serial_log = path.into();
+        self
+    }
+
+    /// Enable user-mode networking, forwarding `host_port` to guest SSH.
+    pub fn with_ssh_forward(mut self, host_port: u16) -> Self {
+        self.ssh_port = Some(host_port);
+        self
+    }
+
+    /// Use a specific firmware image instead of searching.
+    pub fn with_firmware(mut self, path: impl Into<PathBuf>) -> Self {
+        self.firmware = Some(path.into());
+        self
+    }
+
+    /// Use a specific variable store template instead of searching.
+    pub fn with_vars_template(mut self, path: impl Into<PathBuf>) -> Self {
+        self.vars_template = Some(path.into());
+        self
+    }
+
+    /// Write the per-run variable store to a different file.
+    pub fn with_vars(mut self, path: impl Into<PathBuf>) -> Self {
+        self.vars = path.into();
+        self
+    }
+
+    /// Force KVM on or off.
+    pub fn with_kvm(mut self, enabled: bool) -> Self {
+        self.kvm = Some(enabled);
+        self
+    }
+
+    /// Look up firmware and `/dev/kvm` under a different root (for testing).
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    fn rooted(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /// Whether `/dev/kvm` exists and can be opened read-write.
+    pub fn kvm_available(&self) -> bool {
+        fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(self.rooted(KVM_DEVICE))
+            .is_ok()
+    }
+
+    /// Whether the VM will run with KVM (forced setting, else detection).
+    ///
+    /// KVM is only used for the host's own architecture.
+    pub fn uses_kvm(&self) -> bool {
+        let native = Arch::from_name(std::env::consts::ARCH) == Some(self.arch);
+        self.kvm.unwrap_or_else(|| native && self.kvm_available())
+    }
+
+    /// Firmware image: the configured one, else the first OVMF path that exists.
+    pub fn find_firmware(&self) -> Result<PathBuf, QemuError> {
+        if let Some(fw) = &self.firmware {
+            return Ok(fw.clone());
+        }
+        let candidates: Vec<PathBuf> = ovmf_code_paths(self.arch)
+            .iter()
+            .map(|p| self.rooted(p))
+            .collect();
+        candidates
+            .iter()
+            .find(|p| p.is_file())
+            .cloned()
+            .ok_or(QemuError::FirmwareNotFound(candidates))
+    }
+
+    /// Variable store template: the configured one, else the one paired with
+    /// the firmware found by the search, else the first that exists.
+    pub fn find_vars_template(&self) -> Result<PathBuf, QemuError> {
+        if let Some(template) = &self.vars_template {
+            return Ok(template.clone());
+        }
+        let candidates: Vec<PathBuf> = ovmf_vars_paths(self.arch)
+            .iter()
+            .map(|p| self.rooted(p))
+            .collect();
+        let paired = match self.firmware {
+            Some(_) => None,
+            None => ovmf_code_paths(self.arch)
+                .iter()
+                .position(|p| self.rooted(p).is_file())
+                .and_then(|i| candidates.get(i)),
+        };
+        paired
+            .into_iter()
+            .chain(&candidates)
+            .find(|p| p.is_file())
+            .cloned()
+            .ok_or(QemuError::VarsNotFound(candidates))
+    }
+
+    /// Full argv, starting with the qemu-system binary.
+    ///
+    /// Copies the variable store template to `vars` first, so every run
+    /// starts with fresh NVRAM and the template stays untouched.
+    pub fn argv(&self) -> Result<Vec<String>, QemuError> {
+        let firmware = self.find_firmware()?;
+        let template = self.find_vars_template()?;
+        fs::copy(&template, &self.vars)
+            .map_err(|e| QemuError::VarsCopy(self.vars.clone(), e.to_string()))?;
+        let kvm = self.uses_kvm();
+        let mut argv: Vec<String> = vec![
+            self.arch.qemu_binary().to_string(),
+            "-name".to_string(),
+            self.variant.os_id().to_string(),
+            "-machine".to_string(),
+            self.arch.qemu_machine().to_string(),
+            "-accel".to_string(),
+            if kvm { "kvm" } else { "tcg" }.to_string(),
+            "-cpu".to_string(),
+            if kvm {
+                "host"
+            } else {
+                self.arch.qemu_tcg_cpu()
+            }
+            .to_string(),
+            "-m".to_string(),
+            format!("{}G", self.memory_gb),
+            "-smp".to_string(),
+            self.cpus.to_string(),
+            "-drive".to_string(),
+            format!(
+                "if=pflash,format=raw,readonly=on,file={}",
+                firmware.display()
+            ),
+            "-drive".to_string(),
+            format!("if=pflash,format=raw,file={}", self.vars.display()),
+        ];
+
+        match &self.mode {
+            BootMode::LiveIso(iso) => {
+                argv.extend([
+                    "-device".to_string(),
+                    "virtio-scsi-pci,id=scsi0".to_string(),
+                    "-drive".to_string(),
+                    format!(
+                        "file={},format=raw,if=none,media=cdrom,readonly=on,id=cd0",
+                        iso.display()
+                    ),
+                    "-device".to_string(),
+                    "scsi-cd,drive=cd0,bootindex=0".to_string(),
+                ]);
+            }
+            BootMode::InstalledDisk(disk) => push_disk(&mut argv, disk, 0, Some(0)),
+            BootMode::Uki(uki) => {
+                argv.extend(["-kernel".to_string(), uki.display().to_string()]);
+            }
+        }
+        if let Some(disk) = &self.disk {
+            let index = usize::from(matches!(self.mode, BootMode::InstalledDisk(_)));
+            push_disk(&mut argv, disk, index, None);
+        }
+
+        match self.ssh_port {
+            Some(port) => argv.extend([
+                "-netdev".to_string(),
+                format!("user,id=net0,hostfwd=tcp::{}-:22", port),
+                "-device".to_string(),
+                "virtio-net-pci,netdev=net0".to_string(),
+            ]),
+            None => argv.extend(["-nic".to_string(), "none".to_string()]),
+        }
+
+        argv.extend([
+            "-serial".to_string(),
+            format!("file:{}", self.serial_log.display()),
+            "-display".to_string(),
+            "none".to_string(),
+            "-no-reboot".to_string(),
+        ]);
+        Ok(argv)
+    }
+}
+
+fn push_disk(argv: &mut Vec<String>, disk: &Path, index: usize, bootindex: Option<u32>) {
+    let is_qcow2 = disk.extension().is_some_and(|e| e == "qcow2");
+    argv.extend([
+        "-drive".to_string(),
+        format!(
+            "file={},format={},if=none,id=disk{}",
+            disk.display(),
+            if is_qcow2 { "qcow2" } else { "raw" },
+            index
+        ),
+        "-device".to_string(),
+        match bootindex {
+            Some(b) => format!("virtio-blk-pci,drive=disk{},bootindex={}", index, b),
+            None => format!("virtio-blk-pci,drive=disk{}", index),
+        },
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    fn firmware_root() -> TempDir {
+        let dir = TempDir::new("qemu");
+        dir.write("usr/share/OVMF/OVMF_CODE_4M.fd", b"fw");
+        dir.write("usr/share/OVMF/OVMF_VARS_4M.fd", b"vars");
+        // 2M store that does not match the 4M code image
+        dir.write("usr/share/edk2/ovmf/OVMF_VARS.fd", b"vars-2m");
+        dir
+    }
+
+    #[test]
+    fn test_live_iso_argv() {
+        let root = firmware_root();
+        let vars = root.path().join("run-vars.fd");
+        let argv = QemuCommand::new(Variant::Levitate, BootMode::LiveIso("live.iso".into()))
+            .with_root(root.path())
+            .with_vars(&vars)
+            .with_kvm(false)
+            .with_disk("virtual-disk.qcow2")
+            .with_ssh_forward(QEMU_SSH_HOST_PORT)
+            .argv()
+            .unwrap()
+            .join(" ");
+        assert!(argv.starts_with(
+            "qemu-system-x86_64 -name levitateos -machine q35 -accel tcg -cpu qemu64 -m 8G"
+        ));
+        assert!(argv.contains("OVMF/OVMF_CODE_4M.fd"));
+        assert!(argv.contains(&format!(
+            "-drive if=pflash,format=raw,file={}",
+            vars.display()
+        )));
+        assert_eq!(fs::read(&vars).unwrap(), b"vars");
+        assert!(argv.contains("file=live.iso,format=raw,if=none,media=cdrom"));
+        assert!(argv.contains("file=virtual-disk.qcow2,format=qcow2,if=none,id=disk0"));
+        assert!(argv.contains("hostfwd=tcp::2222-:22"));
+        assert!(argv.contains("-serial file:/tmp/levitateos-serial.log"));
+    }
+
+    #[test]
+    fn test_installed_and_uki_modes() {
+        let root = firmware_root();
+        let template = root.write("vars.fd", b"vars");
+        let vars = root.path().join("run-vars.fd");
+        let argv = QemuCommand::new(Variant::Acorn, BootMode::InstalledDisk("disk.raw".into()))
+            .with_firmware("/fw.fd")
+            .with_vars_template(&template)
+            .with_vars(&vars)
+            .with_kvm(true)
+            .argv()
+            .unwrap()
+            .join(" ");
+        assert!(argv.contains("-accel kvm -cpu host"));
+        assert!(argv.contains("file=disk.raw,format=raw,if=none,id=disk0"));
+        assert!(argv.contains("virtio-blk-pci,drive=disk0,bootindex=0"));
+        assert!(argv.contains("-nic none"));
+
+        let argv = QemuCommand::new(Variant::Acorn, BootMode::Uki("acornos.efi".into()))
+            .with_firmware("/fw.fd")
+            .with_vars_template(&template)
+            .with_vars(&vars)
+            .with_arch(Arch::Aarch64)
+            .with_kvm(false)
+            .argv()
+            .unwrap()
+            .join(" ");
+        assert!(argv.starts_with("qemu-system-aarch64 -name acornos -machine virt"));
+        assert!(argv.contains("-cpu cortex-a72"));
+        assert!(argv.contains("-kernel acornos.efi"));
+    }
+
+    #[test]
+    fn test_firmware_and_kvm_detection() {
+        let dir = TempDir::new("qemu-empty");
+        let cmd = QemuCommand::new(Variant::Levitate, BootMode::Uki("x.efi".into()))
+            .with_root(dir.path());
+        match cmd.argv() {
+            Err(QemuError::FirmwareNotFound(searched)) => {
+                assert_eq!(searched.len(), OVMF_CODE_PATHS_X86_64.len())
+            }
+            other => panic!("expected FirmwareNotFound, got {:?}", other),
+        }
+        assert!(!cmd.kvm_available());
+        dir.write("dev/kvm", b"");
+        assert!(cmd.kvm_available());
+
+        let cmd = cmd.with_firmware("/fw.fd");
+        match cmd.argv() {
+            Err(QemuError::VarsNotFound(searched)) => {
+                assert_eq!(searched.len(), OVMF_VARS_PATHS_X86_64.len())
+            }
+            other => panic!("expected VarsNotFound, got {:?}", other),
+        }
+    }
 
-/// Temporary raw disk filename (converted to qcow2 after building).
-pub const RAW_DISK_FILENAME: &str = "levitateos-x86_64.raw";
+    #[test]
+    fn test_vars_paths_pair_with_code_paths() {
+        for &arch in Arch::ALL {
+            assert_eq!(ovmf_vars_paths(arch).len(), ovmf_code_paths(arch).len());
+        }
+        // The first existing template would be the 2M one
+        let root = firmware_root();
+        let cmd = QemuCommand::new(Variant::Levitate, BootMode::Uki("x.efi".into()))
+            .with_root(root.path());
+        assert_eq!(
+            cmd.find_vars_template().unwrap(),
+            root.path().join("usr/share/OVMF/OVMF_VARS_4M.fd")
+        );
+    }
+}
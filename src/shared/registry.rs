@@ -0,0 +1,796 @@
+//! Component registry - one definition per feature.
+//!
+//! A feature such as NetworkManager needs binaries, units, D-Bus names, users
+//! and package mappings. Each `Component` below holds all of them, and the
+//! flat lists elsewhere are built from `COMPONENTS` at compile time:
+//!
+//! - `SYSTEM_USERS`, `SYSTEM_GROUPS`, `BINARY_TO_PACKAGE`, ... take the field
+//!   of every component
+//! - `BIN_UTILS`, `SBIN_UTILS`, `ALL_SYSTEMD_UNITS` and
+//!   `DBUS_ACTIVATION_SYMLINKS` take it only from components whose `feeds`
+//!   name the list (see `FlatList`); builders copy the rest from the
+//!   per-feature lists (`NM_BIN`, `auth::SSH_BIN`, ...)
+//!
+//! # Adding a Feature
+//!
+//! 1. Define a `Component` here, with `feeds` naming the flat lists it joins
+//! 2. Add it to `COMPONENTS`
+//!
+//! Everything that reads the flat lists picks it up from there.
+
+use std::collections::BTreeSet;
+
+/// Everything one feature contributes to the rootfs.
+///
+/// Paths in `libexec` and `config` are rootfs-relative; everything else is a
+/// bare name resolved against the usual directory for its kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Component {
+    /// Short name ("networkmanager")
+    pub name: &'static str,
+    /// One-line description
+    pub description: &'static str,
+    /// Binaries in /usr/bin
+    pub bin: &'static [&'static str],
+    /// Binaries in /usr/sbin
+    pub sbin: &'static [&'static str],
+    /// Helpers outside `$PATH`, copied as files or whole trees
+    pub libexec: &'static [&'static str],
+    /// System units in /usr/lib/systemd/system
+    pub units: &'static [&'static str],
+    /// System units copied when the package ships them, never required
+    pub optional_units: &'static [&'static str],
+    /// User units in /usr/lib/systemd/user
+    pub user_units: &'static [&'static str],
+    /// Configuration files
+    pub config: &'static [&'static str],
+    /// System users
+    pub users: &'static [&'static str],
+    /// System groups
+    pub groups: &'static [&'static str],
+    /// Well-known D-Bus names the feature owns
+    pub dbus_names: &'static [&'static str],
+    /// Binary → package, for license collection
+    pub binary_packages: &'static [(&'static str, &'static str)],
+    /// Library prefix → package, for license collection
+    pub library_packages: &'static [(&'static str, &'static str)],
+    /// Flat lists built from this component
+    pub feeds: &'static [FlatList],
+}
+
+/// A flat list that only takes the components which opt into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatList {
+    /// `BIN_UTILS`, from `bin`
+    BinUtils,
+    /// `SBIN_UTILS`, from `sbin`
+    SbinUtils,
+    /// `ALL_SYSTEMD_UNITS`, from `units` and `user_units`
+    SystemdUnits,
+    /// `DBUS_ACTIVATION_SYMLINKS`, from `dbus_names`
+    DbusActivation,
+}
+
+impl Component {
+    /// Empty component, for `..Component::EMPTY` in definitions.
+    pub const EMPTY: Component = Component {
+        name: "",
+        description: "",
+        bin: &[],
+        sbin: &[],
+        libexec: &[],
+        units: &[],
+        optional_units: &[],
+        user_units: &[],
+        config: &[],
+        users: &[],
+        groups: &[],
+        dbus_names: &[],
+        binary_packages: &[],
+        library_packages: &[],
+        feeds: &[],
+    };
+
+    /// Whether the component is part of `list`.
+    pub const fn feeds_into(&self, list: FlatList) -> bool {
+        let mut i = 0;
+        while i < self.feeds.len() {
+            if self.feeds[i] as u8 == list as u8 {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Packages the component's files come from, sorted.
+    pub fn packages(&self) -> Vec<&'static str> {
+        let set: BTreeSet<_> = self
+            .binary_packages
+            .iter()
+            .chain(self.library_packages)
+            .map(|(_, pkg)| *pkg)
+            .collect();
+        set.into_iter().collect()
+    }
+
+    /// License directories to ship (`usr/share/licenses/<package>`).
+    pub fn license_dirs(&self) -> Vec<String> {
+        self.packages()
+            .into_iter()
+            .map(|pkg| format!("usr/share/licenses/{}", pkg))
+            .collect()
+    }
+
+    /// System and user units together, without `optional_units`.
+    pub fn all_units(&self) -> impl Iterator<Item = &'static str> {
+        self.units.iter().chain(self.user_units).copied()
+    }
+}
+
+// =============================================================================
+// Components
+// =============================================================================
+
+/// D-Bus system bus (dbus-broker).
+pub const DBUS: Component = Component {
+    name: "dbus",
+    description: "D-Bus system message bus",
+    units: &["dbus.service", "dbus-broker.service", "dbus.socket"],
+    users: &["dbus"],
+    groups: &["dbus"],
+    dbus_names: &["org.freedesktop.DBus"],
+    binary_packages: &[
+        ("dbus-broker", "dbus-broker"),
+        ("dbus-broker-launch", "dbus-broker"),
+    ],
+    feeds: &[FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// OpenSSH client and server.
+pub const SSH: Component = Component {
+    name: "ssh",
+    description: "OpenSSH client and server",
+    bin: &["ssh", "scp", "sftp", "ssh-keygen", "ssh-add", "ssh-agent"],
+    sbin: &["sshd"],
+    units: &[
+        "sshd.service",
+        "sshd@.service",
+        "sshd-keygen.target",
+        "sshd-keygen@.service",
+        "ssh-host-keys-migration.service",
+    ],
+    // Not shipped by every openssh-server build
+    optional_units: &["sshd.socket"],
+    config: &[
+        "etc/ssh/sshd_config",
+        "etc/ssh/ssh_host_rsa_key",
+        "etc/ssh/ssh_host_rsa_key.pub",
+        "etc/ssh/ssh_host_ecdsa_key",
+        "etc/ssh/ssh_host_ecdsa_key.pub",
+        "etc/ssh/ssh_host_ed25519_key",
+        "etc/ssh/ssh_host_ed25519_key.pub",
+        "etc/ssh/ssh_config",
+    ],
+    users: &["sshd"],
+    groups: &["sshd"],
+    binary_packages: &[
+        ("ssh", "openssh"),
+        ("scp", "openssh"),
+        ("sftp", "openssh"),
+        ("ssh-keygen", "openssh"),
+        ("ssh-add", "openssh"),
+        ("ssh-agent", "openssh"),
+        ("sshd", "openssh-server"),
+    ],
+    feeds: &[FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// chrony NTP client.
+pub const CHRONY: Component = Component {
+    name: "chrony",
+    description: "NTP time synchronization",
+    sbin: &["chronyd"],
+    units: &["chronyd.service"],
+    config: &["etc/chrony.conf"],
+    users: &["chrony"],
+    groups: &["chrony"],
+    binary_packages: &[("chronyd", "chrony")],
+    feeds: &[FlatList::SbinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// NetworkManager.
+pub const NETWORKMANAGER: Component = Component {
+    name: "networkmanager",
+    description: "Network connection management",
+    bin: &["nmcli", "nm-online", "nmtui"],
+    sbin: &["NetworkManager"],
+    units: &[
+        "NetworkManager.service",
+        "NetworkManager-dispatcher.service",
+    ],
+    dbus_names: &["org.freedesktop.NetworkManager"],
+    binary_packages: &[
+        ("nmcli", "NetworkManager"),
+        ("nm-online", "NetworkManager"),
+        ("nmtui", "NetworkManager"),
+        ("NetworkManager", "NetworkManager"),
+    ],
+    library_packages: &[("libnm.so", "NetworkManager-libnm")],
+    feeds: &[FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// wpa_supplicant (WiFi backend for NetworkManager).
+pub const WPA_SUPPLICANT: Component = Component {
+    name: "wpa_supplicant",
+    description: "WiFi authentication",
+    sbin: &["wpa_supplicant", "wpa_cli", "wpa_passphrase"],
+    units: &["wpa_supplicant.service"],
+    dbus_names: &["fi.w1.wpa_supplicant1"],
+    binary_packages: &[
+        ("wpa_supplicant", "wpa_supplicant"),
+        ("wpa_cli", "wpa_supplicant"),
+        ("wpa_passphrase", "wpa_supplicant"),
+    ],
+    feeds: &[FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// polkit authorization framework.
+pub const POLKIT: Component = Component {
+    name: "polkit",
+    description: "Authorization for unprivileged processes",
+    bin: &["pkexec", "pkaction", "pkcheck"],
+    // polkitd is in /usr/lib/polkit-1/, not /usr/sbin
+    libexec: &["usr/lib/polkit-1"],
+    units: &["polkit.service"],
+    users: &["polkitd"],
+    groups: &["polkitd"],
+    dbus_names: &["org.freedesktop.PolicyKit1"],
+    binary_packages: &[
+        ("pkexec", "polkit"),
+        ("pkaction", "polkit"),
+        ("pkcheck", "polkit"),
+        ("polkitd", "polkit"),
+    ],
+    library_packages: &[
+        ("libpolkit-gobject-1.so", "polkit-libs"),
+        ("libpolkit-agent-1.so", "polkit-libs"),
+    ],
+    feeds: &[FlatList::BinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// PipeWire audio (runs per user, so its units are user units).
+pub const PIPEWIRE: Component = Component {
+    name: "pipewire",
+    description: "Audio server with PulseAudio compatibility",
+    bin: &[
+        "pw-cli",
+        "pw-dump",
+        "pw-cat",
+        "pw-play",
+        "pw-record",
+        "pw-top",
+        "pw-metadata",
+        "pw-mon",
+        "pw-link",
+        "wpctl", // WirePlumber control
+        // PulseAudio compat (pipewire-pulse); pacmd is not provided
+        "pactl",
+        "paplay",
+        "parecord",
+    ],
+    sbin: &["pipewire", "pipewire-pulse", "wireplumber"],
+    user_units: &[
+        "pipewire.service",
+        "pipewire.socket",
+        "pipewire-pulse.service",
+        "pipewire-pulse.socket",
+        "wireplumber.service",
+    ],
+    users: &["pipewire"], // For PipeWire system mode (optional)
+    groups: &["pipewire"],
+    binary_packages: &[
+        ("pw-cli", "pipewire"),
+        ("pw-dump", "pipewire"),
+        ("pw-cat", "pipewire"),
+        ("pw-play", "pipewire"),
+        ("pw-record", "pipewire"),
+        ("pw-top", "pipewire"),
+        ("pw-metadata", "pipewire"),
+        ("pw-mon", "pipewire"),
+        ("pw-link", "pipewire"),
+        ("pipewire", "pipewire"),
+        ("pipewire-pulse", "pipewire-pulseaudio"),
+        ("wpctl", "wireplumber"),
+        ("wireplumber", "wireplumber"),
+        ("pactl", "pipewire-pulseaudio"),
+        ("pacmd", "pipewire-pulseaudio"),
+        ("paplay", "pipewire-pulseaudio"),
+        ("parecord", "pipewire-pulseaudio"),
+    ],
+    library_packages: &[
+        ("libpipewire-0.3.so", "pipewire-libs"),
+        ("libspa-0.2.so", "pipewire-libs"),
+    ],
+    feeds: &[FlatList::BinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// BlueZ Bluetooth stack.
+pub const BLUETOOTH: Component = Component {
+    name: "bluetooth",
+    description: "Bluetooth (BlueZ)",
+    bin: &["bluetoothctl"],
+    // bluetoothd is in /usr/libexec/bluetooth/, not /usr/sbin
+    libexec: &["usr/libexec/bluetooth"],
+    units: &["bluetooth.service", "bluetooth.target"],
+    groups: &["bluetooth"], // Users in this group can use bluetooth
+    dbus_names: &["org.bluez"],
+    binary_packages: &[("bluetoothctl", "bluez"), ("bluetoothd", "bluez")],
+    library_packages: &[("libbluetooth.so", "bluez-libs")],
+    feeds: &[FlatList::BinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// UDisks2 storage management.
+pub const UDISKS: Component = Component {
+    name: "udisks",
+    description: "Disk management for unprivileged users",
+    bin: &["udisksctl"],
+    libexec: &["usr/libexec/udisks2"],
+    units: &["udisks2.service"],
+    dbus_names: &["org.freedesktop.UDisks2"],
+    binary_packages: &[("udisksctl", "udisks2"), ("udisksd", "udisks2")],
+    library_packages: &[("libudisks2.so", "udisks2-libs")],
+    feeds: &[FlatList::BinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// UPower power management.
+pub const UPOWER: Component = Component {
+    name: "upower",
+    description: "Power and battery information",
+    bin: &["upower"],
+    libexec: &["usr/libexec/upowerd"],
+    units: &["upower.service"],
+    dbus_names: &["org.freedesktop.UPower"],
+    binary_packages: &[("upower", "upower"), ("upowerd", "upower")],
+    library_packages: &[("libupower-glib.so", "upower")],
+    feeds: &[FlatList::BinUtils, FlatList::SystemdUnits],
+    ..Component::EMPTY
+};
+
+/// D-Bus APIs of systemd's own daemons (timedated, logind, ...).
+///
+/// The daemons ship with systemd itself; this only records the bus names,
+/// which are activated through `dbus-<name>.service` aliases.
+pub const SYSTEMD_BUS: Component = Component {
+    name: "systemd-bus",
+    description: "D-Bus services of the systemd daemons",
+    dbus_names: &[
+        "org.freedesktop.timedate1",
+        "org.freedesktop.hostname1",
+        "org.freedesktop.locale1",
+        "org.freedesktop.login1",
+        "org.freedesktop.network1",
+        "org.freedesktop.resolve1",
+    ],
+    feeds: &[FlatList::DbusActivation],
+    ..Component::EMPTY
+};
+
+/// All registered components, in the order their entries appear in the flat lists.
+pub const COMPONENTS: &[&Component] = &[
+    &DBUS,
+    &SSH,
+    &CHRONY,
+    &NETWORKMANAGER,
+    &WPA_SUPPLICANT,
+    &POLKIT,
+    &PIPEWIRE,
+    &BLUETOOTH,
+    &UDISKS,
+    &UPOWER,
+    &SYSTEMD_BUS,
+];
+
+/// Look up a registered component by name.
+pub fn component(name: &str) -> Option<&'static Component> {
+    COMPONENTS.iter().copied().find(|c| c.name == name)
+}
+
+// =============================================================================
+// Derived Lists
+// =============================================================================
+
+/// A `Component` list field, used to build flat lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Bin,
+    Sbin,
+    Libexec,
+    Units,
+    UserUnits,
+    Config,
+    Users,
+    Groups,
+    DbusNames,
+}
+
+impl Field {
+    /// The field's value for `c`.
+    pub const fn of(self, c: &Component) -> &'static [&'static str] {
+        match self {
+            Field::Bin => c.bin,
+            Field::Sbin => c.sbin,
+            Field::Libexec => c.libexec,
+            Field::Units => c.units,
+            Field::UserUnits => c.user_units,
+            Field::Config => c.config,
+            Field::Users => c.users,
+            Field::Groups => c.groups,
+            Field::DbusNames => c.dbus_names,
+        }
+    }
+}
+
+/// A `Component` mapping field, used to build flat mapping tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairField {
+    BinaryPackages,
+    LibraryPackages,
+}
+
+impl PairField {
+    /// The field's value for `c`.
+    pub const fn of(self, c: &Component) -> &'static [(&'static str, &'static str)] {
+        match self {
+            PairField::BinaryPackages => c.binary_packages,
+            PairField::LibraryPackages => c.library_packages,
+        }
+    }
+}
+
+/// One piece of a derived list.
+pub(crate) enum Part<T: 'static, F> {
+    /// Literal entries
+    List(&'static [T]),
+    /// One field of every registered component
+    Field(F),
+    /// One field of the components that feed a flat list
+    Feeding(FlatList, F),
+}
+
+impl<T, F: Copy> Part<T, F> {
+    /// Field a `Field`/`Feeding` part reads, and the list filtering it.
+    const fn source(&self) -> Option<(Option<FlatList>, F)> {
+        match self {
+            Part::List(_) => None,
+            Part::Field(f) => Some((None, *f)),
+            Part::Feeding(list, f) => Some((Some(*list), *f)),
+        }
+    }
+}
+
+/// Whether `c` contributes to a part filtered by `list`.
+const fn selected(c: &Component, list: Option<FlatList>) -> bool {
+    match list {
+        None => true,
+        Some(list) => c.feeds_into(list),
+    }
+}
+
+macro_rules! derived_fns {
+    ($len:ident, $build:ident, $t:ty, $field:ty, $fill:expr) => {
+        pub(crate) const fn $len(parts: &[Part<$t, $field>]) -> usize {
+            let mut n = 0;
+            let mut i = 0;
+            while i < parts.len() {
+                match parts[i].source() {
+                    None => {
+                        if let Part::List(list) = &parts[i] {
+                            n += list.len();
+                        }
+                    }
+                    Some((list, f)) => {
+                        let mut c = 0;
+                        while c < COMPONENTS.len() {
+                            if selected(COMPONENTS[c], list) {
+                                n += f.of(COMPONENTS[c]).len();
+                            }
+                            c += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            n
+        }
+
+        pub(crate) const fn $build<const N: usize>(parts: &[Part<$t, $field>]) -> [$t; N] {
+            let mut out = [$fill; N];
+            let mut n = 0;
+            let mut i = 0;
+            while i < parts.len() {
+                match parts[i].source() {
+                    None => {
+                        if let Part::List(list) = &parts[i] {
+                            let mut j = 0;
+                            while j < list.len() {
+                                out[n] = list[j];
+                                n += 1;
+                                j += 1;
+                            }
+                        }
+                    }
+                    Some((list, f)) => {
+                        let mut c = 0;
+                        while c < COMPONENTS.len() {
+                            if selected(COMPONENTS[c], list) {
+                                let names = f.of(COMPONENTS[c]);
+                                let mut j = 0;
+                                while j < names.len() {
+                                    out[n] = names[j];
+                                    n += 1;
+                                    j += 1;
+                                }
+                            }
+                            c += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            out
+        }
+    };
+}
+
+derived_fns!(names_len, names, &'static str, Field, "");
+derived_fns!(
+    pairs_len,
+    pairs,
+    (&'static str, &'static str),
+    PairField,
+    ("", "")
+);
+
+// D-Bus activation aliases: `dbus-<name>.service` for every `dbus_names`
+// entry of the components feeding `FlatList::DbusActivation`, concatenated
+// into one byte buffer and then sliced back into names.
+
+const ALIAS_PREFIX: &[u8] = b"dbus-";
+const ALIAS_SUFFIX: &[u8] = b".service";
+
+/// `dbus_names` of the `c`-th component, or nothing if it doesn't feed the
+/// activation symlinks.
+const fn alias_sources(c: usize) -> &'static [&'static str] {
+    if COMPONENTS[c].feeds_into(FlatList::DbusActivation) {
+        COMPONENTS[c].dbus_names
+    } else {
+        &[]
+    }
+}
+
+pub(crate) const fn alias_count() -> usize {
+    let mut n = 0;
+    let mut c = 0;
+    while c < COMPONENTS.len() {
+        n += alias_sources(c).len();
+        c += 1;
+    }
+    n
+}
+
+pub(crate) const fn alias_bytes_len() -> usize {
+    let mut n = 0;
+    let mut c = 0;
+    while c < COMPONENTS.len() {
+        let names = alias_sources(c);
+        let mut j = 0;
+        while j < names.len() {
+            n += ALIAS_PREFIX.len() + names[j].len() + ALIAS_SUFFIX.len();
+            j += 1;
+        }
+        c += 1;
+    }
+    n
+}
+
+pub(crate) const fn alias_bytes<const N: usize>() -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut n = 0;
+    let mut c = 0;
+    while c < COMPONENTS.len() {
+        let names = alias_sources(c);
+        let mut j = 0;
+        while j < names.len() {
+            let parts = [ALIAS_PREFIX, names[j].as_bytes(), ALIAS_SUFFIX];
+            let mut p = 0;
+            while p < parts.len() {
+                let mut k = 0;
+                while k < parts[p].len() {
+                    out[n] = parts[p][k];
+                    n += 1;
+                    k += 1;
+                }
+                p += 1;
+            }
+            j += 1;
+        }
+        c += 1;
+    }
+    out
+}
+
+pub(crate) const fn dbus_aliases<const M: usize>(bytes: &'static [u8]) -> [&'static str; M] {
+    let mut out = [""; M];
+    let mut rest = bytes;
+    let mut n = 0;
+    let mut c = 0;
+    while c < COMPONENTS.len() {
+        let names = alias_sources(c);
+        let mut j = 0;
+        while j < names.len() {
+            let len = ALIAS_PREFIX.len() + names[j].len() + ALIAS_SUFFIX.len();
+            let (alias, tail) = rest.split_at(len);
+            out[n] = match core::str::from_utf8(alias) {
+                Ok(alias) => alias,
+                Err(_) => panic!("D-Bus name is not UTF-8"),
+            };
+            rest = tail;
+            n += 1;
+            j += 1;
+        }
+        c += 1;
+    }
+    out
+}
+
+/// `dbus-<name>.service` activation aliases for the D-Bus names of the
+/// components feeding `FlatList::DbusActivation`, in order.
+macro_rules! dbus_aliases_view {
+    () => {{
+        use $crate::shared::registry::{alias_bytes, alias_bytes_len, alias_count, dbus_aliases};
+        const BYTES: [u8; alias_bytes_len()] = alias_bytes();
+        const N: usize = alias_count();
+        &dbus_aliases::<N>(&BYTES)
+    }};
+}
+
+/// `&[&str]` built from literal lists and component fields, in order.
+macro_rules! names_view {
+    ($($part:expr),+ $(,)?) => {{
+        use $crate::shared::registry::{names, names_len, Field, Part};
+        const PARTS: &[Part<&str, Field>] = &[$($part),+];
+        const N: usize = names_len(PARTS);
+        &names::<N>(PARTS)
+    }};
+}
+
+/// `&[(&str, &str)]` built from literal tables and component fields, in order.
+macro_rules! pairs_view {
+    ($($part:expr),+ $(,)?) => {{
+        use $crate::shared::registry::{pairs, pairs_len, PairField, Part};
+        const PARTS: &[Part<(&str, &str), PairField>] = &[$($part),+];
+        const N: usize = pairs_len(PARTS);
+        &pairs::<N>(PARTS)
+    }};
+}
+
+pub(crate) use dbus_aliases_view;
+pub(crate) use names_view;
+pub(crate) use pairs_view;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::components::{
+        ALL_SYSTEMD_UNITS, BIN_UTILS, DBUS_ACTIVATION_SYMLINKS, NM_BIN, SBIN_UTILS, SSH_UNITS,
+        SYSTEM_USERS,
+    };
+    use crate::shared::licenses::package_for_binary;
+
+    #[test]
+    fn test_flat_lists_are_views() {
+        assert_eq!(NM_BIN, NETWORKMANAGER.bin);
+        assert!(SSH_UNITS.contains(&"sshd.socket"));
+        for c in COMPONENTS {
+            let feeds = |list| c.feeds_into(list);
+            for bin in c.bin {
+                assert_eq!(
+                    BIN_UTILS.contains(bin),
+                    feeds(FlatList::BinUtils),
+                    "{}",
+                    bin
+                );
+            }
+            for bin in c.sbin {
+                assert_eq!(
+                    SBIN_UTILS.contains(bin),
+                    feeds(FlatList::SbinUtils),
+                    "{}",
+                    bin
+                );
+            }
+            for unit in c.all_units() {
+                assert_eq!(
+                    ALL_SYSTEMD_UNITS.contains(&unit),
+                    feeds(FlatList::SystemdUnits),
+                    "{}",
+                    unit
+                );
+            }
+            for unit in c.optional_units {
+                assert!(!ALL_SYSTEMD_UNITS.contains(unit), "{} is optional", unit);
+            }
+            for user in c.users {
+                assert!(SYSTEM_USERS.contains(user));
+            }
+            for (bin, pkg) in c.binary_packages {
+                assert_eq!(package_for_binary(bin), Some(*pkg));
+            }
+        }
+        assert_eq!(SYSTEM_USERS[0], "root");
+    }
+
+    #[test]
+    fn test_dbus_activation_symlinks() {
+        assert_eq!(DBUS_ACTIVATION_SYMLINKS.len(), SYSTEMD_BUS.dbus_names.len());
+        assert_eq!(
+            DBUS_ACTIVATION_SYMLINKS[0],
+            "dbus-org.freedesktop.timedate1.service"
+        );
+        assert_eq!(
+            DBUS_ACTIVATION_SYMLINKS.last(),
+            Some(&"dbus-org.freedesktop.resolve1.service")
+        );
+        for c in COMPONENTS {
+            for name in c.dbus_names {
+                let alias = format!("dbus-{}.service", name);
+                assert_eq!(
+                    DBUS_ACTIVATION_SYMLINKS.contains(&alias.as_str()),
+                    c.feeds_into(FlatList::DbusActivation),
+                    "{}",
+                    alias
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_component_binaries_have_packages() {
+        for c in COMPONENTS {
+            for bin in c.bin.iter().chain(c.sbin) {
+                assert!(
+                    c.binary_packages.iter().any(|(b, _)| b == bin),
+                    "{}: {} has no package mapping",
+                    c.name,
+                    bin
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_packages() {
+        let nm = component("networkmanager").unwrap();
+        assert_eq!(
+            nm.packages(),
+            vec!["NetworkManager", "NetworkManager-libnm"]
+        );
+        assert_eq!(nm.license_dirs()[0], "usr/share/licenses/NetworkManager");
+        assert!(component("nope").is_none());
+
+        let mut names: Vec<_> = COMPONENTS.iter().map(|c| c.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), COMPONENTS.len());
+    }
+}
@@ -5,6 +5,12 @@
 //!
 //! When reasoning about hardware, think modern desktop/laptop (2020+), not server/embedded.
 
+/// Bytes per gigabyte as used by `min_disk_gb`/`recommended_disk_gb`.
+///
+/// Decimal, because that is how drives are sold: a "64 GB" drive has
+/// 64 * 10^9 bytes and should pass a 64 GB requirement.
+pub const BYTES_PER_GB: u64 = 1_000_000_000;
+
 /// Hardware requirements for system installation.
 ///
 /// These values define the floor for a usable daily-driver desktop experience.
@@ -74,8 +80,8 @@ mod tests {
     #[test]
     fn levitate_requirements_are_sane() {
         // Minimum should be less than or equal to recommended
-        assert!(LEVITATE_REQUIREMENTS.min_ram_gb <= LEVITATE_REQUIREMENTS.recommended_ram_gb);
-        assert!(LEVITATE_REQUIREMENTS.min_disk_gb <= LEVITATE_REQUIREMENTS.recommended_disk_gb);
+        const { assert!(LEVITATE_REQUIREMENTS.min_ram_gb <= LEVITATE_REQUIREMENTS.recommended_ram_gb) };
+        const { assert!(LEVITATE_REQUIREMENTS.min_disk_gb <= LEVITATE_REQUIREMENTS.recommended_disk_gb) };
 
         // Should have at least 2 CPU vendors
         assert!(LEVITATE_REQUIREMENTS.supported_vendors.len() >= 2);
@@ -84,12 +90,12 @@ mod tests {
         assert!(LEVITATE_REQUIREMENTS.gpu_vendors.len() >= 2);
 
         // Minimum RAM should be at least 8GB for a daily-driver desktop
-        assert!(LEVITATE_REQUIREMENTS.min_ram_gb >= 8);
+        const { assert!(LEVITATE_REQUIREMENTS.min_ram_gb >= 8) };
     }
 
     #[test]
     fn acorn_requirements_are_sane() {
-        assert!(ACORN_REQUIREMENTS.min_ram_gb <= ACORN_REQUIREMENTS.recommended_ram_gb);
-        assert!(ACORN_REQUIREMENTS.min_disk_gb <= ACORN_REQUIREMENTS.recommended_disk_gb);
+        const { assert!(ACORN_REQUIREMENTS.min_ram_gb <= ACORN_REQUIREMENTS.recommended_ram_gb) };
+        const { assert!(ACORN_REQUIREMENTS.min_disk_gb <= ACORN_REQUIREMENTS.recommended_disk_gb) };
     }
 }
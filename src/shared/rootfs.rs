@@ -16,6 +16,11 @@
 //! - `ESSENTIAL_DIRS` - directories that must exist after extraction
 //! - `MIN_REQUIRED_BYTES` - minimum disk space for installation
 //! - Magic byte constants for format detection
+//! - `detect_rootfs_format()`, `read_superblock()` and `find_rootfs()`, which use them
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 
 // =============================================================================
 // EROFS Build Parameters (Primary)
@@ -81,6 +86,9 @@ pub const SQUASHFS_COMPRESSION: &str = "zstd";
 /// 1MB blocks provide good compression ratio for the base system.
 pub const SQUASHFS_BLOCK_SIZE: &str = "1M";
 
+/// `SQUASHFS_BLOCK_SIZE` in bytes, as stored in the superblock.
+pub const SQUASHFS_BLOCK_SIZE_BYTES: u32 = 1024 * 1024;
+
 /// Name of the squashfs image file (legacy).
 pub const SQUASHFS_NAME: &str = "filesystem.squashfs";
 
@@ -141,9 +149,341 @@ pub const EROFS_MAGIC_OFFSET: u64 = 1024;
 /// The magic is "hsqs" (little-endian "sqsh").
 pub const SQUASHFS_MAGIC: &[u8; 4] = b"hsqs";
 
+/// Block size of EROFS images built by our tools (mkfs.erofs default).
+pub const EROFS_BLOCK_SIZE: u32 = 4096;
+
+/// EROFS feature_incompat bit: per-algorithm compression configs present.
+///
+/// When set, `available_compr_algs` in the superblock is a bitmap of the
+/// algorithms used in the image.
+pub const EROFS_FEATURE_INCOMPAT_COMPR_CFGS: u32 = 0x0000_0002;
+
+/// EROFS compression algorithm names, indexed by bit in `available_compr_algs`.
+pub const EROFS_COMPRESSION_ALGORITHMS: &[&str] = &["lz4", "lzma", "deflate", "zstd"];
+
+/// Squashfs compression names, indexed by compression ID (0 is unused).
+pub const SQUASHFS_COMPRESSION_ALGORITHMS: &[&str] = &["", "gzip", "lzma", "lzo", "xz", "lz4", "zstd"];
+
+// =============================================================================
+// Superblock Readers
+// =============================================================================
+
+/// On-disk rootfs image format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootfsFormat {
+    Erofs,
+    Squashfs,
+}
+
+impl RootfsFormat {
+    /// Filesystem type name as passed to `mount -t`.
+    pub fn fs_type(&self) -> &'static str {
+        match self {
+            RootfsFormat::Erofs => "erofs",
+            RootfsFormat::Squashfs => "squashfs",
+        }
+    }
+
+    /// Compression algorithm our build uses for this format.
+    pub fn expected_compression(&self) -> &'static str {
+        match self {
+            RootfsFormat::Erofs => EROFS_COMPRESSION,
+            RootfsFormat::Squashfs => SQUASHFS_COMPRESSION,
+        }
+    }
+}
+
+/// Metadata read from an EROFS or squashfs superblock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootfsSuperblock {
+    /// Image format
+    pub format: RootfsFormat,
+    /// Filesystem block size in bytes
+    pub block_size: u32,
+    /// Compression algorithms used in the image.
+    ///
+    /// Empty for EROFS images without compression configs (uncompressed or
+    /// legacy lz4-only images).
+    pub compression: Vec<&'static str>,
+    /// Number of inodes
+    pub inode_count: u64,
+    /// Build time (seconds since the Unix epoch)
+    pub build_time: u64,
+    /// Filesystem UUID (EROFS only; squashfs has none)
+    pub uuid: Option<[u8; 16]>,
+}
+
+impl RootfsSuperblock {
+    /// UUID in the canonical 8-4-4-4-12 form.
+    pub fn uuid_string(&self) -> Option<String> {
+        let u = self.uuid?;
+        let hex: String = u.iter().map(|b| format!("{:02x}", b)).collect();
+        Some(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        ))
+    }
+
+    /// Check the image against our build parameters.
+    ///
+    /// Returns one message per mismatch; an empty list means the image was
+    /// built the way `EROFS_COMPRESSION`/`SQUASHFS_COMPRESSION` say it should be.
+    pub fn verify(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let expected = self.format.expected_compression();
+        if !self.compression.contains(&expected) {
+            problems.push(format!(
+                "{} image compressed with [{}], expected {}",
+                self.format.fs_type(),
+                self.compression.join(", "),
+                expected
+            ));
+        }
+        let expected_block = match self.format {
+            RootfsFormat::Erofs => EROFS_BLOCK_SIZE,
+            RootfsFormat::Squashfs => SQUASHFS_BLOCK_SIZE_BYTES,
+        };
+        if self.block_size != expected_block {
+            problems.push(format!(
+                "{} block size is {}, expected {}",
+                self.format.fs_type(),
+                self.block_size,
+                expected_block
+            ));
+        }
+        problems
+    }
+}
+
+/// Detect the rootfs format of an image by its magic bytes.
+///
+/// Returns `Ok(None)` if the file is neither EROFS nor squashfs.
+pub fn detect_rootfs_format(path: impl AsRef<Path>) -> io::Result<Option<RootfsFormat>> {
+    let mut file = File::open(path)?;
+    detect_format(&mut file)
+}
+
+pub(crate) fn detect_format<R: Read + Seek>(reader: &mut R) -> io::Result<Option<RootfsFormat>> {
+    let mut magic = [0u8; 4];
+    reader.seek(SeekFrom::Start(0))?;
+    if reader.read_exact(&mut magic).is_ok() && &magic == SQUASHFS_MAGIC {
+        return Ok(Some(RootfsFormat::Squashfs));
+    }
+    reader.seek(SeekFrom::Start(EROFS_MAGIC_OFFSET))?;
+    if reader.read_exact(&mut magic).is_ok() && u32::from_le_bytes(magic) == EROFS_MAGIC {
+        return Ok(Some(RootfsFormat::Erofs));
+    }
+    Ok(None)
+}
+
+/// Read the superblock of an EROFS or squashfs image.
+///
+/// Fails with `InvalidData` if the file has neither magic.
+pub fn read_superblock(path: impl AsRef<Path>) -> io::Result<RootfsSuperblock> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+    match detect_format(&mut file)? {
+        Some(RootfsFormat::Erofs) => read_erofs_superblock(&mut file),
+        Some(RootfsFormat::Squashfs) => read_squashfs_superblock(&mut file),
+        None => Err(invalid(format!(
+            "{} is not an EROFS or squashfs image",
+            path.display()
+        ))),
+    }
+}
+
+/// `InvalidData` error for a malformed image.
+pub(super) fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+pub(super) fn le_u16(b: &[u8], off: usize) -> u16 {
+    u16::from_le_bytes([b[off], b[off + 1]])
+}
+
+pub(super) fn le_u32(b: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(b[off..off + 4].try_into().unwrap())
+}
+
+fn le_u64(b: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(b[off..off + 8].try_into().unwrap())
+}
+
+/// Parse `struct erofs_super_block` (128 bytes at offset 1024).
+fn read_erofs_superblock<R: Read + Seek>(reader: &mut R) -> io::Result<RootfsSuperblock> {
+    let mut sb = [0u8; 128];
+    reader.seek(SeekFrom::Start(EROFS_MAGIC_OFFSET))?;
+    reader.read_exact(&mut sb)?;
+
+    let blkszbits = sb[12];
+    if !(9..=16).contains(&blkszbits) {
+        return Err(invalid(format!(
+            "EROFS block size bits {} out of range",
+            blkszbits
+        )));
+    }
+    let feature_incompat = le_u32(&sb, 80);
+    let compression = if feature_incompat & EROFS_FEATURE_INCOMPAT_COMPR_CFGS != 0 {
+        let algs = le_u16(&sb, 84);
+        EROFS_COMPRESSION_ALGORITHMS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| algs & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(RootfsSuperblock {
+        format: RootfsFormat::Erofs,
+        block_size: 1 << blkszbits,
+        compression,
+        inode_count: le_u64(&sb, 16),
+        build_time: le_u64(&sb, 24),
+        uuid: Some(sb[48..64].try_into().unwrap()),
+    })
+}
+
+/// Parse the squashfs 4.0 superblock (96 bytes at offset 0).
+fn read_squashfs_superblock<R: Read + Seek>(reader: &mut R) -> io::Result<RootfsSuperblock> {
+    let mut sb = [0u8; 96];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut sb)?;
+
+    let major = le_u16(&sb, 28);
+    if major != 4 {
+        return Err(invalid(format!("unsupported squashfs version {}", major)));
+    }
+    let compression_id = le_u16(&sb, 20) as usize;
+    let compression = SQUASHFS_COMPRESSION_ALGORITHMS
+        .get(compression_id)
+        .filter(|name| !name.is_empty())
+        .map(|name| vec![*name])
+        .ok_or_else(|| invalid(format!("unknown squashfs compression id {}", compression_id)))?;
+
+    Ok(RootfsSuperblock {
+        format: RootfsFormat::Squashfs,
+        block_size: le_u32(&sb, 12),
+        compression,
+        inode_count: u64::from(le_u32(&sb, 4)),
+        build_time: u64::from(le_u32(&sb, 8)),
+        uuid: None,
+    })
+}
+
+/// Find the live rootfs image on the running system.
+///
+/// Walks `ROOTFS_SEARCH_PATHS` in order and returns the first file whose
+/// magic bytes identify it as EROFS or squashfs. Files that exist but are
+/// not valid images (truncated downloads, placeholders) are skipped.
+pub fn find_rootfs() -> Option<(PathBuf, RootfsFormat)> {
+    find_rootfs_in("/")
+}
+
+/// Like `find_rootfs()`, but with `ROOTFS_SEARCH_PATHS` resolved under `root`.
+pub fn find_rootfs_in(root: impl AsRef<Path>) -> Option<(PathBuf, RootfsFormat)> {
+    ROOTFS_SEARCH_PATHS.iter().find_map(|p| {
+        let path = root.as_ref().join(p.trim_start_matches('/'));
+        let format = detect_rootfs_format(&path).ok()??;
+        Some((path, format))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shared::test_util::TempDir;
+
+    fn erofs_image(algs: u16) -> Vec<u8> {
+        let mut image = vec![0u8; 8192];
+        let sb = &mut image[EROFS_MAGIC_OFFSET as usize..];
+        sb[0..4].copy_from_slice(&EROFS_MAGIC.to_le_bytes());
+        sb[12] = 12; // 4096-byte blocks
+        sb[16..24].copy_from_slice(&12345u64.to_le_bytes());
+        sb[24..32].copy_from_slice(&1_700_000_000u64.to_le_bytes());
+        sb[48..64].copy_from_slice(&[
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+            0xcd, 0xef,
+        ]);
+        sb[80..84].copy_from_slice(&EROFS_FEATURE_INCOMPAT_COMPR_CFGS.to_le_bytes());
+        sb[84..86].copy_from_slice(&algs.to_le_bytes());
+        image
+    }
+
+    fn squashfs_image(compression_id: u16, block_size: u32) -> Vec<u8> {
+        let mut image = vec![0u8; 4096];
+        image[0..4].copy_from_slice(SQUASHFS_MAGIC);
+        image[4..8].copy_from_slice(&500u32.to_le_bytes());
+        image[8..12].copy_from_slice(&1_600_000_000u32.to_le_bytes());
+        image[12..16].copy_from_slice(&block_size.to_le_bytes());
+        image[20..22].copy_from_slice(&compression_id.to_le_bytes());
+        image[28..30].copy_from_slice(&4u16.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn test_read_erofs_superblock() {
+        let dir = TempDir::new("erofs");
+        let path = dir.write("filesystem.erofs", erofs_image(1 << 3));
+        assert_eq!(detect_rootfs_format(&path).unwrap(), Some(RootfsFormat::Erofs));
+
+        let sb = read_superblock(&path).unwrap();
+        assert_eq!(sb.block_size, 4096);
+        assert_eq!(sb.compression, ["zstd"]);
+        assert_eq!(sb.inode_count, 12345);
+        assert_eq!(sb.build_time, 1_700_000_000);
+        assert_eq!(
+            sb.uuid_string().unwrap(),
+            "12345678-9abc-def0-0123-456789abcdef"
+        );
+        assert!(sb.verify().is_empty());
+
+        let lz4 = dir.write("lz4.erofs", erofs_image(1));
+        let problems = read_superblock(&lz4).unwrap().verify();
+        assert_eq!(problems, ["erofs image compressed with [lz4], expected zstd"]);
+    }
+
+    #[test]
+    fn test_read_squashfs_superblock() {
+        let dir = TempDir::new("squashfs");
+        let path = dir.write("filesystem.squashfs", squashfs_image(6, 1 << 20));
+        let sb = read_superblock(&path).unwrap();
+        assert_eq!(sb.format, RootfsFormat::Squashfs);
+        assert_eq!(sb.compression, ["zstd"]);
+        assert_eq!(sb.inode_count, 500);
+        assert_eq!(sb.uuid, None);
+        assert!(sb.verify().is_empty());
+
+        let gzip = dir.write("gzip.squashfs", squashfs_image(1, 128 * 1024));
+        assert_eq!(read_superblock(&gzip).unwrap().verify().len(), 2);
+    }
+
+    #[test]
+    fn test_not_a_rootfs() {
+        let dir = TempDir::new("notrootfs");
+        let path = dir.write("junk", vec![0u8; 4096]);
+        assert_eq!(detect_rootfs_format(&path).unwrap(), None);
+        let err = read_superblock(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_find_rootfs_verifies_magic() {
+        let dir = TempDir::new("findrootfs");
+        assert_eq!(find_rootfs_in(dir.path()), None);
+
+        // Placeholder at the preferred path is skipped in favour of a real image
+        dir.write("media/cdrom/live/filesystem.erofs", b"not an image");
+        dir.write("run/initramfs/live/filesystem.squashfs", squashfs_image(6, 1 << 20));
+        let (path, format) = find_rootfs_in(dir.path()).unwrap();
+        assert_eq!(format, RootfsFormat::Squashfs);
+        assert!(path.ends_with("run/initramfs/live/filesystem.squashfs"));
+    }
 
     #[test]
     fn test_essential_dirs_list() {
@@ -182,8 +522,8 @@ mod tests {
     #[test]
     fn test_min_required_bytes_is_reasonable() {
         // Should be at least 1GB, at most 10GB
-        assert!(MIN_REQUIRED_BYTES >= 1024 * 1024 * 1024);
-        assert!(MIN_REQUIRED_BYTES <= 10 * 1024 * 1024 * 1024);
+        const { assert!(MIN_REQUIRED_BYTES >= 1024 * 1024 * 1024) };
+        const { assert!(MIN_REQUIRED_BYTES <= 10 * 1024 * 1024 * 1024) };
     }
 
     #[test]
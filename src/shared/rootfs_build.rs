@@ -0,0 +1,466 @@
+//! Rootfs image build parameters (mkfs.erofs / mksquashfs argv).
+//!
+//! The constants in `rootfs.rs` say *what* the image should look like; these
+//! types turn them into the exact command line, so leviso and AcornOS's
+//! builder don't each assemble `-z`/`-C`/`-b` flags by hand.
+//!
+//! Reproducibility options are part of the parameters:
+//! - a fixed timestamp (`-T` / `-mkfs-time` + `-all-time`, and `SOURCE_DATE_EPOCH`)
+//! - `--all-root` / `-all-root` so build-user ownership never leaks into the image
+//! - a fixed UUID (EROFS only; squashfs has none)
+
+use std::fmt;
+
+use super::rootfs::{
+    EROFS_BLOCK_SIZE, EROFS_CHUNK_SIZE, EROFS_COMPRESSION, EROFS_COMPRESSION_LEVEL,
+    SQUASHFS_BLOCK_SIZE_BYTES, SQUASHFS_COMPRESSION,
+};
+use crate::variant::Variant;
+
+/// Environment variable honoured by mkfs.erofs, mksquashfs and most build tools.
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Valid compression levels per algorithm (inclusive), for both tools.
+///
+/// Algorithms that take no level (e.g., lz4 for mkfs.erofs) are absent.
+pub const COMPRESSION_LEVEL_RANGES: &[(&str, u8, u8)] = &[
+    ("zstd", 1, 22),
+    ("lz4hc", 0, 12),
+    ("lzma", 0, 9),
+    ("deflate", 0, 9),
+    ("gzip", 1, 9),
+    ("lzo", 1, 9),
+];
+
+/// Smallest mkfs.erofs physical cluster size (-C), one EROFS block.
+pub const EROFS_MIN_CHUNK_SIZE: u32 = EROFS_BLOCK_SIZE;
+
+/// Largest mkfs.erofs physical cluster size (-C) we allow.
+pub const EROFS_MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Smallest mksquashfs block size (-b).
+pub const SQUASHFS_MIN_BLOCK_SIZE: u32 = 4096;
+
+/// Largest mksquashfs block size (-b).
+pub const SQUASHFS_MAX_BLOCK_SIZE: u32 = 1024 * 1024;
+
+/// An out-of-range or malformed build parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildParamError {
+    /// Parameter name (e.g., "compression_level")
+    pub param: &'static str,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl BuildParamError {
+    fn new(param: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            param,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for BuildParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.param, self.message)
+    }
+}
+
+impl std::error::Error for BuildParamError {}
+
+fn check_level(algorithm: &str, level: Option<u8>) -> Result<(), BuildParamError> {
+    let Some(level) = level else {
+        return Ok(());
+    };
+    match COMPRESSION_LEVEL_RANGES
+        .iter()
+        .find(|(a, _, _)| *a == algorithm)
+    {
+        Some(&(_, min, max)) if (min..=max).contains(&level) => Ok(()),
+        Some(&(_, min, max)) => Err(BuildParamError::new(
+            "compression_level",
+            format!("{} level {} not in {}..={}", algorithm, level, min, max),
+        )),
+        None => Err(BuildParamError::new(
+            "compression_level",
+            format!("{} does not take a compression level", algorithm),
+        )),
+    }
+}
+
+fn check_power_of_two(
+    param: &'static str,
+    value: u32,
+    min: u32,
+    max: u32,
+) -> Result<(), BuildParamError> {
+    if !value.is_power_of_two() || !(min..=max).contains(&value) {
+        return Err(BuildParamError::new(
+            param,
+            format!("{} must be a power of two in {}..={}", value, min, max),
+        ));
+    }
+    Ok(())
+}
+
+fn check_uuid(uuid: &str) -> Result<(), BuildParamError> {
+    let groups: Vec<&str> = uuid.split('-').collect();
+    let lengths: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+    let hex = groups
+        .iter()
+        .all(|g| g.bytes().all(|b| b.is_ascii_hexdigit()));
+    if lengths != [8, 4, 4, 4, 12] || !hex {
+        return Err(BuildParamError::new(
+            "uuid",
+            format!("{:?} is not in 8-4-4-4-12 hex form", uuid),
+        ));
+    }
+    Ok(())
+}
+
+fn check_excludes(excludes: &[String]) -> Result<(), BuildParamError> {
+    for path in excludes {
+        if path.is_empty() || path.starts_with('/') || path.split('/').any(|c| c == "..") {
+            return Err(BuildParamError::new(
+                "exclude",
+                format!("{:?} must be a relative path inside the source tree", path),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// =============================================================================
+// EROFS
+// =============================================================================
+
+/// Parameters for `mkfs.erofs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErofsBuildParams {
+    /// Compression algorithm (-z)
+    pub compression: &'static str,
+    /// Compression level, if the algorithm takes one
+    pub compression_level: Option<u8>,
+    /// Physical cluster size in bytes (-C)
+    pub chunk_size: u32,
+    /// Fixed timestamp for every inode and the superblock (-T)
+    pub timestamp: Option<u64>,
+    /// Fixed filesystem UUID (-U)
+    pub uuid: Option<String>,
+    /// Make every file owned by root:root (--all-root)
+    pub all_root: bool,
+    /// Paths relative to the source tree to leave out (--exclude-path)
+    pub excludes: Vec<String>,
+}
+
+impl ErofsBuildParams {
+    /// LevitateOS rootfs: `EROFS_COMPRESSION` at `EROFS_COMPRESSION_LEVEL`.
+    pub fn levitate() -> Self {
+        Self {
+            compression: EROFS_COMPRESSION,
+            compression_level: Some(EROFS_COMPRESSION_LEVEL),
+            chunk_size: EROFS_CHUNK_SIZE,
+            timestamp: None,
+            uuid: None,
+            all_root: true,
+            excludes: Vec::new(),
+        }
+    }
+
+    /// AcornOS rootfs: same algorithm, lower level for faster builds.
+    pub fn acorn() -> Self {
+        Self {
+            compression_level: Some(crate::acorn::EROFS_COMPRESSION_LEVEL),
+            ..Self::levitate()
+        }
+    }
+
+    /// Parameters for a variant's live rootfs.
+    pub fn for_variant(variant: Variant) -> Self {
+        match variant {
+            Variant::Levitate => Self::levitate(),
+            Variant::Acorn => Self::acorn(),
+        }
+    }
+
+    /// Set a fixed timestamp (usually `SOURCE_DATE_EPOCH`).
+    pub fn with_timestamp(mut self, epoch: u64) -> Self {
+        self.timestamp = Some(epoch);
+        self
+    }
+
+    /// Set a fixed filesystem UUID.
+    pub fn with_uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.uuid = Some(uuid.into());
+        self
+    }
+
+    /// Set the compression level.
+    pub fn with_compression_level(mut self, level: u8) -> Self {
+        self.compression_level = Some(level);
+        self
+    }
+
+    /// Leave a path (relative to the source tree) out of the image.
+    pub fn with_exclude(mut self, path: impl Into<String>) -> Self {
+        self.excludes.push(path.into());
+        self
+    }
+
+    /// Check every parameter is in range.
+    pub fn validate(&self) -> Result<(), BuildParamError> {
+        check_level(self.compression, self.compression_level)?;
+        check_power_of_two(
+            "chunk_size",
+            self.chunk_size,
+            EROFS_MIN_CHUNK_SIZE,
+            EROFS_MAX_CHUNK_SIZE,
+        )?;
+        if let Some(ref uuid) = self.uuid {
+            check_uuid(uuid)?;
+        }
+        check_excludes(&self.excludes)
+    }
+
+    /// Full `mkfs.erofs` argv building `image` from the `source` directory.
+    pub fn argv(&self, image: &str, source: &str) -> Result<Vec<String>, BuildParamError> {
+        self.validate()?;
+        let mut argv = vec!["mkfs.erofs".to_string()];
+        argv.push(match self.compression_level {
+            Some(level) => format!("-z{},level={}", self.compression, level),
+            None => format!("-z{}", self.compression),
+        });
+        argv.push(format!("-C{}", self.chunk_size));
+        if let Some(epoch) = self.timestamp {
+            argv.push(format!("-T{}", epoch));
+        }
+        if let Some(ref uuid) = self.uuid {
+            argv.push(format!("-U{}", uuid));
+        }
+        if self.all_root {
+            argv.push("--all-root".to_string());
+        }
+        for path in &self.excludes {
+            argv.push(format!("--exclude-path={}", path));
+        }
+        argv.push(image.to_string());
+        argv.push(source.to_string());
+        Ok(argv)
+    }
+
+    /// Environment to run the command with (`SOURCE_DATE_EPOCH` when a timestamp is set).
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        self.timestamp
+            .map(|t| (SOURCE_DATE_EPOCH, t.to_string()))
+            .into_iter()
+            .collect()
+    }
+}
+
+// =============================================================================
+// Squashfs (legacy)
+// =============================================================================
+
+/// Parameters for `mksquashfs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SquashfsBuildParams {
+    /// Compression algorithm (-comp)
+    pub compression: &'static str,
+    /// Compression level (-Xcompression-level), if set
+    pub compression_level: Option<u8>,
+    /// Block size in bytes (-b)
+    pub block_size: u32,
+    /// Fixed filesystem and inode timestamp (-mkfs-time, -all-time)
+    pub timestamp: Option<u64>,
+    /// Make every file owned by root:root (-all-root)
+    pub all_root: bool,
+    /// Paths relative to the source tree to leave out (-e)
+    pub excludes: Vec<String>,
+}
+
+impl SquashfsBuildParams {
+    /// LevitateOS legacy squashfs rootfs.
+    pub fn levitate() -> Self {
+        Self {
+            compression: SQUASHFS_COMPRESSION,
+            compression_level: None,
+            block_size: SQUASHFS_BLOCK_SIZE_BYTES,
+            timestamp: None,
+            all_root: true,
+            excludes: Vec::new(),
+        }
+    }
+
+    /// Parameters for a variant's squashfs rootfs.
+    ///
+    /// Returns `None` for AcornOS, which ships EROFS only.
+    pub fn for_variant(variant: Variant) -> Option<Self> {
+        match variant {
+            Variant::Levitate => Some(Self::levitate()),
+            Variant::Acorn => None,
+        }
+    }
+
+    /// Set a fixed timestamp (usually `SOURCE_DATE_EPOCH`).
+    pub fn with_timestamp(mut self, epoch: u64) -> Self {
+        self.timestamp = Some(epoch);
+        self
+    }
+
+    /// Set the compression level.
+    pub fn with_compression_level(mut self, level: u8) -> Self {
+        self.compression_level = Some(level);
+        self
+    }
+
+    /// Leave a path (relative to the source tree) out of the image.
+    pub fn with_exclude(mut self, path: impl Into<String>) -> Self {
+        self.excludes.push(path.into());
+        self
+    }
+
+    /// Check every parameter is in range.
+    pub fn validate(&self) -> Result<(), BuildParamError> {
+        check_level(self.compression, self.compression_level)?;
+        check_power_of_two(
+            "block_size",
+            self.block_size,
+            SQUASHFS_MIN_BLOCK_SIZE,
+            SQUASHFS_MAX_BLOCK_SIZE,
+        )?;
+        check_excludes(&self.excludes)
+    }
+
+    /// Full `mksquashfs` argv building `image` from the `source` directory.
+    pub fn argv(&self, image: &str, source: &str) -> Result<Vec<String>, BuildParamError> {
+        self.validate()?;
+        let mut argv: Vec<String> = [
+            "mksquashfs",
+            source,
+            image,
+            "-noappend",
+            "-comp",
+            self.compression,
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        if let Some(level) = self.compression_level {
+            argv.extend(["-Xcompression-level".to_string(), level.to_string()]);
+        }
+        argv.extend(["-b".to_string(), self.block_size.to_string()]);
+        if let Some(epoch) = self.timestamp {
+            argv.extend(["-mkfs-time".to_string(), epoch.to_string()]);
+            argv.extend(["-all-time".to_string(), epoch.to_string()]);
+        }
+        if self.all_root {
+            argv.push("-all-root".to_string());
+        }
+        // -e consumes every following argument, so it must come last
+        if !self.excludes.is_empty() {
+            argv.push("-e".to_string());
+            argv.extend(self.excludes.iter().cloned());
+        }
+        Ok(argv)
+    }
+
+    /// Environment to run the command with (`SOURCE_DATE_EPOCH` when a timestamp is set).
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        self.timestamp
+            .map(|t| (SOURCE_DATE_EPOCH, t.to_string()))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erofs_argv() {
+        let argv = ErofsBuildParams::levitate()
+            .with_timestamp(1_700_000_000)
+            .with_uuid("12345678-9abc-def0-0123-456789abcdef")
+            .with_exclude("var/cache/dnf")
+            .argv("out/filesystem.erofs", "staging")
+            .unwrap();
+        assert_eq!(
+            argv,
+            [
+                "mkfs.erofs",
+                "-zzstd,level=6",
+                "-C1048576",
+                "-T1700000000",
+                "-U12345678-9abc-def0-0123-456789abcdef",
+                "--all-root",
+                "--exclude-path=var/cache/dnf",
+                "out/filesystem.erofs",
+                "staging",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variants_share_one_code_path() {
+        let levitate = ErofsBuildParams::for_variant(Variant::Levitate);
+        let acorn = ErofsBuildParams::for_variant(Variant::Acorn);
+        assert_eq!(levitate.compression_level, Some(6));
+        assert_eq!(acorn.compression_level, Some(3));
+        assert_eq!(levitate.chunk_size, acorn.chunk_size);
+        assert_eq!(levitate.compression, acorn.compression);
+        assert!(SquashfsBuildParams::for_variant(Variant::Acorn).is_none());
+    }
+
+    #[test]
+    fn test_squashfs_argv() {
+        let params = SquashfsBuildParams::levitate()
+            .with_timestamp(42)
+            .with_compression_level(19)
+            .with_exclude("proc/kcore");
+        assert_eq!(
+            params
+                .argv("filesystem.squashfs", "staging")
+                .unwrap()
+                .join(" "),
+            "mksquashfs staging filesystem.squashfs -noappend -comp zstd -Xcompression-level 19 \
+             -b 1048576 -mkfs-time 42 -all-time 42 -all-root -e proc/kcore"
+        );
+        assert_eq!(params.env(), [(SOURCE_DATE_EPOCH, "42".to_string())]);
+    }
+
+    #[test]
+    fn test_range_validation() {
+        let err = ErofsBuildParams::levitate()
+            .with_compression_level(23)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.param, "compression_level");
+
+        let mut params = ErofsBuildParams::levitate();
+        params.chunk_size = 3000;
+        assert_eq!(params.validate().unwrap_err().param, "chunk_size");
+
+        assert!(ErofsBuildParams::levitate()
+            .with_uuid("not-a-uuid")
+            .validate()
+            .is_err());
+        assert!(ErofsBuildParams::levitate()
+            .with_exclude("/etc")
+            .validate()
+            .is_err());
+        assert!(ErofsBuildParams::levitate()
+            .with_exclude("../x")
+            .validate()
+            .is_err());
+
+        let mut squash = SquashfsBuildParams::levitate();
+        squash.block_size = 2 * 1024 * 1024;
+        assert_eq!(squash.validate().unwrap_err().param, "block_size");
+        squash = SquashfsBuildParams::levitate();
+        squash.compression = "xz";
+        assert!(squash.with_compression_level(5).validate().is_err());
+    }
+}
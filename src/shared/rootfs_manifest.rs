@@ -0,0 +1,921 @@
+//! Rootfs manifest verifier.
+//!
+//! `components.rs` says what a LevitateOS rootfs must contain; this module
+//! checks a rootfs directory against it. `Manifest::from_components()` turns
+//! the component lists into expected entries, and `Manifest::verify()` walks
+//! the tree and reports what is missing, has the wrong type, or is a dangling
+//! symlink. `Manifest::for_profile()` does the same for a smaller install
+//! profile.
+//!
+//! Paths are resolved inside the rootfs: absolute symlink targets are taken
+//! relative to the rootfs root, so a merged-usr tree (`bin -> usr/bin`) is
+//! checked the way it will look after boot, not the way it looks on the host.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
+
+use super::auth::components::{
+    AUTH_BIN, AUTH_SBIN, PAM_CONFIGS, PAM_MODULES, SECURITY_FILES, SHADOW_SBIN, SUDO_LIBS,
+};
+use super::components::{
+    critical_libs, ALL_SYSTEMD_UNITS, BIN_UTILS, ETC_FILES, FHS_DIRS, FHS_SYMLINKS, SBIN_UTILS,
+    SYSTEMD_BINARIES,
+};
+use super::profile::Profile;
+use super::registry::{Field, FlatList, COMPONENTS};
+use crate::arch::Arch;
+
+/// Symlinks followed while resolving one path before giving up (as in Linux).
+pub const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Directories systemd loads unit files from, in lookup order.
+pub const UNIT_SEARCH_DIRS: &[&str] = &[
+    "etc/systemd/system",
+    "usr/lib/systemd/system",
+    "usr/lib/systemd/user",
+];
+
+/// Where sudo's plugins and `libsudo_util` are installed.
+pub const SUDO_LIBEXEC_DIR: &str = "usr/libexec/sudo";
+
+// =============================================================================
+// Expected Entries
+// =============================================================================
+
+/// How bad a problem with an entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Degraded functionality, system still boots and logs in
+    Warning,
+    /// A listed component is broken
+    Error,
+    /// The system will not boot or nobody can log in
+    Critical,
+}
+
+impl Severity {
+    /// Lowercase name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What must be at an entry's path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A directory (symlinks to one are fine)
+    Dir,
+    /// A regular file (symlinks to one are fine)
+    File,
+    /// A regular file with an execute bit
+    Executable,
+    /// A symlink itself, pointing at this rootfs-relative path
+    Symlink(&'static str),
+    /// Anything (after following symlinks)
+    Any,
+}
+
+impl EntryKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            EntryKind::Dir => "directory",
+            EntryKind::File => "file",
+            EntryKind::Executable => "executable",
+            EntryKind::Symlink(_) => "symlink",
+            EntryKind::Any => "file or directory",
+        }
+    }
+}
+
+/// One expected path in the rootfs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Component the entry comes from ("bin", "systemd-units", "pam", ...)
+    pub component: &'static str,
+    /// Rootfs-relative paths; the entry is satisfied if any of them is
+    pub paths: Vec<String>,
+    /// Expected type
+    pub kind: EntryKind,
+    /// Severity when the entry is not satisfied
+    pub severity: Severity,
+}
+
+impl ManifestEntry {
+    /// Entry with a single path.
+    pub fn new(
+        component: &'static str,
+        path: impl Into<String>,
+        kind: EntryKind,
+        severity: Severity,
+    ) -> Self {
+        Self {
+            component,
+            paths: vec![path.into()],
+            kind,
+            severity,
+        }
+    }
+
+    /// Also accept `path`.
+    pub fn or_at(mut self, path: impl Into<String>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Path used in reports.
+    pub fn path(&self) -> &str {
+        &self.paths[0]
+    }
+}
+
+/// Expected contents of a rootfs.
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The LevitateOS rootfs as described by `components.rs`, the component
+    /// registry and the auth lists.
+    ///
+    /// Entries of a registered component are reported under its name.
+    pub fn from_components() -> Self {
+        Self::for_profile(Profile::Desktop)
+    }
+
+    /// Like `from_components`, but only with the components in `profile`.
+    pub fn for_profile(profile: Profile) -> Self {
+        Self::for_target(profile, Arch::DEFAULT)
+    }
+
+    /// Like `for_profile`, for a rootfs built for `arch`.
+    pub fn for_target(profile: Profile, arch: Arch) -> Self {
+        use EntryKind::*;
+        use Severity::*;
+
+        let mut m = Self::new();
+        for (link, target) in FHS_SYMLINKS {
+            m = m.with_entry(ManifestEntry::new(
+                "merged-usr",
+                *link,
+                Symlink(target),
+                Critical,
+            ));
+        }
+        let bin = unowned(BIN_UTILS, &[Field::Bin]);
+        let sbin = unowned(SBIN_UTILS, &[Field::Sbin]);
+        let etc = unowned(ETC_FILES, &[Field::Config]);
+        let libs = critical_libs(arch);
+        m = m
+            .with_list("fhs", "", FHS_DIRS, Dir, Error)
+            .with_list("bin", "usr/bin", &bin, Executable, Error)
+            .with_list("sbin", "usr/sbin", &sbin, Executable, Error)
+            .with_list(
+                "systemd",
+                "usr/lib/systemd",
+                SYSTEMD_BINARIES,
+                Executable,
+                Error,
+            )
+            .with_list("etc", "", &etc, File, Error)
+            .with_list("libs", "", &libs, File, Critical)
+            .with_list("auth", "usr/bin", AUTH_BIN, Executable, Error)
+            .with_list("auth", "usr/sbin", AUTH_SBIN, Executable, Error)
+            .with_list("auth", "usr/sbin", SHADOW_SBIN, Executable, Warning)
+            .with_list("pam", "usr/lib64/security", PAM_MODULES, File, Critical)
+            .with_list("pam", "", PAM_CONFIGS, File, Critical)
+            .with_list("pam", "", SECURITY_FILES, File, Error)
+            .with_list("sudo", SUDO_LIBEXEC_DIR, SUDO_LIBS, File, Error);
+        for unit in unowned(ALL_SYSTEMD_UNITS, &[Field::Units, Field::UserUnits]) {
+            m = m.with_entry(unit_entry("systemd-units", unit));
+        }
+
+        for c in profile.components() {
+            m = m
+                .with_list(c.name, "usr/bin", c.bin, Executable, Error)
+                .with_list(c.name, "usr/sbin", c.sbin, Executable, Error)
+                .with_list(c.name, "", c.libexec, Any, Error)
+                .with_list(c.name, "", c.config, File, Error);
+            if c.feeds_into(FlatList::SystemdUnits) {
+                for unit in c.all_units() {
+                    m = m.with_entry(unit_entry(c.name, unit));
+                }
+            }
+        }
+
+        // pam_unix.so execs unix_chkpwd; without it password changes fail silently
+        m.raise("auth", "usr/sbin/unix_chkpwd", Critical)
+    }
+
+    /// Add an entry.
+    pub fn with_entry(mut self, entry: ManifestEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Add `names` under `dir` ("" when the names are already rootfs-relative).
+    pub fn with_list(
+        mut self,
+        component: &'static str,
+        dir: &str,
+        names: &[&str],
+        kind: EntryKind,
+        severity: Severity,
+    ) -> Self {
+        for name in names {
+            let path = if dir.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", dir, name)
+            };
+            self.entries
+                .push(ManifestEntry::new(component, path, kind, severity));
+        }
+        self
+    }
+
+    fn raise(mut self, component: &str, path: &str, severity: Severity) -> Self {
+        for e in &mut self.entries {
+            if e.component == component && e.path() == path {
+                e.severity = e.severity.max(severity);
+            }
+        }
+        self
+    }
+
+    /// All entries.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Check a rootfs directory against the manifest.
+    ///
+    /// Fails only if `root` itself is unusable or a path cannot be read;
+    /// everything else is reported as an issue.
+    pub fn verify(&self, root: impl AsRef<Path>) -> io::Result<ManifestReport> {
+        let root = root.as_ref();
+        if !fs::metadata(root)?.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", root.display()),
+            ));
+        }
+
+        let mut issues = Vec::new();
+        for entry in &self.entries {
+            let mut first = None;
+            let mut satisfied = false;
+            for path in &entry.paths {
+                match check_path(root, path, entry.kind)? {
+                    None => {
+                        satisfied = true;
+                        break;
+                    }
+                    // Prefer a specific problem over "missing" at another location
+                    Some(kind) => {
+                        if first.is_none() || first == Some(IssueKind::Missing) {
+                            first = Some(kind);
+                        }
+                    }
+                }
+            }
+            if !satisfied {
+                issues.push(ManifestIssue {
+                    component: entry.component,
+                    path: entry.path().to_string(),
+                    severity: entry.severity,
+                    kind: first.unwrap_or(IssueKind::Missing),
+                });
+            }
+        }
+        Ok(ManifestReport {
+            checked: self.entries.len(),
+            issues,
+        })
+    }
+}
+
+/// Entries of `list` that no registered component claims in any of `fields`.
+fn unowned(list: &[&'static str], fields: &[Field]) -> Vec<&'static str> {
+    list.iter()
+        .copied()
+        .filter(|name| {
+            !COMPONENTS
+                .iter()
+                .any(|c| fields.iter().any(|f| f.of(c).contains(name)))
+        })
+        .collect()
+}
+
+/// A unit file, found in any of `UNIT_SEARCH_DIRS`.
+fn unit_entry(component: &'static str, unit: &str) -> ManifestEntry {
+    let mut entry = ManifestEntry::new(
+        component,
+        format!("{}/{}", UNIT_SEARCH_DIRS[1], unit),
+        EntryKind::File,
+        Severity::Error,
+    );
+    for dir in [UNIT_SEARCH_DIRS[0], UNIT_SEARCH_DIRS[2]] {
+        entry = entry.or_at(format!("{}/{}", dir, unit));
+    }
+    entry
+}
+
+/// Check the rootfs at `root` against `Manifest::from_components()`.
+pub fn verify_rootfs(root: impl AsRef<Path>) -> io::Result<ManifestReport> {
+    Manifest::from_components().verify(root)
+}
+
+// =============================================================================
+// Issues and Report
+// =============================================================================
+
+/// What is wrong with an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Nothing at the path
+    Missing,
+    /// Something of the wrong type at the path
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// A symlink on the way points at nothing
+    Dangling { link: String, target: String },
+    /// Expected symlink points somewhere else
+    WrongTarget { expected: String, found: String },
+    /// More than `MAX_SYMLINK_HOPS` symlinks
+    SymlinkLoop,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::Missing => write!(f, "missing"),
+            IssueKind::WrongType { expected, found } => {
+                write!(f, "is a {}, expected a {}", found, expected)
+            }
+            IssueKind::Dangling { link, target } => {
+                write!(f, "dangling symlink {} -> {}", link, target)
+            }
+            IssueKind::WrongTarget { expected, found } => {
+                write!(f, "points to {}, expected {}", found, expected)
+            }
+            IssueKind::SymlinkLoop => write!(f, "too many levels of symlinks"),
+        }
+    }
+}
+
+/// One unsatisfied manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestIssue {
+    pub component: &'static str,
+    /// Rootfs-relative path
+    pub path: String,
+    pub severity: Severity,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for ManifestIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.path, self.kind)
+    }
+}
+
+/// Result of `Manifest::verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestReport {
+    /// Number of entries checked
+    pub checked: usize,
+    /// Unsatisfied entries, in manifest order
+    pub issues: Vec<ManifestIssue>,
+}
+
+impl ManifestReport {
+    /// True when there are no errors or critical issues (warnings are allowed).
+    pub fn is_ok(&self) -> bool {
+        self.worst().is_none_or(|s| s < Severity::Error)
+    }
+
+    /// Most severe issue, if any.
+    pub fn worst(&self) -> Option<Severity> {
+        self.issues.iter().map(|i| i.severity).max()
+    }
+
+    /// Issues of exactly `severity`.
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &ManifestIssue> {
+        self.issues.iter().filter(move |i| i.severity == severity)
+    }
+
+    /// Issues grouped by component, sorted by component name.
+    pub fn by_component(&self) -> BTreeMap<&'static str, Vec<&ManifestIssue>> {
+        let mut map: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for issue in &self.issues {
+            map.entry(issue.component).or_default().push(issue);
+        }
+        map
+    }
+}
+
+impl fmt::Display for ManifestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} entries have problems",
+            self.issues.len(),
+            self.checked
+        )?;
+        for (component, mut issues) in self.by_component() {
+            issues.sort_by_key(|i| std::cmp::Reverse(i.severity));
+            writeln!(f, "{}:", component)?;
+            for issue in issues {
+                writeln!(f, "  {}", issue)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Resolution
+// =============================================================================
+
+/// Check one path; `None` means it is as expected.
+fn check_path(root: &Path, rel: &str, kind: EntryKind) -> io::Result<Option<IssueKind>> {
+    if let EntryKind::Symlink(expected) = kind {
+        // Only the last component is the link; its parents are followed
+        let (parent, name) = rel.rsplit_once('/').unwrap_or(("", rel));
+        let host = match resolve_in_root(root, parent)? {
+            Resolved::Found(dir) => dir.join(name),
+            Resolved::Missing => return Ok(Some(IssueKind::Missing)),
+            Resolved::Dangling { link, target } => {
+                return Ok(Some(IssueKind::Dangling { link, target }))
+            }
+            Resolved::Loop => return Ok(Some(IssueKind::SymlinkLoop)),
+        };
+        let meta = match fs::symlink_metadata(&host) {
+            Ok(m) => m,
+            Err(e) if is_absent(&e) => return Ok(Some(IssueKind::Missing)),
+            Err(e) => return Err(e),
+        };
+        if !meta.file_type().is_symlink() {
+            return Ok(Some(IssueKind::WrongType {
+                expected: kind.describe(),
+                found: describe(&meta),
+            }));
+        }
+        let found = fs::read_link(&host)?;
+        let found = found.to_string_lossy();
+        if found.trim_start_matches('/') != expected.trim_start_matches('/') {
+            return Ok(Some(IssueKind::WrongTarget {
+                expected: expected.to_string(),
+                found: found.into_owned(),
+            }));
+        }
+        // The link itself is right; it must also lead somewhere
+        return match resolve_in_root(root, rel)? {
+            Resolved::Found(_) => Ok(None),
+            Resolved::Missing => Ok(Some(IssueKind::Dangling {
+                link: rel.to_string(),
+                target: expected.to_string(),
+            })),
+            Resolved::Dangling { link, target } => Ok(Some(IssueKind::Dangling { link, target })),
+            Resolved::Loop => Ok(Some(IssueKind::SymlinkLoop)),
+        };
+    }
+
+    let host = match resolve_in_root(root, rel)? {
+        Resolved::Found(host) => host,
+        Resolved::Missing => return Ok(Some(IssueKind::Missing)),
+        Resolved::Dangling { link, target } => {
+            return Ok(Some(IssueKind::Dangling { link, target }))
+        }
+        Resolved::Loop => return Ok(Some(IssueKind::SymlinkLoop)),
+    };
+    let meta = fs::metadata(&host)?;
+    let ok = match kind {
+        EntryKind::Dir => meta.is_dir(),
+        EntryKind::File => meta.is_file(),
+        EntryKind::Executable => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        EntryKind::Any => true,
+        EntryKind::Symlink(_) => unreachable!(),
+    };
+    if ok {
+        return Ok(None);
+    }
+    let found = if meta.is_file() && kind == EntryKind::Executable {
+        "non-executable file"
+    } else {
+        describe(&meta)
+    };
+    Ok(Some(IssueKind::WrongType {
+        expected: kind.describe(),
+        found,
+    }))
+}
+
+fn describe(meta: &fs::Metadata) -> &'static str {
+    let ft = meta.file_type();
+    if ft.is_symlink() {
+        "symlink"
+    } else if ft.is_dir() {
+        "directory"
+    } else if ft.is_file() {
+        "file"
+    } else {
+        "special file"
+    }
+}
+
+/// Outcome of resolving a rootfs-relative path.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Resolved {
+    /// Host path of the final, non-symlink object
+    Found(PathBuf),
+    /// A path component does not exist
+    Missing,
+    /// A symlink's target does not exist
+    Dangling { link: String, target: String },
+    /// Too many symlinks
+    Loop,
+}
+
+/// Resolve `rel` inside `root`, following symlinks as the booted system would.
+fn resolve_in_root(root: &Path, rel: &str) -> io::Result<Resolved> {
+    resolve_traced(root, rel, &mut Vec::new())
+}
+
+/// `resolve_in_root`, also recording every symlink followed (rootfs-relative
+/// path of the link itself, in the order they were met).
+pub(crate) fn resolve_traced(
+    root: &Path,
+    rel: &str,
+    followed: &mut Vec<String>,
+) -> io::Result<Resolved> {
+    let mut pending: VecDeque<String> = split(Path::new(rel)).into();
+    let mut current: Vec<String> = Vec::new();
+    // Symlinks being expanded: (link, target, queue length before expansion)
+    let mut links: Vec<(String, String, usize)> = Vec::new();
+    let mut hops = 0;
+
+    while let Some(part) = pending.pop_front() {
+        while links
+            .last()
+            .is_some_and(|(_, _, base)| pending.len() < *base)
+        {
+            links.pop();
+        }
+        if part == ".." {
+            current.pop();
+            continue;
+        }
+        current.push(part);
+        let host = root.join(current.join("/"));
+        let meta = match fs::symlink_metadata(&host) {
+            Ok(m) => m,
+            Err(e) if is_absent(&e) => {
+                return Ok(match links.pop() {
+                    Some((link, target, _)) => Resolved::Dangling { link, target },
+                    None => Resolved::Missing,
+                });
+            }
+            Err(e) => return Err(e),
+        };
+        if !meta.file_type().is_symlink() {
+            continue;
+        }
+
+        hops += 1;
+        if hops > MAX_SYMLINK_HOPS {
+            return Ok(Resolved::Loop);
+        }
+        let target = fs::read_link(&host)?;
+        let link = current.join("/");
+        followed.push(link.clone());
+        current.pop();
+        if target.is_absolute() {
+            current.clear();
+        }
+        links.push((link, target.to_string_lossy().into_owned(), pending.len()));
+        for part in split(&target).into_iter().rev() {
+            pending.push_front(part);
+        }
+    }
+    Ok(Resolved::Found(root.join(current.join("/"))))
+}
+
+/// Whether `e` means there is nothing at the path: it does not exist, or a
+/// parent is not a directory (`usr/bin/ls` when `usr/bin` is a file).
+fn is_absent(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+    )
+}
+
+fn split(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+            Component::ParentDir => Some("..".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::components::CRITICAL_LIBS;
+    use crate::shared::test_util::TempDir;
+
+    fn merged_usr(dir: &TempDir) {
+        for (link, target) in FHS_SYMLINKS {
+            dir.mkdir(target);
+            dir.symlink(link, target);
+        }
+    }
+
+    fn exe(dir: &TempDir, rel: &str) {
+        let path = dir.write(rel, "#!/bin/sh\n");
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_from_components_covers_lists() {
+        let m = Manifest::from_components();
+        let find = |p: &str| m.entries().iter().find(|e| e.paths.iter().any(|x| x == p));
+
+        assert_eq!(find("usr/bin/ls").unwrap().kind, EntryKind::Executable);
+        assert_eq!(find("bin").unwrap().kind, EntryKind::Symlink("usr/bin"));
+        assert_eq!(
+            find("usr/sbin/unix_chkpwd").unwrap().severity,
+            Severity::Critical
+        );
+        assert_eq!(
+            find("usr/lib64/security/pam_unix.so").unwrap().component,
+            "pam"
+        );
+        assert_eq!(find("usr/bin/nmcli").unwrap().component, "networkmanager");
+        assert_eq!(find("usr/libexec/bluetooth").unwrap().kind, EntryKind::Any);
+        assert!(find("usr/lib/systemd/user/pipewire.service").is_some());
+        assert!(find(CRITICAL_LIBS[0]).is_some());
+    }
+
+    #[test]
+    fn test_server_profile_drops_desktop_components() {
+        let m = Manifest::for_profile(Profile::Server);
+        let has = |p: &str| m.entries().iter().any(|e| e.paths.iter().any(|x| x == p));
+
+        assert!(has("usr/bin/ls"));
+        assert!(has("usr/sbin/sshd"));
+        assert!(has("usr/lib/systemd/system/sshd.service"));
+        // Optional unit: copied when present, like in ALL_SYSTEMD_UNITS
+        assert!(!has("usr/lib/systemd/system/sshd.socket"));
+        assert!(!has("usr/bin/bluetoothctl"));
+        assert!(!has("usr/lib/systemd/user/pipewire.service"));
+        assert!(m.entries().len() < Manifest::from_components().entries().len());
+
+        let arm = Manifest::for_target(Profile::Server, Arch::Aarch64);
+        let has = |p: &str| arm.entries().iter().any(|e| e.paths.iter().any(|x| x == p));
+        assert!(has("usr/lib64/ld-linux-aarch64.so.1"));
+        assert!(!has("usr/lib64/ld-linux-x86-64.so.2"));
+        assert_eq!(arm.entries().len(), m.entries().len());
+    }
+
+    #[test]
+    fn test_resolves_through_merged_usr() {
+        let dir = TempDir::new("manifest-usr");
+        merged_usr(&dir);
+        exe(&dir, "usr/bin/ls");
+        dir.symlink("usr/lib64/libc.so.6", "/lib64/libc-2.39.so");
+        dir.write("usr/lib64/libc-2.39.so", "");
+
+        let report = Manifest::new()
+            .with_entry(ManifestEntry::new(
+                "merged-usr",
+                "bin",
+                EntryKind::Symlink("usr/bin"),
+                Severity::Critical,
+            ))
+            .with_list(
+                "bin",
+                "bin",
+                &["ls"],
+                EntryKind::Executable,
+                Severity::Error,
+            )
+            .with_list(
+                "libs",
+                "lib64",
+                &["libc.so.6"],
+                EntryKind::File,
+                Severity::Critical,
+            )
+            .verify(dir.path())
+            .unwrap();
+        assert!(report.issues.is_empty(), "{}", report);
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_reports_problems() {
+        let dir = TempDir::new("manifest-bad");
+        dir.mkdir("usr/bin");
+        dir.mkdir("bin");
+        dir.write("usr/bin/cat", "");
+        dir.mkdir("usr/lib64/libm.so.6");
+        dir.symlink("usr/lib64/libdl.so.2", "libdl-gone.so");
+        dir.symlink("loop", "loop");
+        dir.write("usr/lib/systemd/user/pipewire.service", "");
+
+        let report = Manifest::new()
+            .with_entry(ManifestEntry::new(
+                "merged-usr",
+                "bin",
+                EntryKind::Symlink("usr/bin"),
+                Severity::Critical,
+            ))
+            .with_list(
+                "bin",
+                "usr/bin",
+                &["cat", "ls"],
+                EntryKind::Executable,
+                Severity::Error,
+            )
+            .with_list(
+                "libs",
+                "usr/lib64",
+                &["libm.so.6", "libdl.so.2"],
+                EntryKind::File,
+                Severity::Critical,
+            )
+            .with_list("misc", "", &["loop"], EntryKind::File, Severity::Warning)
+            .with_entry(
+                ManifestEntry::new(
+                    "systemd-units",
+                    "usr/lib/systemd/system/pipewire.service",
+                    EntryKind::File,
+                    Severity::Error,
+                )
+                .or_at("usr/lib/systemd/user/pipewire.service"),
+            )
+            .verify(dir.path())
+            .unwrap();
+
+        let kinds: Vec<_> = report
+            .issues
+            .iter()
+            .map(|i| (i.path.as_str(), &i.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "bin",
+                    &IssueKind::WrongType {
+                        expected: "symlink",
+                        found: "directory"
+                    }
+                ),
+                (
+                    "usr/bin/cat",
+                    &IssueKind::WrongType {
+                        expected: "executable",
+                        found: "non-executable file"
+                    }
+                ),
+                ("usr/bin/ls", &IssueKind::Missing),
+                (
+                    "usr/lib64/libm.so.6",
+                    &IssueKind::WrongType {
+                        expected: "file",
+                        found: "directory"
+                    }
+                ),
+                (
+                    "usr/lib64/libdl.so.2",
+                    &IssueKind::Dangling {
+                        link: "usr/lib64/libdl.so.2".into(),
+                        target: "libdl-gone.so".into()
+                    }
+                ),
+                ("loop", &IssueKind::SymlinkLoop),
+            ]
+        );
+        assert_eq!(report.worst(), Some(Severity::Critical));
+        assert!(!report.is_ok());
+        assert_eq!(report.by_component()["libs"].len(), 2);
+        assert_eq!(report.with_severity(Severity::Warning).count(), 1);
+    }
+
+    #[test]
+    fn test_wrong_symlink_target() {
+        let dir = TempDir::new("manifest-target");
+        dir.mkdir("usr/lib");
+        dir.symlink("lib64", "usr/lib");
+        dir.symlink("sbin", "/usr/sbin");
+
+        let report = Manifest::new()
+            .with_entry(ManifestEntry::new(
+                "merged-usr",
+                "lib64",
+                EntryKind::Symlink("usr/lib64"),
+                Severity::Critical,
+            ))
+            .with_entry(ManifestEntry::new(
+                "merged-usr",
+                "sbin",
+                EntryKind::Symlink("usr/sbin"),
+                Severity::Critical,
+            ))
+            .verify(dir.path())
+            .unwrap();
+        assert_eq!(
+            report.issues[0].kind,
+            IssueKind::WrongTarget {
+                expected: "usr/lib64".into(),
+                found: "usr/lib".into()
+            }
+        );
+        // Absolute target is accepted but leads nowhere
+        assert!(matches!(report.issues[1].kind, IssueKind::Dangling { .. }));
+    }
+
+    #[test]
+    fn test_missing_intermediate_is_not_dangling() {
+        let dir = TempDir::new("manifest-missing");
+        merged_usr(&dir);
+        assert_eq!(
+            resolve_in_root(dir.path(), "bin/nope").unwrap(),
+            Resolved::Missing
+        );
+        assert!(Manifest::from_components()
+            .verify(dir.path().join("nope"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parent_is_a_file() {
+        let dir = TempDir::new("manifest-notdir");
+        dir.write("usr/bin", "not a directory\n");
+        dir.symlink("bin", "usr/bin");
+        dir.mkdir("loop");
+        dir.symlink("loop/a", "b");
+        dir.symlink("loop/b", "a");
+
+        let report = Manifest::new()
+            .with_entry(ManifestEntry::new(
+                "bin",
+                "usr/bin/ls",
+                EntryKind::Executable,
+                Severity::Error,
+            ))
+            .with_entry(ManifestEntry::new(
+                "bin",
+                "bin/sh",
+                EntryKind::Symlink("bash"),
+                Severity::Error,
+            ))
+            .with_entry(ManifestEntry::new(
+                "loop",
+                "loop/a/link",
+                EntryKind::Symlink("target"),
+                Severity::Error,
+            ))
+            .verify(dir.path())
+            .unwrap();
+        let kinds: Vec<_> = report.issues.iter().map(|i| &i.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &IssueKind::Missing,
+                &IssueKind::Missing,
+                &IssueKind::SymlinkLoop
+            ]
+        );
+        assert_eq!(
+            resolve_in_root(dir.path(), "usr/bin/ls").unwrap(),
+            Resolved::Missing
+        );
+    }
+}
@@ -0,0 +1,573 @@
+//! Serial console boot log parser.
+//!
+//! Test VMs write their serial console to `QEMU_SERIAL_LOG`. Instead of
+//! grepping that file for strings, harnesses feed it to `SerialLogParser`,
+//! which tracks how far the boot got, when each stage started, and which
+//! known failure signatures appeared:
+//!
+//! ```text
+//! firmware -> bootloader -> kernel -> initramfs -> switch_root -> init -> login -> shell-ready
+//! ```
+//!
+//! Stages only move forward; a marker for an earlier stage is ignored. The
+//! parser accepts raw chunks as they arrive (`push_bytes`) or whole lines
+//! (`push_line_at`, for replaying a saved log with known timestamps).
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub use super::test_protocol::SHELL_READY_MARKER;
+
+// =============================================================================
+// Stages
+// =============================================================================
+
+/// A boot stage, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BootStage {
+    /// UEFI firmware (OVMF)
+    Firmware,
+    /// systemd-boot / EFI stub
+    Bootloader,
+    /// Kernel running
+    Kernel,
+    /// Initramfs userspace
+    Initramfs,
+    /// Switching to the real root
+    SwitchRoot,
+    /// systemd or OpenRC on the real root
+    Init,
+    /// getty / login prompt
+    Login,
+    /// Test-mode shell printed `SHELL_READY_MARKER`
+    ShellReady,
+}
+
+impl BootStage {
+    /// All stages in boot order.
+    pub const ALL: &'static [BootStage] = &[
+        BootStage::Firmware,
+        BootStage::Bootloader,
+        BootStage::Kernel,
+        BootStage::Initramfs,
+        BootStage::SwitchRoot,
+        BootStage::Init,
+        BootStage::Login,
+        BootStage::ShellReady,
+    ];
+
+    /// Short name for reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BootStage::Firmware => "firmware",
+            BootStage::Bootloader => "bootloader",
+            BootStage::Kernel => "kernel",
+            BootStage::Initramfs => "initramfs",
+            BootStage::SwitchRoot => "switch_root",
+            BootStage::Init => "init",
+            BootStage::Login => "login",
+            BootStage::ShellReady => "shell-ready",
+        }
+    }
+}
+
+impl fmt::Display for BootStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Substrings that mark the start of a stage.
+pub const STAGE_MARKERS: &[(BootStage, &str)] = &[
+    (BootStage::Firmware, "BdsDxe:"),
+    (BootStage::Firmware, "UEFI firmware"),
+    (BootStage::Bootloader, "systemd-boot"),
+    (BootStage::Bootloader, "EFI stub:"),
+    (BootStage::Kernel, "Linux version "),
+    (BootStage::Kernel, "Booting Linux on physical CPU"),
+    (BootStage::Initramfs, "Run /init as init process"),
+    (BootStage::SwitchRoot, "Switching root."),
+    (BootStage::SwitchRoot, "switch_root"),
+    (BootStage::Init, "is starting up Linux"), // OpenRC banner
+    (BootStage::Init, "Welcome to "),          // systemd banner (not "(Initrd)")
+    (BootStage::Login, " login: "),
+    (BootStage::Login, "Started serial-getty@"),
+    (BootStage::Login, "Started Serial Getty on"),
+    (BootStage::ShellReady, SHELL_READY_MARKER),
+];
+
+// =============================================================================
+// Failures
+// =============================================================================
+
+/// A known failure signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootFailure {
+    /// "Kernel panic - not syncing: <reason>"
+    KernelPanic(String),
+    /// systemd emergency or rescue shell
+    EmergencyMode,
+    /// A systemd unit or OpenRC service failed to start
+    FailedUnit(String),
+    /// The root filesystem could not be found or mounted
+    RootfsNotFound(String),
+}
+
+impl fmt::Display for BootFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootFailure::KernelPanic(reason) => write!(f, "kernel panic: {}", reason),
+            BootFailure::EmergencyMode => write!(f, "dropped to emergency mode"),
+            BootFailure::FailedUnit(unit) => write!(f, "failed to start {}", unit),
+            BootFailure::RootfsNotFound(line) => write!(f, "root filesystem not found: {}", line),
+        }
+    }
+}
+
+impl BootFailure {
+    /// Whether the boot cannot continue after this failure.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, BootFailure::FailedUnit(_))
+    }
+}
+
+/// Substrings that by themselves mean the root filesystem is missing.
+///
+/// Kernel (`VFS: Cannot open root device "sda2" or unknown-block(0,0)`),
+/// dracut (`Warning: /dev/root does not exist`), systemd in the initrd
+/// (`Dependency failed for sysroot.mount - /sysroot.`) and Alpine's mkinitfs
+/// init (`mount: mounting /dev/sda2 on /sysroot failed: ...`).
+pub const ROOTFS_MISSING_SIGNATURES: &[&str] = &[
+    "VFS: Unable to mount root fs",
+    "VFS: Cannot open root device",
+    "Warning: /dev/root does not exist",
+    "Dependency failed for sysroot.mount",
+    "Dependency failed for initrd-root-device.target",
+    " on /sysroot failed",
+];
+
+/// Substrings meaning some device is missing; a missing root filesystem
+/// only when the line also has a `root=...` or `rootfs` token.
+pub const DEVICE_MISSING_SIGNATURES: &[&str] = &[
+    "Timed out waiting for device",
+    " does not exist",
+    " not found",
+];
+
+/// Substrings meaning systemd dropped to an emergency shell.
+pub const EMERGENCY_SIGNATURES: &[&str] = &[
+    "You are in emergency mode",
+    "You are in rescue mode",
+    "Entering emergency mode",
+];
+
+fn detect_failure(line: &str) -> Option<BootFailure> {
+    if let Some(i) = line.find("Kernel panic - not syncing") {
+        let reason = line[i..]
+            .split_once(": ")
+            .map_or("", |(_, r)| r)
+            .trim()
+            .to_string();
+        return Some(BootFailure::KernelPanic(reason));
+    }
+    if EMERGENCY_SIGNATURES.iter().any(|s| line.contains(s)) {
+        return Some(BootFailure::EmergencyMode);
+    }
+    // systemd: "[FAILED] Failed to start foo.service - Foo."
+    if let Some(i) = line.find("Failed to start ") {
+        let unit = line[i + "Failed to start ".len()..]
+            .trim()
+            .trim_end_matches('.')
+            .to_string();
+        return Some(BootFailure::FailedUnit(unit));
+    }
+    // OpenRC: " * ERROR: sshd failed to start"
+    if let Some(i) = line.find("ERROR: ") {
+        if let Some(svc) = line[i + "ERROR: ".len()..].strip_suffix(" failed to start") {
+            return Some(BootFailure::FailedUnit(svc.trim().to_string()));
+        }
+    }
+    if ROOTFS_MISSING_SIGNATURES.iter().any(|s| line.contains(s))
+        || (DEVICE_MISSING_SIGNATURES.iter().any(|s| line.contains(s)) && names_root(line))
+    {
+        return Some(BootFailure::RootfsNotFound(line.trim().to_string()));
+    }
+    None
+}
+
+/// Whether a word on the line is a `root=` argument or `rootfs`.
+fn names_root(line: &str) -> bool {
+    line.split(|c: char| c.is_whitespace() || "\"':,".contains(c))
+        .any(|word| {
+            let word = word.to_ascii_lowercase();
+            word.starts_with("root=") || word.trim_end_matches('.') == "rootfs"
+        })
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+/// When a stage was first seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageEvent {
+    /// Stage entered
+    pub stage: BootStage,
+    /// Time since the parser started
+    pub at: Duration,
+    /// 1-based line number in the log
+    pub line: usize,
+}
+
+/// A failure signature with its position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureEvent {
+    /// What went wrong
+    pub failure: BootFailure,
+    /// Stage the boot was in
+    pub stage: Option<BootStage>,
+    /// Time since the parser started
+    pub at: Duration,
+    /// 1-based line number in the log
+    pub line: usize,
+}
+
+/// Streaming classifier for serial console output.
+#[derive(Debug)]
+pub struct SerialLogParser {
+    started: Instant,
+    partial: Vec<u8>,
+    lines: usize,
+    last_at: Duration,
+    stages: Vec<StageEvent>,
+    failures: Vec<FailureEvent>,
+}
+
+impl Default for SerialLogParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerialLogParser {
+    /// Parser whose clock starts now.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            partial: Vec::new(),
+            lines: 0,
+            last_at: Duration::ZERO,
+            stages: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Feed a raw chunk from the serial stream, timestamped with the wall clock.
+    ///
+    /// Incomplete trailing lines are buffered until the next chunk.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let at = self.started.elapsed();
+        self.partial.extend_from_slice(bytes);
+        while let Some(nl) = self.partial.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=nl).collect();
+            self.push_line_at(&String::from_utf8_lossy(&line), at);
+        }
+        // The login prompt has no trailing newline
+        if !self.partial.is_empty() {
+            let pending = String::from_utf8_lossy(&self.partial).into_owned();
+            if pending.ends_with("login: ") {
+                self.partial.clear();
+                self.push_line_at(&pending, at);
+            }
+        }
+    }
+
+    /// Feed one complete line observed at `at` (time since boot started).
+    pub fn push_line_at(&mut self, line: &str, at: Duration) {
+        self.lines += 1;
+        self.last_at = self.last_at.max(at);
+        let line = clean_line(line);
+        let (_, text) = split_kernel_timestamp(&line);
+
+        if let Some(stage) = detect_stage(text) {
+            if self.stage().is_none_or(|current| stage > current) {
+                self.stages.push(StageEvent {
+                    stage,
+                    at,
+                    line: self.lines,
+                });
+            }
+        }
+        if let Some(failure) = detect_failure(text) {
+            self.failures.push(FailureEvent {
+                failure,
+                stage: self.stage(),
+                at,
+                line: self.lines,
+            });
+        }
+    }
+
+    /// Furthest stage reached.
+    pub fn stage(&self) -> Option<BootStage> {
+        self.stages.last().map(|e| e.stage)
+    }
+
+    /// Stage transitions in order.
+    pub fn stages(&self) -> &[StageEvent] {
+        &self.stages
+    }
+
+    /// Failure signatures seen so far.
+    pub fn failures(&self) -> &[FailureEvent] {
+        &self.failures
+    }
+
+    /// Whether the shell-ready marker was seen.
+    pub fn is_ready(&self) -> bool {
+        self.stage() == Some(BootStage::ShellReady)
+    }
+
+    /// First fatal failure, if any.
+    pub fn fatal_failure(&self) -> Option<&FailureEvent> {
+        self.failures.iter().find(|f| f.failure.is_fatal())
+    }
+
+    /// How long each stage lasted (the last one until the latest line).
+    pub fn stage_durations(&self) -> Vec<(BootStage, Duration)> {
+        self.stages
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let end = self.stages.get(i + 1).map_or(self.last_at, |next| next.at);
+                (e.stage, end.saturating_sub(e.at))
+            })
+            .collect()
+    }
+
+    /// Summary of the boot so far.
+    pub fn report(&self) -> BootReport {
+        BootReport {
+            reached: self.stage(),
+            durations: self.stage_durations(),
+            failures: self.failures.clone(),
+        }
+    }
+}
+
+/// Parse a saved serial log. Lines carry no wall-clock times, so stage
+/// timings come from kernel timestamps (`[    1.234567]`) where present.
+pub fn parse_serial_log(path: impl AsRef<Path>) -> io::Result<BootReport> {
+    let text = fs::read(path)?;
+    let mut parser = SerialLogParser::new();
+    let mut at = Duration::ZERO;
+    for line in String::from_utf8_lossy(&text).lines() {
+        if let (Some(ts), _) = split_kernel_timestamp(line) {
+            at = at.max(ts);
+        }
+        parser.push_line_at(line, at);
+    }
+    Ok(parser.report())
+}
+
+/// Outcome of a boot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootReport {
+    /// Furthest stage reached
+    pub reached: Option<BootStage>,
+    /// Time spent in each stage
+    pub durations: Vec<(BootStage, Duration)>,
+    /// Failure signatures in order
+    pub failures: Vec<FailureEvent>,
+}
+
+impl BootReport {
+    /// Whether the boot got to a ready shell without fatal failures.
+    pub fn succeeded(&self) -> bool {
+        self.reached == Some(BootStage::ShellReady)
+            && !self.failures.iter().any(|f| f.failure.is_fatal())
+    }
+}
+
+impl fmt::Display for BootReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reached {
+            Some(stage) => writeln!(f, "reached: {}", stage)?,
+            None => writeln!(f, "reached: nothing (no recognizable output)")?,
+        }
+        for (stage, duration) in &self.durations {
+            writeln!(f, "  {:<12} {:>8.2}s", stage.name(), duration.as_secs_f64())?;
+        }
+        for event in &self.failures {
+            let stage = event.stage.map_or("-", |s| s.name());
+            writeln!(f, "  line {} ({}): {}", event.line, stage, event.failure)?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Line Helpers
+// =============================================================================
+
+fn detect_stage(text: &str) -> Option<BootStage> {
+    STAGE_MARKERS
+        .iter()
+        .rev()
+        .find(|(stage, marker)| {
+            text.contains(marker) && !(*stage == BootStage::Init && text.contains("(Initrd)"))
+        })
+        .map(|(stage, _)| *stage)
+}
+
+/// Strip CR and ANSI escape sequences (systemd colors its status lines).
+fn clean_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    // Parameters end at the first letter
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+            }
+            '\r' | '\n' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Split a `[    1.234567] ` printk prefix off a line.
+fn split_kernel_timestamp(line: &str) -> (Option<Duration>, &str) {
+    let Some(rest) = line.strip_prefix('[') else {
+        return (None, line);
+    };
+    let Some((ts, text)) = rest.split_once(']') else {
+        return (None, line);
+    };
+    match ts.trim().parse::<f64>() {
+        Ok(secs) if secs >= 0.0 => (Some(Duration::from_secs_f64(secs)), text.trim_start()),
+        _ => (None, line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_util::TempDir;
+
+    const LIVE_BOOT: &str = "\
+BdsDxe: loading Boot0001 \"UEFI QEMU DVD-ROM\"
+BdsDxe: starting Boot0001
+EFI stub: Loaded initrd from LINUX_EFI_INITRD_MEDIA_GUID device path
+[    0.000000] Linux version 6.12.5 (builder@levitate) #1 SMP
+[    1.500000] Run /init as init process
+[    3.000000] switch_root: switching to /newroot
+[    3.200000] systemd[1]: Welcome to \x1b[1mLevitateOS\x1b[0m!
+[\x1b[0;1;31mFAILED\x1b[0m] Failed to start chronyd.service - NTP client/server.
+levitateos login: root (automatic login)
+___SHELL_READY___
+";
+
+    #[test]
+    fn test_marker_matches_profile_script() {
+        let script = include_str!("auth/files/00-levitate-test.sh");
+        assert!(script.contains(&format!("echo \"{}\"", SHELL_READY_MARKER)));
+    }
+
+    #[test]
+    fn test_full_boot() {
+        let dir = TempDir::new("serial-log");
+        let log = dir.write("serial.log", LIVE_BOOT);
+        let report = parse_serial_log(&log).unwrap();
+        assert_eq!(report.reached, Some(BootStage::ShellReady));
+        let stages: Vec<BootStage> = report.durations.iter().map(|(s, _)| *s).collect();
+        assert_eq!(stages, BootStage::ALL);
+        assert!(report.succeeded());
+        assert_eq!(
+            report.failures[0].failure,
+            BootFailure::FailedUnit("chronyd.service - NTP client/server".to_string())
+        );
+        assert_eq!(report.failures[0].stage, Some(BootStage::Init));
+        // kernel -> initramfs took 1.5s by printk time
+        assert_eq!(report.durations[2].1, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_streaming_and_failures() {
+        let mut parser = SerialLogParser::new();
+        parser.push_bytes(b"[    0.000000] Linux ver");
+        assert_eq!(parser.stage(), None);
+        parser.push_bytes(b"sion 6.6.30-0-lts\r\n[    2.1] Run /init as init process\n");
+        assert_eq!(parser.stage(), Some(BootStage::Initramfs));
+        parser.push_bytes(b"[   32.0] VFS: Unable to mount root fs on unknown-block(0,0)\n");
+        parser.push_bytes(b"[   32.1] Kernel panic - not syncing: VFS: Unable to mount root fs\n");
+
+        let fatal = parser.fatal_failure().unwrap();
+        assert!(matches!(fatal.failure, BootFailure::RootfsNotFound(_)));
+        assert_eq!(fatal.stage, Some(BootStage::Initramfs));
+        assert_eq!(
+            parser.failures()[1].failure,
+            BootFailure::KernelPanic("VFS: Unable to mount root fs".to_string())
+        );
+        assert!(!parser.report().succeeded());
+    }
+
+    #[test]
+    fn test_rootfs_signatures() {
+        let missing =
+            |line: &str| matches!(detect_failure(line), Some(BootFailure::RootfsNotFound(_)));
+        for line in [
+            "[    1.9] VFS: Cannot open root device \"sda2\" or unknown-block(0,0): error -6",
+            "[  182.3] dracut-initqueue[301]: Warning: /dev/root does not exist",
+            "[DEPEND] Dependency failed for sysroot.mount - /sysroot.",
+            "mount: mounting /dev/vda2 on /sysroot failed: No such file or directory",
+            "Warning: root=LABEL=LEVITATEOS does not exist",
+            "rootfs not found",
+        ] {
+            assert!(missing(line), "{}", line);
+        }
+        for line in [
+            "/root/.cache does not exist",
+            "chroot: /usr/bin/foo not found",
+            "[  182.3] dracut-initqueue[301]: Warning: /dev/mapper/swap does not exist",
+            "Timed out waiting for device dev-ttyS1.device - /dev/ttyS1.",
+            "Mounted /root/backup",
+        ] {
+            assert!(!missing(line), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_openrc_boot() {
+        let mut parser = SerialLogParser::new();
+        let t = Duration::from_secs;
+        parser.push_line_at("   OpenRC 0.55 is starting up Linux 6.12.5 (x86_64)", t(5));
+        parser.push_line_at(" * ERROR: sshd failed to start", t(6));
+        parser.push_bytes(b"acornos login: ");
+        assert_eq!(parser.stage(), Some(BootStage::Login));
+        assert_eq!(
+            parser.failures()[0].failure,
+            BootFailure::FailedUnit("sshd".to_string())
+        );
+        // initrd banner doesn't count as init
+        let mut initrd = SerialLogParser::new();
+        initrd.push_line_at("Welcome to LevitateOS 1.0 (Initrd)!", t(1));
+        assert_eq!(initrd.stage(), None);
+        initrd.push_line_at("You are in emergency mode.", t(2));
+        assert_eq!(
+            initrd.fatal_failure().map(|f| &f.failure),
+            Some(&BootFailure::EmergencyMode)
+        );
+    }
+}
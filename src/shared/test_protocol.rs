@@ -0,0 +1,330 @@
+//! Serial console test-harness protocol.
+//!
+//! In test mode (interactive shell on the serial console) the installed
+//! profile snippet `auth/files/00-levitate-test.sh` prints markers the
+//! harness waits for. This module is the single definition of that protocol:
+//! `profile_snippet` generates the shell side, `ProtocolDecoder` reads it back.
+//!
+//! ```text
+//! ___SHELL_READY___                  shell is up (once)
+//! ___PROMPT___                       shell is waiting for input
+//! ___CMD_START___ <id>               output of command <id> follows
+//! ...output lines...
+//! ___CMD_END___ <id> <exit code>     command <id> finished
+//! ```
+//!
+//! Commands are sent as `__lt_run <id> sh -c '<command>'` (see
+//! `CommandEncoder`). The markers are printed by the shell function, so the
+//! terminal echo of the typed line never contains them. Each command runs in
+//! its own `sh`, so `cd` or variable assignments don't carry over.
+
+use std::fmt::Write as _;
+
+use super::command::shell_quote;
+
+/// Printed once when the test shell is ready.
+pub const SHELL_READY_MARKER: &str = "___SHELL_READY___";
+
+/// Printed before every prompt.
+pub const PROMPT_MARKER: &str = "___PROMPT___";
+
+/// Starts the output of a framed command: `___CMD_START___ <id>`.
+pub const CMD_START_MARKER: &str = "___CMD_START___";
+
+/// Ends a framed command: `___CMD_END___ <id> <exit code>`.
+pub const CMD_END_MARKER: &str = "___CMD_END___";
+
+/// Shell function that runs a command between start/end markers.
+pub const RUN_FUNCTION: &str = "__lt_run";
+
+/// Where the profile snippet is installed.
+pub const TEST_PROFILE_PATH: &str = "/etc/profile.d/00-levitate-test.sh";
+
+/// Environment variable set in test mode.
+pub const TEST_MODE_ENV: &str = "LEVITATE_TEST_MODE";
+
+// =============================================================================
+// Encoder
+// =============================================================================
+
+/// Shell profile snippet implementing the protocol on `console` (e.g., "ttyS0").
+///
+/// The snippet only activates in interactive shells on that console, so
+/// normal logins are unaffected.
+pub fn profile_snippet(console: &str) -> String {
+    let rule = "═".repeat(67);
+    let mut s = String::new();
+    let _ = write!(
+        s,
+        r#"#!/bin/bash
+# LevitateOS test mode instrumentation
+# Activates ONLY on serial console ({console}) - test harness environment
+#
+# Generated by distro_spec::shared::test_protocol::profile_snippet.
+# Change the protocol there, not here.
+
+# Only run in interactive shells
+[[ $- != *i* ]] && return
+
+# Detect test mode: serial console = test mode
+if [[ $(tty) == /dev/{console} ]]; then
+    export {env}=1
+else
+    # Not test mode - exit early
+    return
+fi
+
+# {rule}
+# TEST MODE ACTIVE - Minimal instrumentation for test harness
+# {rule}
+
+# Run a command between start/end markers: {run} <id> <argv...>
+{run}() {{
+    local id=$1 rc
+    shift
+    printf '{start} %s\n' "$id"
+    "$@"
+    rc=$?
+    printf '{end} %s %s\n' "$id" "$rc"
+}}
+
+# Signal shell is ready - test harness waits for this
+echo "{ready}"
+
+# Emit prompt marker for first command
+# Subsequent commands get {prompt} from PROMPT_COMMAND
+echo "{prompt}"
+
+# Simple PROMPT_COMMAND to emit prompt marker after each command
+PROMPT_COMMAND='echo "{prompt}"'
+"#,
+        console = console,
+        env = TEST_MODE_ENV,
+        rule = rule,
+        run = RUN_FUNCTION,
+        start = CMD_START_MARKER,
+        end = CMD_END_MARKER,
+        ready = SHELL_READY_MARKER,
+        prompt = PROMPT_MARKER,
+    );
+    s
+}
+
+/// Produces the lines a harness types to run commands.
+#[derive(Debug, Default)]
+pub struct CommandEncoder {
+    next_id: u64,
+}
+
+impl CommandEncoder {
+    /// Encoder starting at id 1.
+    pub fn new() -> Self {
+        Self { next_id: 1 }
+    }
+
+    /// Input line (with trailing newline) running `command`, and its id.
+    pub fn encode(&mut self, command: &str) -> (u64, String) {
+        let id = self.next_id.max(1);
+        self.next_id = id + 1;
+        (
+            id,
+            format!("{} {} sh -c {}\n", RUN_FUNCTION, id, shell_quote(command)),
+        )
+    }
+}
+
+// =============================================================================
+// Decoder
+// =============================================================================
+
+/// Something the shell reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolEvent {
+    /// `SHELL_READY_MARKER`
+    Ready,
+    /// `PROMPT_MARKER`
+    Prompt,
+    /// A framed command started
+    CommandStart { id: u64 },
+    /// One output line of a framed command
+    Output { id: u64, line: String },
+    /// A framed command finished
+    CommandEnd { id: u64, exit_code: i32 },
+}
+
+/// Complete result of a framed command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Command id from `CommandEncoder`
+    pub id: u64,
+    /// Output lines (stdout and stderr interleaved, CR stripped)
+    pub lines: Vec<String>,
+    /// Exit status
+    pub exit_code: i32,
+}
+
+impl CommandOutput {
+    /// Whether the command exited with status 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Output joined with newlines.
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+/// Streaming decoder for the serial console.
+///
+/// Lines outside a command frame (kernel messages, echoed input) are ignored.
+#[derive(Debug, Default)]
+pub struct ProtocolDecoder {
+    partial: Vec<u8>,
+    ready: bool,
+    current: Option<(u64, Vec<String>)>,
+    completed: Vec<CommandOutput>,
+}
+
+impl ProtocolDecoder {
+    /// Decoder waiting for the ready marker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `SHELL_READY_MARKER` has been seen.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Feed a raw chunk; returns the events it completed.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Vec<ProtocolEvent> {
+        self.partial.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(nl) = self.partial.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=nl).collect();
+            events.extend(self.push_line(&String::from_utf8_lossy(&line)));
+        }
+        events
+    }
+
+    /// Feed one line; returns the events it produced.
+    pub fn push_line(&mut self, line: &str) -> Vec<ProtocolEvent> {
+        let line = line.trim_end_matches(['\n', '\r']).replace('\r', "");
+        let mut events = Vec::new();
+
+        // Output without a trailing newline runs into the end marker
+        if let Some(pos) = line.find(CMD_END_MARKER) {
+            if pos > 0 {
+                events.extend(self.output(&line[..pos]));
+            }
+            let mut fields = line[pos + CMD_END_MARKER.len()..].split_whitespace();
+            let id = fields.next().and_then(|f| f.parse().ok());
+            let code = fields.next().and_then(|f| f.parse().ok());
+            if let (Some(id), Some(exit_code)) = (id, code) {
+                if let Some((current, lines)) = self.current.take() {
+                    if current == id {
+                        self.completed.push(CommandOutput {
+                            id,
+                            lines,
+                            exit_code,
+                        });
+                    }
+                }
+                events.push(ProtocolEvent::CommandEnd { id, exit_code });
+            }
+            return events;
+        }
+
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix(CMD_START_MARKER) {
+            if let Ok(id) = rest.trim().parse() {
+                self.current = Some((id, Vec::new()));
+                events.push(ProtocolEvent::CommandStart { id });
+                return events;
+            }
+        }
+        if self.current.is_none() {
+            if trimmed == SHELL_READY_MARKER {
+                self.ready = true;
+                events.push(ProtocolEvent::Ready);
+            } else if trimmed == PROMPT_MARKER {
+                events.push(ProtocolEvent::Prompt);
+            }
+            return events;
+        }
+        events.extend(self.output(&line));
+        events
+    }
+
+    fn output(&mut self, line: &str) -> Option<ProtocolEvent> {
+        let (id, lines) = self.current.as_mut()?;
+        lines.push(line.to_string());
+        Some(ProtocolEvent::Output {
+            id: *id,
+            line: line.to_string(),
+        })
+    }
+
+    /// Take finished commands, oldest first.
+    pub fn take_completed(&mut self) -> Vec<CommandOutput> {
+        std::mem::take(&mut self.completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_file_is_generated() {
+        assert_eq!(
+            include_str!("auth/files/00-levitate-test.sh"),
+            profile_snippet("ttyS0"),
+            "auth/files/00-levitate-test.sh is out of date; regenerate it with profile_snippet(\"ttyS0\")"
+        );
+    }
+
+    #[test]
+    fn test_encode_command() {
+        let mut enc = CommandEncoder::new();
+        assert_eq!(
+            enc.encode("ls -l /"),
+            (1, "__lt_run 1 sh -c 'ls -l /'\n".to_string())
+        );
+        assert_eq!(enc.encode("true").0, 2);
+    }
+
+    #[test]
+    fn test_decode_session() {
+        let mut dec = ProtocolDecoder::new();
+        let mut events = dec.push_bytes(b"[  5.0] random: crng init done\r\n___SHELL_READY___\r\n");
+        assert!(dec.is_ready());
+        events.extend(dec.push_bytes(
+            b"___PROMPT___\r\nroot@levitateos:~# __lt_run 1 sh -c 'cat /etc/hostname; false'\r\n\
+              ___CMD_START___ 1\r\nlevitateos\r\nno newline___CMD_END___ 1 1\r\n___PROMPT___\r\n",
+        ));
+        assert_eq!(events[0], ProtocolEvent::Ready);
+        assert_eq!(events[1], ProtocolEvent::Prompt);
+        assert_eq!(events[2], ProtocolEvent::CommandStart { id: 1 });
+        assert_eq!(events.last(), Some(&ProtocolEvent::Prompt),);
+
+        let done = dec.take_completed();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].lines, vec!["levitateos", "no newline"]);
+        assert_eq!(done[0].exit_code, 1);
+        assert!(!done[0].success());
+        assert!(dec.take_completed().is_empty());
+    }
+
+    #[test]
+    fn test_markers_inside_output_are_data() {
+        let mut dec = ProtocolDecoder::new();
+        dec.push_line("___CMD_START___ 7");
+        dec.push_line("___PROMPT___");
+        dec.push_line("___CMD_END___ 7 0");
+        let done = dec.take_completed();
+        assert_eq!(done[0].lines, vec!["___PROMPT___"]);
+        assert!(done[0].success());
+    }
+}
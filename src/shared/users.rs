@@ -6,6 +6,8 @@
 use smallvec::SmallVec;
 use std::borrow::Cow;
 
+use super::command::shell_quote;
+
 /// Root account configuration.
 pub const ROOT_HOME: &str = "/root";
 
@@ -57,26 +59,116 @@ impl UserSpec {
         self
     }
 
+    /// Validate this spec against the account naming rules.
+    ///
+    /// Returns a human-readable reason on failure.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_username(&self.username)?;
+        if RESERVED_USERNAMES.contains(&self.username.as_str()) {
+            return Err(format!("'{}' is a reserved system account", self.username));
+        }
+        validate_shell(&self.shell)?;
+        if let Some(ref name) = self.full_name {
+            if name
+                .chars()
+                .any(|c| c.is_control() || FULL_NAME_FORBIDDEN.contains(c))
+            {
+                return Err(format!(
+                    "full name must not contain control characters or any of {}",
+                    FULL_NAME_FORBIDDEN
+                ));
+            }
+        }
+        for group in &self.groups {
+            validate_username(group).map_err(|e| format!("group {}", e))?;
+        }
+        Ok(())
+    }
+
     /// Generate the useradd command for this user.
+    ///
+    /// Every field is shell-quoted; the command is run with `sh -c`.
     pub fn useradd_command(&self) -> String {
-        let mut cmd = format!("useradd -m -s {}", self.shell);
+        let mut cmd = format!("useradd -m -s {}", shell_quote(&self.shell));
 
         if !self.groups.is_empty() {
             cmd.push_str(" -G ");
             let groups: Vec<&str> = self.groups.iter().map(|s| s.as_str()).collect();
-            cmd.push_str(&groups.join(","));
+            cmd.push_str(&shell_quote(&groups.join(",")));
         }
 
         if let Some(ref name) = self.full_name {
-            cmd.push_str(&format!(" -c \"{}\"", name));
+            cmd.push_str(&format!(" -c {}", shell_quote(name)));
         }
 
         cmd.push(' ');
-        cmd.push_str(&self.username);
+        cmd.push_str(&shell_quote(&self.username));
         cmd
     }
 }
 
+/// Maximum length of a user or group name (shadow-utils default).
+pub const MAX_USERNAME_LEN: usize = 32;
+
+/// Names that may not be used for regular accounts.
+pub const RESERVED_USERNAMES: &[&str] = &[
+    "root", "bin", "daemon", "adm", "lp", "sync", "shutdown", "halt", "mail",
+    "operator", "games", "ftp", "nobody",
+];
+
+/// Characters not allowed in a full name: the GECOS separator and
+/// characters the shell treats specially.
+pub const FULL_NAME_FORBIDDEN: &str = ":\"'`$;&|<>()[]{}*?!\\";
+
+/// Validate a login shell: an absolute path of `[A-Za-z0-9/._+-]` without
+/// `..` components.
+pub fn validate_shell(shell: &str) -> Result<(), String> {
+    if !shell.starts_with('/') {
+        return Err(format!("shell '{}' must be an absolute path", shell));
+    }
+    let safe = shell
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._+-".contains(c));
+    if !safe || shell.split('/').any(|part| part == "..") {
+        return Err(format!(
+            "shell '{}' may only contain letters, digits, '/', '.', '_', '+' and '-'",
+            shell.escape_default()
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a user or group name.
+///
+/// Follows the portable shadow-utils rule: `[a-z_][a-z0-9_-]*`, at most
+/// `MAX_USERNAME_LEN` characters. Used for both user and group names.
+pub fn validate_username(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    if name.len() > MAX_USERNAME_LEN {
+        return Err(format!(
+            "'{}' is longer than {} characters",
+            name, MAX_USERNAME_LEN
+        ));
+    }
+    let mut chars = name.chars();
+    let first = chars.next().unwrap_or_default();
+    if !(first.is_ascii_lowercase() || first == '_') {
+        return Err(format!(
+            "'{}' must start with a lowercase letter or underscore",
+            name
+        ));
+    }
+    if !chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+        return Err(format!(
+            "'{}' may only contain lowercase letters, digits, '_' and '-'",
+            name
+        ));
+    }
+    Ok(())
+}
+
 /// Sudoers configuration.
 pub const SUDOERS_WHEEL_LINE: &str = "%wheel ALL=(ALL:ALL) ALL";
 
@@ -126,4 +218,41 @@ mod tests {
         assert!(cmd.contains("-s /bin/bash"));
         assert!(cmd.contains("alice"));
     }
+
+    #[test]
+    fn user_spec_validation() {
+        assert!(UserSpec::new("alice", "/bin/bash", &["wheel"]).validate().is_ok());
+        assert!(UserSpec::new("Alice", "/bin/bash", &[]).validate().is_err());
+        assert!(UserSpec::new("9lives", "/bin/bash", &[]).validate().is_err());
+        assert!(UserSpec::new("bob", "bash", &[]).validate().is_err());
+        assert!(UserSpec::new("bob", "/bin/ash", &["Wheel"]).validate().is_err());
+        assert!(UserSpec::new("root", "/bin/bash", &[]).validate().is_err());
+        assert!(validate_username(&"a".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn user_spec_rejects_shell_injection() {
+        for shell in [
+            "/bin/sh;reboot",
+            "/bin/$(id)",
+            "/bin/ba sh",
+            "/bin/../tmp/x",
+        ] {
+            assert!(
+                UserSpec::new("bob", shell, &[]).validate().is_err(),
+                "{}",
+                shell
+            );
+        }
+        for name in ["Bob $(id)", "Bob `id`", "Bob; reboot", "Bob\nEve", "a:b"] {
+            let user = UserSpec::new("bob", "/bin/bash", &[]).with_full_name(name);
+            assert!(user.validate().is_err(), "{:?}", name);
+        }
+        let user = UserSpec::new("bob", "/bin/bash", &[]).with_full_name("Bob Smith-Jones");
+        assert!(user.validate().is_ok());
+        assert_eq!(
+            user.useradd_command(),
+            "useradd -m -s /bin/bash -c 'Bob Smith-Jones' bob"
+        );
+    }
 }
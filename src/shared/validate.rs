@@ -0,0 +1,467 @@
+//! Cross-list consistency checks.
+//!
+//! Each list in the spec is checked on its own by unit tests, but most
+//! invariants span several lists: every binary needs a package mapping for
+//! the license bundle, enabled services need unit files, and the initramfs
+//! module names must agree with the boot module paths. `validate_spec()`
+//! checks those invariants for one variant and returns every violation as a
+//! structured `Diagnostic`, so downstream crates can run it in their own
+//! tests after extending the lists.
+//!
+//! ```rust
+//! use distro_spec::{validate_spec, Variant};
+//!
+//! for variant in Variant::ALL {
+//!     let report = validate_spec(*variant);
+//!     assert!(report.is_ok(), "{}", report);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::auth::components::{AUTH_BIN, AUTH_SBIN, PAM_CONFIGS, PAM_MODULES, SHADOW_SBIN};
+use super::boot_modules::{CORE_BOOT_MODULES, INSTALL_BOOT_MODULES, USB_BOOT_MODULES};
+use super::components::{
+    ALL_SYSTEMD_UNITS, BIN_UTILS, CRITICAL_LIBS, ESSENTIAL_UNITS, ETC_FILES, FHS_DIRS, SBIN_UTILS,
+    SYSTEMD_BINARIES, SYSTEM_GROUPS, SYSTEM_USERS,
+};
+use super::licenses::{package_for_binary, package_for_library, BINARY_TO_PACKAGE, LIB_TO_PACKAGE};
+use super::modules::{
+    module_path, INSTALL_MODULES, INSTALL_MODULES_BUILTIN, LIVE_MODULES, LIVE_MODULES_BUILTIN,
+    MODULE_PATHS,
+};
+use super::registry::{FlatList, COMPONENTS};
+use super::rootfs_manifest::Severity;
+use crate::variant::Variant;
+use crate::{acorn, levitate};
+
+// =============================================================================
+// Diagnostics
+// =============================================================================
+
+/// What is wrong with an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The entry appears more than once in its list
+    Duplicate,
+    /// The entry has no counterpart in `other`
+    Orphan { other: &'static str },
+    /// The list and `other` disagree about the entry
+    Contradiction { other: &'static str, detail: String },
+}
+
+/// One violated invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Variant being checked
+    pub variant: Variant,
+    /// How bad the violation is
+    pub severity: Severity,
+    /// List the entry comes from (e.g., "BIN_UTILS")
+    pub list: &'static str,
+    /// The offending entry
+    pub entry: String,
+    /// What is wrong with it
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {}: {}: ",
+            self.variant.os_id(),
+            self.severity,
+            self.list,
+            self.entry
+        )?;
+        match &self.kind {
+            DiagnosticKind::Duplicate => f.write_str("listed more than once"),
+            DiagnosticKind::Orphan { other } => write!(f, "missing from {}", other),
+            DiagnosticKind::Contradiction { other, detail } => {
+                write!(f, "contradicts {} ({})", other, detail)
+            }
+        }
+    }
+}
+
+/// Result of `validate_spec()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecReport {
+    /// All violations, in check order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl SpecReport {
+    /// Whether nothing worse than a warning was found.
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .all(|d| d.severity == Severity::Warning)
+    }
+
+    /// Diagnostics of at least `severity`.
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |d| d.severity >= severity)
+    }
+
+    /// Diagnostics about entries of `list`.
+    pub fn for_list<'a>(&'a self, list: &'a str) -> impl Iterator<Item = &'a Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.list == list)
+    }
+}
+
+impl fmt::Display for SpecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.diagnostics.is_empty() {
+            return f.write_str("spec is consistent");
+        }
+        for d in &self.diagnostics {
+            writeln!(f, "{}", d)?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Checks
+// =============================================================================
+
+/// Check the cross-list invariants of `variant`.
+///
+/// Duplicates are warnings; orphans and contradictions are errors, except
+/// for boot modules the live initramfs does not name, which are warnings.
+pub fn validate_spec(variant: Variant) -> SpecReport {
+    let mut c = Checker {
+        variant,
+        report: SpecReport::default(),
+    };
+    match variant {
+        Variant::Levitate => c.levitate(),
+        Variant::Acorn => c.acorn(),
+    }
+    c.report
+}
+
+struct Checker {
+    variant: Variant,
+    report: SpecReport,
+}
+
+impl Checker {
+    fn levitate(&mut self) {
+        for (name, list) in [
+            ("FHS_DIRS", FHS_DIRS),
+            ("BIN_UTILS", BIN_UTILS),
+            ("SBIN_UTILS", SBIN_UTILS),
+            ("SYSTEMD_BINARIES", SYSTEMD_BINARIES),
+            ("ESSENTIAL_UNITS", ESSENTIAL_UNITS),
+            ("ALL_SYSTEMD_UNITS", ALL_SYSTEMD_UNITS),
+            ("ETC_FILES", ETC_FILES),
+            ("CRITICAL_LIBS", CRITICAL_LIBS),
+            ("SYSTEM_USERS", SYSTEM_USERS),
+            ("SYSTEM_GROUPS", SYSTEM_GROUPS),
+            ("PAM_MODULES", PAM_MODULES),
+            ("PAM_CONFIGS", PAM_CONFIGS),
+        ] {
+            self.duplicates(name, list.iter().copied());
+        }
+        self.mappings("BINARY_TO_PACKAGE", BINARY_TO_PACKAGE);
+        self.mappings("LIB_TO_PACKAGE", LIB_TO_PACKAGE);
+        self.mappings("MODULE_PATHS", MODULE_PATHS);
+
+        // License bundle: every shipped binary and library needs a package
+        for (name, list) in [
+            ("BIN_UTILS", BIN_UTILS),
+            ("SBIN_UTILS", SBIN_UTILS),
+            ("SYSTEMD_BINARIES", SYSTEMD_BINARIES),
+            ("AUTH_BIN", AUTH_BIN),
+            ("AUTH_SBIN", AUTH_SBIN),
+            ("SHADOW_SBIN", SHADOW_SBIN),
+        ] {
+            for bin in list {
+                if package_for_binary(bin).is_none() {
+                    self.orphan(name, bin, "BINARY_TO_PACKAGE");
+                }
+            }
+        }
+        for lib in CRITICAL_LIBS {
+            let file = lib.rsplit('/').next().unwrap_or(lib);
+            if package_for_library(file).is_none() {
+                self.orphan("CRITICAL_LIBS", lib, "LIB_TO_PACKAGE");
+            }
+        }
+
+        // Units; optional units and components outside
+        // FlatList::SystemdUnits ship through their own lists
+        let separate: Vec<&str> = COMPONENTS
+            .iter()
+            .filter(|c| !c.feeds_into(FlatList::SystemdUnits))
+            .flat_map(|c| c.all_units())
+            .chain(
+                COMPONENTS
+                    .iter()
+                    .flat_map(|c| c.optional_units.iter().copied()),
+            )
+            .collect();
+        for unit in ESSENTIAL_UNITS {
+            if !ALL_SYSTEMD_UNITS.contains(unit) && !separate.contains(unit) {
+                self.orphan("ESSENTIAL_UNITS", unit, "ALL_SYSTEMD_UNITS");
+            }
+        }
+        let services: Vec<_> = levitate::ENABLED_SERVICES.iter().map(|s| s.name).collect();
+        self.duplicates("ENABLED_SERVICES", services.iter().copied());
+        for service in levitate::ENABLED_SERVICES
+            .iter()
+            .chain(levitate::FALLBACK_NETWORK_SERVICES)
+        {
+            if !ALL_SYSTEMD_UNITS.contains(&service.unit_name().as_str()) {
+                self.orphan("ENABLED_SERVICES", service.name, "ALL_SYSTEMD_UNITS");
+            }
+        }
+
+        // Initramfs modules
+        self.modules("LIVE_MODULES", LIVE_MODULES, LIVE_MODULES_BUILTIN);
+        self.modules("INSTALL_MODULES", INSTALL_MODULES, INSTALL_MODULES_BUILTIN);
+        // levitate::BOOT_MODULES re-exports CORE_BOOT_MODULES (no USB)
+        self.agree(
+            "LIVE_MODULES",
+            LIVE_MODULES,
+            "CORE_BOOT_MODULES",
+            CORE_BOOT_MODULES,
+        );
+        self.agree(
+            "INSTALL_MODULES",
+            INSTALL_MODULES,
+            "INSTALL_BOOT_MODULES",
+            INSTALL_BOOT_MODULES,
+        );
+    }
+
+    fn acorn(&mut self) {
+        let services: Vec<_> = acorn::ENABLED_SERVICES.iter().map(|s| s.name).collect();
+        self.duplicates("ENABLED_SERVICES", services.iter().copied());
+        self.duplicates(
+            "AVAILABLE_SERVICES",
+            acorn::AVAILABLE_SERVICES.iter().copied(),
+        );
+        self.subset(
+            "ENABLED_SERVICES",
+            &services,
+            "AVAILABLE_SERVICES",
+            acorn::AVAILABLE_SERVICES,
+        );
+        self.duplicates("packages", acorn::all_live_packages().into_iter());
+
+        self.duplicates("BOOT_MODULES", acorn::BOOT_MODULES.iter().copied());
+        // BOOT_MODULES is a hand-written copy of CORE_BOOT_MODULES + USB_BOOT_MODULES
+        for (list, group) in [
+            ("CORE_BOOT_MODULES", CORE_BOOT_MODULES),
+            ("USB_BOOT_MODULES", USB_BOOT_MODULES),
+        ] {
+            let entries = group.iter().map(|m| (*m, *m));
+            self.in_order(list, entries, "BOOT_MODULES", acorn::BOOT_MODULES);
+        }
+        self.mappings("MODULE_PATHS", MODULE_PATHS);
+        self.modules("LIVE_MODULES", LIVE_MODULES, LIVE_MODULES_BUILTIN);
+        self.agree(
+            "LIVE_MODULES",
+            LIVE_MODULES,
+            "CORE_BOOT_MODULES",
+            CORE_BOOT_MODULES,
+        );
+    }
+
+    fn push(&mut self, severity: Severity, list: &'static str, entry: &str, kind: DiagnosticKind) {
+        self.report.diagnostics.push(Diagnostic {
+            variant: self.variant,
+            severity,
+            list,
+            entry: entry.to_string(),
+            kind,
+        });
+    }
+
+    fn orphan(&mut self, list: &'static str, entry: &str, other: &'static str) {
+        self.push(
+            Severity::Error,
+            list,
+            entry,
+            DiagnosticKind::Orphan { other },
+        );
+    }
+
+    fn contradiction(
+        &mut self,
+        list: &'static str,
+        entry: &str,
+        other: &'static str,
+        detail: String,
+    ) {
+        self.push(
+            Severity::Error,
+            list,
+            entry,
+            DiagnosticKind::Contradiction { other, detail },
+        );
+    }
+
+    /// Entries listed more than once (reported once each).
+    fn duplicates<'a>(&mut self, list: &'static str, entries: impl Iterator<Item = &'a str>) {
+        let mut seen = HashMap::new();
+        for entry in entries {
+            let count = seen.entry(entry).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                self.push(Severity::Warning, list, entry, DiagnosticKind::Duplicate);
+            }
+        }
+    }
+
+    /// Repeated keys: a warning if they agree, an error if they don't.
+    fn mappings(&mut self, list: &'static str, pairs: &[(&str, &str)]) {
+        let mut first: HashMap<&str, &str> = HashMap::new();
+        let mut reported = Vec::new();
+        for (key, value) in pairs {
+            let Some(prev) = first.get(key).copied() else {
+                first.insert(key, value);
+                continue;
+            };
+            if reported.contains(key) {
+                continue;
+            }
+            reported.push(*key);
+            if prev == *value {
+                self.push(Severity::Warning, list, key, DiagnosticKind::Duplicate);
+            } else {
+                self.contradiction(
+                    list,
+                    key,
+                    list,
+                    format!("maps to both {} and {}", prev, value),
+                );
+            }
+        }
+    }
+
+    /// Every entry of `list` must be in `other`.
+    fn subset(&mut self, list: &'static str, entries: &[&str], other: &'static str, of: &[&str]) {
+        for entry in entries {
+            if !of.contains(entry) {
+                self.orphan(list, entry, other);
+            }
+        }
+    }
+
+    /// Loadable modules need a `MODULE_PATHS` entry.
+    fn modules(&mut self, list: &'static str, names: &[&str], builtin: &[&str]) {
+        self.duplicates(list, names.iter().copied());
+        for name in names {
+            if !builtin.contains(name) && module_path(name).is_none() {
+                self.orphan(list, name, "MODULE_PATHS");
+            }
+        }
+    }
+
+    /// A module-name list and a module-path list name the same modules in
+    /// the same (dependency) order.
+    ///
+    /// Names missing from the paths are errors; paths the names don't mention
+    /// are warnings, since the boot lists may carry extra drivers.
+    fn agree(&mut self, list: &'static str, names: &[&str], other: &'static str, paths: &[&str]) {
+        let entries = names
+            .iter()
+            .filter_map(|name| module_path(name).map(|path| (*name, path)));
+        self.in_order(list, entries, other, paths);
+        for path in paths {
+            if !names.iter().any(|n| module_path(n) == Some(*path)) {
+                self.push(
+                    Severity::Warning,
+                    other,
+                    path,
+                    DiagnosticKind::Orphan { other: list },
+                );
+            }
+        }
+    }
+
+    /// Every `(entry, key)` has its key in `of`, in the same relative order.
+    fn in_order<'a>(
+        &mut self,
+        list: &'static str,
+        entries: impl Iterator<Item = (&'a str, &'a str)>,
+        other: &'static str,
+        of: &[&str],
+    ) {
+        let mut last: Option<(usize, &str)> = None;
+        for (entry, key) in entries {
+            let Some(pos) = of.iter().position(|k| *k == key) else {
+                self.orphan(list, entry, other);
+                continue;
+            };
+            if let Some((prev_pos, prev)) = last {
+                if pos < prev_pos {
+                    let detail = format!("listed after {} here, before it there", prev);
+                    self.contradiction(list, entry, other, detail);
+                }
+            }
+            last = Some((pos, entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_spec_is_consistent() {
+        for variant in Variant::ALL {
+            let report = validate_spec(*variant);
+            assert!(report.is_ok(), "{}", report);
+        }
+    }
+
+    #[test]
+    fn test_reports_problems() {
+        let mut c = Checker {
+            variant: Variant::Levitate,
+            report: SpecReport::default(),
+        };
+        c.duplicates("L", ["a", "b", "a", "a"].into_iter());
+        c.mappings("M", &[("x", "1"), ("x", "2"), ("y", "1"), ("y", "1")]);
+        c.agree(
+            "NAMES",
+            &["nvme", "nvme-core", "nonexistent"],
+            "PATHS",
+            &[
+                "kernel/drivers/nvme/host/nvme-core",
+                "kernel/drivers/nvme/host/nvme",
+                "kernel/fs/ext4/ext4",
+            ],
+        );
+        let r = c.report;
+
+        assert_eq!(r.for_list("L").count(), 1);
+        assert_eq!(
+            r.for_list("L").next().unwrap().kind,
+            DiagnosticKind::Duplicate
+        );
+        let m: Vec<_> = r.for_list("M").collect();
+        assert!(matches!(m[0].kind, DiagnosticKind::Contradiction { .. }));
+        assert_eq!(m[1].severity, Severity::Warning);
+        let names: Vec<_> = r.for_list("NAMES").collect();
+        assert_eq!(names.len(), 1);
+        assert_eq!(names[0].entry, "nvme-core");
+        assert!(r
+            .for_list("PATHS")
+            .any(|d| d.entry == "kernel/fs/ext4/ext4"));
+        assert!(!r.is_ok());
+        assert!(r
+            .to_string()
+            .contains("M: x: contradicts M (maps to both 1 and 2)"));
+    }
+}
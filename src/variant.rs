@@ -0,0 +1,265 @@
+//! Distro variant selection.
+//!
+//! Most of the spec is exposed as per-variant constants (`levitate::*`,
+//! `acorn::*`). Code that has to work for either variant at runtime (installers,
+//! answer files, test harnesses) uses `Variant` to pick the right set without
+//! matching on the OS name everywhere.
+
+use crate::shared::boot::{BootEntry, LoaderConfig};
+use crate::shared::components::ALL_SYSTEMD_UNITS;
+use crate::shared::kernels::{KernelVersion, VersionedBootFiles};
+use crate::shared::profile::Profile;
+use crate::shared::requirements::{SystemRequirements, ACORN_REQUIREMENTS, LEVITATE_REQUIREMENTS};
+use crate::shared::services::ServiceManager;
+use crate::shared::users::UserSpec;
+use crate::{acorn, levitate};
+
+/// A supported distro variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// LevitateOS: Rocky Linux, systemd, glibc, GNU coreutils
+    Levitate,
+    /// AcornOS: Alpine Linux, OpenRC, musl, busybox
+    Acorn,
+}
+
+/// Init system used by a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitSystem {
+    Systemd,
+    OpenRc,
+}
+
+impl Variant {
+    /// All known variants.
+    pub const ALL: &'static [Variant] = &[Variant::Levitate, Variant::Acorn];
+
+    /// Parse a variant from its OS ID (e.g., "levitateos") or short name ("levitate").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "levitate" | "levitateos" => Some(Variant::Levitate),
+            "acorn" | "acornos" => Some(Variant::Acorn),
+            _ => None,
+        }
+    }
+
+    /// OS identifier (lowercase, used in filenames and entry names).
+    pub fn os_id(&self) -> &'static str {
+        match self {
+            Variant::Levitate => levitate::OS_ID,
+            Variant::Acorn => acorn::OS_ID,
+        }
+    }
+
+    /// Human-readable OS name.
+    pub fn os_name(&self) -> &'static str {
+        match self {
+            Variant::Levitate => levitate::OS_NAME,
+            Variant::Acorn => acorn::OS_NAME,
+        }
+    }
+
+    /// ISO volume label.
+    pub fn iso_label(&self) -> &'static str {
+        match self {
+            Variant::Levitate => levitate::ISO_LABEL,
+            Variant::Acorn => acorn::ISO_LABEL,
+        }
+    }
+
+    /// Default hostname for fresh installations.
+    pub fn default_hostname(&self) -> &'static str {
+        match self {
+            Variant::Levitate => levitate::DEFAULT_HOSTNAME,
+            Variant::Acorn => acorn::DEFAULT_HOSTNAME,
+        }
+    }
+
+    /// Init system used by this variant.
+    pub fn init_system(&self) -> InitSystem {
+        match self {
+            Variant::Levitate => InitSystem::Systemd,
+            Variant::Acorn => InitSystem::OpenRc,
+        }
+    }
+
+    /// Hardware requirements for this variant.
+    pub fn requirements(&self) -> &'static SystemRequirements {
+        match self {
+            Variant::Levitate => &LEVITATE_REQUIREMENTS,
+            Variant::Acorn => &ACORN_REQUIREMENTS,
+        }
+    }
+
+    /// Services enabled on a fresh installation (default profile).
+    pub fn enabled_services(&self) -> Vec<&'static dyn ServiceManager> {
+        self.profile_services(Profile::default())
+    }
+
+    /// Services enabled on a fresh installation with `profile`.
+    pub fn profile_services(&self, profile: Profile) -> Vec<&'static dyn ServiceManager> {
+        match self {
+            Variant::Levitate => levitate::profile_services(profile)
+                .map(|s| s as &dyn ServiceManager)
+                .collect(),
+            Variant::Acorn => acorn::profile_services(profile)
+                .map(|s| s as &dyn ServiceManager)
+                .collect(),
+        }
+    }
+
+    /// Whether `name` is a service shipped by this variant.
+    ///
+    /// LevitateOS knows every `.service` unit in `ALL_SYSTEMD_UNITS`;
+    /// AcornOS knows the OpenRC scripts in `acorn::AVAILABLE_SERVICES`.
+    pub fn is_known_service(&self, name: &str) -> bool {
+        match self {
+            Variant::Levitate => {
+                let unit = format!("{}.service", name.trim_end_matches(".service"));
+                levitate::ENABLED_SERVICES
+                    .iter()
+                    .any(|s| s.unit_name() == unit)
+                    || ALL_SYSTEMD_UNITS.contains(&unit.as_str())
+            }
+            Variant::Acorn => acorn::AVAILABLE_SERVICES.contains(&name),
+        }
+    }
+
+    /// Command to enable a service by name on this variant.
+    pub fn enable_service_command(&self, name: &str) -> String {
+        match self.init_system() {
+            InitSystem::Systemd => format!("systemctl enable {}", name),
+            InitSystem::OpenRc => format!("rc-update add {} default", name),
+        }
+    }
+
+    /// Kernel command line options that unlock the LUKS volume `uuid` as
+    /// `/dev/mapper/<mapper>` in the initramfs.
+    ///
+    /// dracut reads `rd.luks.name`; Alpine's mkinitfs reads `cryptroot` and
+    /// `cryptdm` instead.
+    pub fn luks_root_options(&self, uuid: &str, mapper: &str) -> String {
+        match self.init_system() {
+            InitSystem::Systemd => format!("rd.luks.name={}={}", uuid, mapper),
+            InitSystem::OpenRc => format!("cryptroot=UUID={} cryptdm={}", uuid, mapper),
+        }
+    }
+
+    /// Filenames of the live UKIs (in `EFI/Linux` on the ISO).
+    pub fn live_uki_filenames(&self) -> Vec<&'static str> {
+        match self {
+            Variant::Levitate => levitate::UKI_ENTRIES.iter().map(|e| e.filename).collect(),
+            Variant::Acorn => acorn::UKI_ENTRIES.iter().map(|e| e.filename).collect(),
+        }
+    }
+
+    /// Filenames of the pre-built UKIs for installed systems (in `boot/uki` on the ISO).
+    pub fn installed_uki_filenames(&self) -> Vec<&'static str> {
+        match self {
+            Variant::Levitate => levitate::UKI_INSTALLED_ENTRIES
+                .iter()
+                .map(|e| e.filename)
+                .collect(),
+            Variant::Acorn => acorn::UKI_INSTALLED_ENTRIES
+                .iter()
+                .map(|e| e.filename)
+                .collect(),
+        }
+    }
+
+    /// Versioned kernel, initramfs and UKI names for an installed kernel.
+    pub fn boot_files(&self, version: KernelVersion) -> VersionedBootFiles {
+        VersionedBootFiles::new(self.os_id(), version)
+    }
+
+    /// Default login shell for new users.
+    pub fn default_shell(&self) -> &'static str {
+        match self {
+            Variant::Levitate => levitate::DEFAULT_SHELL,
+            Variant::Acorn => acorn::DEFAULT_SHELL,
+        }
+    }
+
+    /// Create a UserSpec with this variant's default shell and groups.
+    pub fn default_user(&self, username: impl Into<String>) -> UserSpec {
+        match self {
+            Variant::Levitate => levitate::default_user(username),
+            Variant::Acorn => acorn::default_user(username),
+        }
+    }
+
+    /// Boot entry for an installed system using the given root device.
+    pub fn boot_entry_with_root(&self, root_device: impl Into<String>) -> BootEntry {
+        match self {
+            Variant::Levitate => levitate::boot_entry_with_root(root_device),
+            Variant::Acorn => acorn::boot_entry_with_root(root_device),
+        }
+    }
+
+    /// Default loader.conf for an installed system.
+    pub fn default_loader_config(&self) -> LoaderConfig {
+        match self {
+            Variant::Levitate => levitate::default_loader_config(),
+            Variant::Acorn => acorn::default_loader_config(),
+        }
+    }
+
+    /// Script the installer writes to bless counted boot entries, as
+    /// (absolute path, contents). LevitateOS uses systemd's own units.
+    pub fn bless_boot_script(&self) -> Option<(&'static str, String)> {
+        match self {
+            Variant::Levitate => None,
+            Variant::Acorn => Some((
+                acorn::BLESS_BOOT_SCRIPT_PATH,
+                acorn::bless_boot_init_script(),
+            )),
+        }
+    }
+
+    /// Commands to run in the chroot to turn on boot assessment.
+    pub fn boot_assessment_commands(&self) -> Vec<String> {
+        match self {
+            Variant::Levitate => levitate::boot_assessment_enable_commands(),
+            Variant::Acorn => vec![acorn::bless_boot_enable_command()],
+        }
+    }
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.os_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Variant::from_name("levitate"), Some(Variant::Levitate));
+        assert_eq!(Variant::from_name("AcornOS"), Some(Variant::Acorn));
+        assert_eq!(Variant::from_name("fedora"), None);
+    }
+
+    #[test]
+    fn test_variant_identity_matches_modules() {
+        assert_eq!(Variant::Levitate.os_id(), levitate::OS_ID);
+        assert_eq!(Variant::Acorn.iso_label(), acorn::ISO_LABEL);
+        assert_eq!(Variant::Acorn.init_system(), InitSystem::OpenRc);
+        assert_eq!(
+            Variant::Levitate.enabled_services().len(),
+            levitate::ENABLED_SERVICES.len()
+        );
+        assert_eq!(Variant::Acorn.profile_services(Profile::Minimal).len(), 1);
+    }
+
+    #[test]
+    fn test_known_services() {
+        assert!(Variant::Levitate.is_known_service("sshd"));
+        assert!(Variant::Levitate.is_known_service("bluetooth.service"));
+        assert!(!Variant::Levitate.is_known_service("nonexistent"));
+        assert!(Variant::Acorn.is_known_service("dhcpcd"));
+        assert!(!Variant::Acorn.is_known_service("NetworkManager"));
+    }
+}